@@ -0,0 +1,63 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+#[cfg(feature = "std")]
+use std::{fs, io, path::Path};
+
+#[derive(Debug, Default)]
+pub struct Animation {
+    frames: Vec<String>,
+}
+
+impl Animation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_frame(&mut self, frame: impl Into<String>) {
+        self.frames.push(frame.into());
+    }
+
+    pub fn frames(&self) -> &[String] {
+        &self.frames
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            writeln!(text, "Frame {}", i + 1).unwrap();
+            text.push_str(frame);
+            if !frame.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_text() {
+        let mut animation = Animation::new();
+        animation.push_frame("#.\n..\n");
+        animation.push_frame("##\n..");
+
+        assert_eq!(animation.frames().len(), 2);
+        assert_eq!(
+            animation.to_text(),
+            "Frame 1\n#.\n..\n\nFrame 2\n##\n..\n\n"
+        );
+    }
+}
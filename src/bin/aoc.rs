@@ -0,0 +1,110 @@
+use std::{env, io, path::Path};
+
+#[cfg(feature = "encrypted-inputs")]
+use std::fs;
+
+#[cfg(feature = "encrypted-inputs")]
+use aoc::encryption;
+use aoc::puzzles::{self, Status};
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum AocError {
+    IoError(io::Error),
+    MissingCommand,
+    UnknownCommand(String),
+    FeatureDisabled(&'static str),
+}
+
+impl From<io::Error> for AocError {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+#[cfg(feature = "encrypted-inputs")]
+const INPUTS_DIRECTORY: &str = "inputs";
+
+fn main() -> Result<(), AocError> {
+    let command = env::args().nth(1).ok_or(AocError::MissingCommand)?;
+
+    match command.as_str() {
+        "encrypt-inputs" => encrypt_inputs(),
+        "list" => {
+            print!("{}", list(Path::new(".")));
+            Ok(())
+        }
+        _ => Err(AocError::UnknownCommand(command)),
+    }
+}
+
+#[cfg(feature = "encrypted-inputs")]
+fn encrypt_inputs() -> Result<(), AocError> {
+    let passphrase = encryption::passphrase()?;
+
+    let mut paths: Vec<_> = fs::read_dir(INPUTS_DIRECTORY)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+
+    for path in paths {
+        if path.extension().is_some_and(|extension| extension == "txt") {
+            let encrypted = encryption::encrypt_file(&path, &passphrase)?;
+            println!("{} -> {}", path.display(), encrypted.display());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "encrypted-inputs"))]
+fn encrypt_inputs() -> Result<(), AocError> {
+    Err(AocError::FeatureDisabled("encrypted-inputs"))
+}
+
+fn list(root: &Path) -> String {
+    let yes_no = |b| if b { "yes" } else { "no" };
+
+    let mut output = format!(
+        "{:>3}  {:<26}  {:<6}  {:<5}  {}\n",
+        "Day", "Title", "Solver", "Input", "Verified"
+    );
+
+    for day in 1..=puzzles::TITLES.len() {
+        let Some(Status {
+            title,
+            implemented,
+            input,
+            verified_answers,
+            ..
+        }) = puzzles::status(root, day)
+        else {
+            continue;
+        };
+
+        output += &format!(
+            "{:>3}  {:<26}  {:<6}  {:<5}  {}/2\n",
+            day,
+            title,
+            yes_no(implemented),
+            yes_no(input),
+            verified_answers.min(2)
+        );
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list() {
+        let listing = list(Path::new(env!("CARGO_MANIFEST_DIR")));
+        let lines: Vec<_> = listing.lines().collect();
+
+        assert_eq!(lines.len(), 26);
+        assert!(lines[1].starts_with("  1  Calorie Counting            yes"));
+    }
+}
@@ -1,6 +1,6 @@
 use std::{io, num::ParseIntError};
 
-use aoc::read_lines;
+use aoc::{puzzles::part_label, read_lines};
 
 #[derive(Debug)]
 enum Day1Error {
@@ -26,8 +26,8 @@ const INPUT_PATH: &str = "inputs/day1.txt";
 fn main() -> Result<(), Day1Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(1, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(1, 2), part2(&input)?);
 
     Ok(())
 }
@@ -1,12 +1,14 @@
-use std::{collections::VecDeque, io, num::ParseIntError, str::FromStr};
+use std::{io, num::ParseIntError, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{animation::Animation, cli::Args, puzzles::part_label, read_lines};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day10Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
     InvalidInstruction,
+    UnsupportedInstruction,
 }
 
 impl From<io::Error> for Day10Error {
@@ -24,42 +26,96 @@ impl From<ParseIntError> for Day10Error {
 const INPUT_PATH: &str = "inputs/day10.txt";
 
 fn main() -> Result<(), Day10Error> {
+    let args = Args::from_env();
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: \n{}", part2(&input)?);
+    let isa = if args.flag("--extended") {
+        Isa::Extended
+    } else {
+        Isa::Puzzle
+    };
+
+    let default_sampling = Sampling::default();
+    let sampling = Sampling {
+        offset: args.parse("--offset")?.unwrap_or(default_sampling.offset),
+        stride: args.parse("--stride")?.unwrap_or(default_sampling.stride),
+        limit: args.parse("--limit")?.unwrap_or(default_sampling.limit),
+    };
+
+    println!("{}: {:?}", part_label(10, 1), part1(&input, isa, sampling)?);
+    println!("{}: \n{}", part_label(10, 2), part2(&input, isa)?);
+
+    if let Some(path) = args.value("--animate") {
+        animate_crt(&input, isa)?.save(path)?;
+    }
 
     Ok(())
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Isa {
+    Puzzle,
+    Extended,
+}
+
+#[derive(Debug, Clone, Copy)]
 enum Instruction {
     Noop,
     AddX(isize),
+    AddY(isize),
+    Jump(isize),
+    NoopFor(usize),
 }
 
 impl FromStr for Instruction {
     type Err = Day10Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v: Vec<_> = s.split(" ").collect();
+        let v: Vec<_> = s.split(' ').collect();
         match v.as_slice() {
             ["noop"] => Ok(Self::Noop),
             ["addx", value] => Ok(Self::AddX(value.parse()?)),
+            ["addy", value] => Ok(Self::AddY(value.parse()?)),
+            ["jmp", offset] => Ok(Self::Jump(offset.parse()?)),
+            ["nop", cycles] => match cycles.parse()? {
+                0 => Err(Self::Err::InvalidInstruction),
+                cycles => Ok(Self::NoopFor(cycles)),
+            },
             _ => Err(Self::Err::InvalidInstruction),
         }
     }
 }
 
-fn parse_instructions(input: &Vec<String>) -> Result<VecDeque<Instruction>, Day10Error> {
-    input.iter().map(|line| line.parse()).collect()
+fn parse_instructions(input: &[String], isa: Isa) -> Result<Vec<Instruction>, Day10Error> {
+    input
+        .iter()
+        .map(|line| {
+            let instruction: Instruction = line.parse()?;
+
+            if isa == Isa::Puzzle && instruction.is_extended() {
+                Err(Day10Error::UnsupportedInstruction)
+            } else {
+                Ok(instruction)
+            }
+        })
+        .collect()
 }
 
 impl Instruction {
+    fn is_extended(&self) -> bool {
+        matches!(
+            self,
+            Instruction::AddY(_) | Instruction::Jump(_) | Instruction::NoopFor(_)
+        )
+    }
+
     fn cycles_to_complete(&self) -> usize {
         match self {
             Instruction::Noop => 1,
             Instruction::AddX(_) => 2,
+            Instruction::AddY(_) => 2,
+            Instruction::Jump(_) => 1,
+            Instruction::NoopFor(cycles) => *cycles,
         }
     }
 
@@ -73,6 +129,8 @@ impl Instruction {
 struct State {
     cycle_number: usize,
     x_register_value: isize,
+    y_register_value: isize,
+    instruction_pointer: usize,
     in_progress: Option<(Instruction, usize)>,
 }
 
@@ -81,19 +139,23 @@ impl State {
         Self {
             cycle_number: 0,
             x_register_value: 1,
+            y_register_value: 0,
+            instruction_pointer: 0,
             in_progress: None,
         }
     }
 
-    fn begin_tick(mut self, instructions: &mut VecDeque<Instruction>) -> Self {
+    fn is_running(&self, program: &[Instruction]) -> bool {
+        self.instruction_pointer < program.len()
+    }
+
+    fn begin_tick(mut self, program: &[Instruction]) -> Self {
         self.cycle_number += 1;
 
-        match self.in_progress {
-            Some(_) => {}
-            None => {
-                self.in_progress = instructions
-                    .pop_front()
-                    .map(Instruction::with_cycles_to_complete)
+        if self.in_progress.is_none() {
+            if let Some(instruction) = program.get(self.instruction_pointer) {
+                self.in_progress = Some(instruction.with_cycles_to_complete());
+                self.instruction_pointer += 1;
             }
         }
 
@@ -101,20 +163,24 @@ impl State {
     }
 
     fn end_tick(mut self) -> Self {
-        match self.in_progress {
-            Some((instruction, cycles_left)) => {
-                if cycles_left <= 1 {
-                    match instruction {
-                        Instruction::Noop => {}
-                        Instruction::AddX(value) => self.x_register_value += value,
+        if let Some((instruction, cycles_left)) = self.in_progress {
+            if cycles_left <= 1 {
+                match instruction {
+                    Instruction::Noop | Instruction::NoopFor(_) => {}
+                    Instruction::AddX(value) => self.x_register_value += value,
+                    Instruction::AddY(value) => self.y_register_value += value,
+                    Instruction::Jump(offset) => {
+                        // Jumps complete in the cycle they were fetched, so the pointer is just past them
+                        self.instruction_pointer = (self.instruction_pointer - 1)
+                            .checked_add_signed(offset)
+                            .unwrap_or(usize::MAX)
                     }
-
-                    self.in_progress = None;
-                } else {
-                    self.in_progress = Some((instruction, cycles_left - 1));
                 }
+
+                self.in_progress = None;
+            } else {
+                self.in_progress = Some((instruction, cycles_left - 1));
             }
-            None => {}
         }
 
         self
@@ -125,22 +191,58 @@ impl State {
     }
 }
 
-fn part1(input: &Vec<String>) -> Result<isize, Day10Error> {
-    let mut instructions = parse_instructions(input)?;
-
+fn run(program: &[Instruction], mut on_cycle: impl FnMut(&State)) -> State {
     let mut state = State::new();
-    let mut total_signal_strength = 0;
 
-    while !instructions.is_empty() {
-        state = state.begin_tick(&mut instructions);
+    while state.is_running(program) {
+        state = state.begin_tick(program);
 
-        if state.cycle_number % 40 == 20 && state.cycle_number <= 220 {
-            total_signal_strength += state.signal_strength();
-        }
+        on_cycle(&state);
 
         state = state.end_tick();
     }
 
+    state
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sampling {
+    offset: usize,
+    stride: usize,
+    limit: usize,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self {
+            offset: 20,
+            stride: 40,
+            limit: 220,
+        }
+    }
+}
+
+impl Sampling {
+    fn includes(&self, cycle_number: usize) -> bool {
+        if cycle_number < self.offset || cycle_number > self.limit {
+            return false;
+        }
+
+        (cycle_number - self.offset).is_multiple_of(self.stride)
+    }
+}
+
+fn part1(input: &[String], isa: Isa, sampling: Sampling) -> Result<isize, Day10Error> {
+    let program = parse_instructions(input, isa)?;
+
+    let mut total_signal_strength = 0;
+
+    run(&program, |state| {
+        if sampling.includes(state.cycle_number) {
+            total_signal_strength += state.signal_strength();
+        }
+    });
+
     Ok(total_signal_strength)
 }
 
@@ -159,7 +261,7 @@ impl Crt {
         }
     }
 
-    fn update(mut self, state: &State) -> Self {
+    fn update(&mut self, state: &State) {
         let index = state.cycle_number - 1;
         let is_lit = state
             .x_register_value
@@ -167,8 +269,6 @@ impl Crt {
             <= 1;
 
         self.display[index % CRT_AREA] = is_lit;
-
-        self
     }
 
     fn to_str(&self) -> String {
@@ -184,27 +284,63 @@ impl Crt {
     }
 }
 
-fn part2(input: &Vec<String>) -> Result<String, Day10Error> {
-    let mut instructions = parse_instructions(input)?;
-
-    let mut state = State::new();
+fn draw_crt(program: &[Instruction], mut on_frame: impl FnMut(&Crt)) -> Crt {
     let mut crt = Crt::new();
 
-    while !instructions.is_empty() {
-        state = state.begin_tick(&mut instructions);
+    run(program, |state| {
+        crt.update(state);
+        on_frame(&crt);
+    });
+
+    crt
+}
 
-        crt = crt.update(&state);
+fn part2(input: &[String], isa: Isa) -> Result<String, Day10Error> {
+    let program = parse_instructions(input, isa)?;
 
-        state = state.end_tick();
-    }
+    Ok(draw_crt(&program, |_| {}).to_str())
+}
+
+fn animate_crt(input: &[String], isa: Isa) -> Result<Animation, Day10Error> {
+    let program = parse_instructions(input, isa)?;
 
-    Ok(crt.to_str())
+    let mut animation = Animation::new();
+    draw_crt(&program, |crt| animation.push_frame(crt.to_str()));
+
+    Ok(animation)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extended_instructions() {
+        let input: Vec<String> = "\
+addx 3
+jmp +2
+addx 100
+addy 5
+nop 3
+noop
+"
+        .lines()
+        .map(|s| s.to_owned())
+        .collect();
+
+        assert!(matches!(
+            parse_instructions(&input, Isa::Puzzle),
+            Err(Day10Error::UnsupportedInstruction)
+        ));
+
+        let program = parse_instructions(&input, Isa::Extended).unwrap();
+        let state = run(&program, |_| {});
+
+        assert_eq!(state.cycle_number, 9);
+        assert_eq!(state.x_register_value, 4);
+        assert_eq!(state.y_register_value, 5);
+    }
+
     #[test]
     fn test_part1() {
         let input: Vec<String> = "\
@@ -359,7 +495,17 @@ noop
         .map(|s| s.to_owned())
         .collect();
 
-        assert_eq!(part1(&input).unwrap(), 13140);
+        assert_eq!(
+            part1(&input, Isa::Puzzle, Sampling::default()).unwrap(),
+            13140
+        );
+
+        let sampling = Sampling {
+            offset: 60,
+            stride: 80,
+            limit: 220,
+        };
+        assert_eq!(part1(&input, Isa::Puzzle, sampling).unwrap(), 8040);
     }
 
     #[test]
@@ -517,7 +663,7 @@ noop
         .collect();
 
         assert_eq!(
-            part2(&input).unwrap(),
+            part2(&input, Isa::Puzzle).unwrap(),
             String::from(
                 "\
 ##..##..##..##..##..##..##..##..##..##..
@@ -529,5 +675,13 @@ noop
 "
             )
         );
+
+        let animation = animate_crt(&input, Isa::Puzzle).unwrap();
+
+        assert_eq!(animation.frames().len(), CRT_AREA);
+        assert_eq!(
+            animation.frames().last(),
+            Some(&part2(&input, Isa::Puzzle).unwrap())
+        );
     }
 }
@@ -1,13 +1,29 @@
 use std::{collections::VecDeque, io, num::ParseIntError, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{cli::Args, cycle::CycleDetector, puzzles::part_label, read_lines, top_k};
+#[cfg(feature = "num-bigint")]
+use num_bigint::BigUint;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day11Error {
     IoError(io::Error),
+    #[cfg(feature = "serde")]
+    JsonError(serde_json::Error),
     ParseIntError(ParseIntError),
-    InvalidMonkeyOperation,
+    InvalidMonkeyOperation(String),
+    DivisionByZero,
+    Overflow,
     InvalidMonkeyFormat,
+    InvalidReliefDivisor,
+    NotEnoughMonkeys,
+    InvalidThrowTarget {
+        monkey: usize,
+        target: usize,
+        monkeys: usize,
+    },
 }
 
 impl From<io::Error> for Day11Error {
@@ -16,6 +32,13 @@ impl From<io::Error> for Day11Error {
     }
 }
 
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for Day11Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::JsonError(e)
+    }
+}
+
 impl From<ParseIntError> for Day11Error {
     fn from(e: ParseIntError) -> Self {
         Self::ParseIntError(e)
@@ -25,48 +48,262 @@ impl From<ParseIntError> for Day11Error {
 const INPUT_PATH: &str = "inputs/day11.txt";
 
 fn main() -> Result<(), Day11Error> {
+    let args = Args::from_env();
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(11, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(11, 2), part2(&input)?);
+
+    let simulation = Simulation {
+        relief_divisor: args.parse("--relief")?.unwrap_or(PART2.relief_divisor),
+        rounds: args.parse("--rounds")?.unwrap_or(PART2.rounds),
+        top_monkeys: args.parse("--top")?.unwrap_or(PART2.top_monkeys),
+    };
+
+    if args.flag("--relief") || args.flag("--rounds") || args.flag("--top") {
+        match simulate_extrapolated(&input, simulation) {
+            Ok(monkey_business) => println!(
+                "Monkey business of top {} after {} rounds with relief divisor {}: {:?}",
+                simulation.top_monkeys,
+                simulation.rounds,
+                simulation.relief_divisor,
+                monkey_business
+            ),
+            // Without a modulo nothing keeps worry levels small.
+            Err(Day11Error::Overflow) => eprintln!(
+                "Worry levels overflowed with relief divisor {}; use --exact to track them exactly",
+                simulation.relief_divisor
+            ),
+            Err(e) => return Err(e),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    if let Some(path) = args.value("--export") {
+        std::fs::write(path, round_snapshots(&input, simulation)?)?;
+    }
+    #[cfg(not(feature = "serde"))]
+    if args.flag("--export") {
+        eprintln!("--export requires the serde feature");
+    }
+
+    if args.flag("--log") {
+        print!("{}", inspection_log(&input, simulation)?);
+    }
+
+    #[cfg(feature = "num-bigint")]
+    if args.flag("--exact") {
+        let (monkey_business, items) = exact_rounds(&input, simulation)?;
+
+        println!(
+            "Exact monkey business after {} rounds: {}",
+            simulation.rounds, monkey_business
+        );
+        for (i, items) in items.iter().enumerate() {
+            let items: Vec<_> = items.iter().map(|item| item.to_string()).collect();
+            println!("Monkey {}: {}", i, items.join(", "));
+        }
+    }
+    #[cfg(not(feature = "num-bigint"))]
+    if args.flag("--exact") {
+        eprintln!("--exact requires the num-bigint feature");
+    }
 
     Ok(())
 }
 
+// The right-hand side of a monkey's operation, in terms of the old worry level.
 #[derive(PartialEq, Eq, Debug)]
-enum MonkeyOperation {
-    Add(usize),
-    Multiply(usize),
-    Square,
+#[cfg_attr(feature = "serde", derive(Serialize))]
+enum Expression {
+    Old,
+    Constant(usize),
+    Add(Box<Expression>, Box<Expression>),
+    Subtract(Box<Expression>, Box<Expression>),
+    Multiply(Box<Expression>, Box<Expression>),
+    Divide(Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Old,
+    Number(usize),
+    Operator(char),
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => {}
+            '+' | '-' | '*' | '/' => tokens.push(Token::Operator(c)),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '0'..='9' => {
+                let mut number = c.to_digit(10)? as usize;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    number = number.checked_mul(10)?.checked_add(digit as usize)?;
+                    chars.next();
+                }
+
+                tokens.push(Token::Number(number));
+            }
+            'o' => {
+                (chars.next()? == 'l' && chars.next()? == 'd').then_some(())?;
+                tokens.push(Token::Old);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(tokens)
+}
+
+// Usual precedence: `*` and `/` bind tighter than `+` and `-`, and all four
+// associate to the left.
+fn parse_sum(tokens: &mut &[Token]) -> Option<Expression> {
+    let mut left = parse_product(tokens)?;
+
+    while let Some(&Token::Operator(operator @ ('+' | '-'))) = tokens.first() {
+        *tokens = &tokens[1..];
+        let right = parse_product(tokens)?;
+
+        left = match operator {
+            '+' => Expression::Add(Box::new(left), Box::new(right)),
+            _ => Expression::Subtract(Box::new(left), Box::new(right)),
+        };
+    }
+
+    Some(left)
+}
+
+fn parse_product(tokens: &mut &[Token]) -> Option<Expression> {
+    let mut left = parse_factor(tokens)?;
+
+    while let Some(&Token::Operator(operator @ ('*' | '/'))) = tokens.first() {
+        *tokens = &tokens[1..];
+        let right = parse_factor(tokens)?;
+
+        left = match (operator, right) {
+            ('*', right) => Expression::Multiply(Box::new(left), Box::new(right)),
+            // A literal zero is rejected up front, other divisors when evaluated.
+            (_, Expression::Constant(0)) => return None,
+            (_, right) => Expression::Divide(Box::new(left), Box::new(right)),
+        };
+    }
+
+    Some(left)
 }
 
-impl FromStr for MonkeyOperation {
+fn parse_factor(tokens: &mut &[Token]) -> Option<Expression> {
+    let (token, rest) = tokens.split_first()?;
+    *tokens = rest;
+
+    match token {
+        Token::Old => Some(Expression::Old),
+        Token::Number(n) => Some(Expression::Constant(*n)),
+        Token::Open => {
+            let expression = parse_sum(tokens)?;
+
+            let (Token::Close, rest) = tokens.split_first()? else {
+                return None;
+            };
+            *tokens = rest;
+
+            Some(expression)
+        }
+        _ => None,
+    }
+}
+
+impl FromStr for Expression {
     type Err = Day11Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v: Vec<_> = s.split(" ").collect();
-        match v.as_slice() {
-            ["old", "*", "old"] => Ok(Self::Square),
-            ["old", "*", n] => Ok(Self::Multiply(n.parse()?)),
-            ["old", "+", n] => Ok(Self::Add(n.parse()?)),
-            _ => Err(Self::Err::InvalidMonkeyOperation),
+        let invalid = || Self::Err::InvalidMonkeyOperation(s.to_owned());
+
+        let tokens = tokenize(s).ok_or_else(invalid)?;
+        let mut rest = tokens.as_slice();
+
+        match parse_sum(&mut rest) {
+            Some(expression) if rest.is_empty() => Ok(expression),
+            _ => Err(invalid()),
         }
     }
 }
 
-impl MonkeyOperation {
-    fn calculate(&self, old: usize) -> usize {
-        use MonkeyOperation::*;
+impl Expression {
+    // Only addition and multiplication commute with taking remainders, so a
+    // division or subtraction anywhere in the tree rules the modulo out.
+    fn preserves_remainders(&self) -> bool {
+        use Expression::*;
 
         match self {
-            Add(n) => old + n,
-            Multiply(n) => old * n,
-            Square => old * old,
+            Old | Constant(_) => true,
+            Add(a, b) | Multiply(a, b) => a.preserves_remainders() && b.preserves_remainders(),
+            Subtract(..) | Divide(..) => false,
         }
     }
+
+    fn calculate(&self, old: usize) -> Result<usize, Day11Error> {
+        use Expression::*;
+
+        Ok(match self {
+            Old => old,
+            Constant(n) => *n,
+            Add(a, b) => a
+                .calculate(old)?
+                .checked_add(b.calculate(old)?)
+                .ok_or(Day11Error::Overflow)?,
+            Subtract(a, b) => a.calculate(old)?.saturating_sub(b.calculate(old)?),
+            Multiply(a, b) => a
+                .calculate(old)?
+                .checked_mul(b.calculate(old)?)
+                .ok_or(Day11Error::Overflow)?,
+            Divide(a, b) => a
+                .calculate(old)?
+                .checked_div(b.calculate(old)?)
+                .ok_or(Day11Error::DivisionByZero)?,
+        })
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl Expression {
+    fn calculate_exact(&self, old: &BigUint) -> Result<BigUint, Day11Error> {
+        use Expression::*;
+
+        Ok(match self {
+            Old => old.clone(),
+            Constant(n) => BigUint::from(*n),
+            Add(a, b) => a.calculate_exact(old)? + b.calculate_exact(old)?,
+            Subtract(a, b) => {
+                let (a, b) = (a.calculate_exact(old)?, b.calculate_exact(old)?);
+                if a > b {
+                    a - b
+                } else {
+                    BigUint::ZERO
+                }
+            }
+            Multiply(a, b) => a.calculate_exact(old)? * b.calculate_exact(old)?,
+            Divide(a, b) => {
+                let divisor = b.calculate_exact(old)?;
+                if divisor == BigUint::ZERO {
+                    return Err(Day11Error::DivisionByZero);
+                }
+
+                a.calculate_exact(old)? / divisor
+            }
+        })
+    }
 }
 
 #[derive(PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 struct MonkeyTest {
     if_divisible_by: usize,
     then_throw_to: usize,
@@ -107,7 +344,18 @@ impl TryFrom<&[String]> for MonkeyTest {
 
 impl MonkeyTest {
     fn apply(&self, item: usize) -> usize {
-        if item % self.if_divisible_by == 0 {
+        if item.is_multiple_of(self.if_divisible_by) {
+            self.then_throw_to
+        } else {
+            self.else_throw_to
+        }
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl MonkeyTest {
+    fn apply_exact(&self, item: &BigUint) -> usize {
+        if item % BigUint::from(self.if_divisible_by) == BigUint::ZERO {
             self.then_throw_to
         } else {
             self.else_throw_to
@@ -115,10 +363,18 @@ impl MonkeyTest {
     }
 }
 
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+struct Item {
+    id: usize,
+    worry_level: usize,
+}
+
 #[derive(PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 struct Monkey {
-    items: VecDeque<usize>,
-    operation: MonkeyOperation,
+    items: VecDeque<Item>,
+    operation: Expression,
     test: MonkeyTest,
 }
 
@@ -134,8 +390,14 @@ impl TryFrom<&[String]> for Monkey {
 
                 let items = items
                     .split(", ")
-                    .map(|item| item.parse())
-                    .collect::<Result<_, _>>()?;
+                    .enumerate()
+                    .map(|(id, item)| {
+                        Ok(Item {
+                            id,
+                            worry_level: item.parse()?,
+                        })
+                    })
+                    .collect::<Result<_, Self::Error>>()?;
 
                 let operation = operation
                     .strip_prefix("  Operation: new = ")
@@ -153,129 +415,363 @@ impl TryFrom<&[String]> for Monkey {
 }
 
 struct ThrownItem {
-    item: usize,
+    item: Item,
+    inspected_worry_level: usize,
+    thrown_to: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Inspection {
+    item_id: usize,
+    round: usize,
+    monkey: usize,
+    worry_level_before: usize,
+    worry_level_after: usize,
     thrown_to: usize,
 }
 
 impl Monkey {
-    fn take_turn(&mut self) -> Vec<ThrownItem> {
+    // Every item held at the start of the turn is inspected once. Items thrown
+    // to this same monkey join the back of its queue, to be inspected again on
+    // its next turn rather than this one.
+    fn take_turn(
+        &mut self,
+        relief_divisor: usize,
+        modulo: Option<usize>,
+    ) -> Result<Vec<ThrownItem>, Day11Error> {
         self.items
             .drain(..)
             .map(|item| {
-                let item = self.operation.calculate(item) / 3;
+                let worry_level = self.operation.calculate(item.worry_level)? / relief_divisor;
+                let worry_level = match modulo {
+                    Some(modulo) => worry_level % modulo,
+                    None => worry_level,
+                };
 
-                ThrownItem {
-                    item,
-                    thrown_to: self.test.apply(item),
-                }
+                Ok(ThrownItem {
+                    item: Item {
+                        worry_level,
+                        ..item
+                    },
+                    inspected_worry_level: item.worry_level,
+                    thrown_to: self.test.apply(worry_level),
+                })
             })
             .collect()
     }
+}
 
-    fn take_turn_2(&mut self, modulo: usize) -> Vec<ThrownItem> {
-        self.items
-            .drain(..)
-            .map(|item| {
-                let item = self.operation.calculate(item) % modulo;
+fn parse_monkeys(input: &[String]) -> Result<Vec<Monkey>, Day11Error> {
+    let mut monkeys: Vec<Monkey> = input
+        .split(|line| line.is_empty())
+        .map(|lines| lines.try_into())
+        .collect::<Result<_, _>>()?;
 
-                ThrownItem {
-                    item,
-                    thrown_to: self.test.apply(item),
-                }
-            })
-            .collect()
+    for (id, item) in monkeys
+        .iter_mut()
+        .flat_map(|monkey| monkey.items.iter_mut())
+        .enumerate()
+    {
+        item.id = id;
     }
+
+    // Targets are positions in the input, whatever the monkeys are called.
+    for (monkey, test) in monkeys.iter().map(|monkey| &monkey.test).enumerate() {
+        for target in [test.then_throw_to, test.else_throw_to] {
+            if target >= monkeys.len() {
+                return Err(Day11Error::InvalidThrowTarget {
+                    monkey,
+                    target,
+                    monkeys: monkeys.len(),
+                });
+            }
+        }
+    }
+
+    Ok(monkeys)
 }
 
-fn parse_monkeys(input: &Vec<String>) -> Result<Vec<Monkey>, Day11Error> {
-    input
-        .split(|line| line.is_empty())
-        .map(|lines| lines.try_into())
-        .collect()
+fn monkey_business(activity: Vec<usize>, top_monkeys: usize) -> Result<usize, Day11Error> {
+    let top_activity = top_k(activity, top_monkeys);
+
+    if top_activity.len() < top_monkeys {
+        return Err(Day11Error::NotEnoughMonkeys);
+    }
+
+    Ok(top_activity.into_iter().product())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Simulation {
+    relief_divisor: usize,
+    rounds: usize,
+    top_monkeys: usize,
 }
 
-fn monkey_business(mut activity: Vec<usize>) -> usize {
-    activity.sort_by(|a, b| b.cmp(a));
+const PART1: Simulation = Simulation {
+    relief_divisor: 3,
+    rounds: 20,
+    top_monkeys: 2,
+};
+
+const PART2: Simulation = Simulation {
+    relief_divisor: 1,
+    rounds: 10000,
+    top_monkeys: 2,
+};
 
-    activity[0] * activity[1]
+fn simulate(input: &[String], simulation: Simulation) -> Result<usize, Day11Error> {
+    simulate_with_inspections(input, simulation, |_| {})
 }
 
-fn part1(input: &Vec<String>) -> Result<usize, Day11Error> {
+fn worry_modulo(monkeys: &[Monkey], simulation: Simulation) -> Result<Option<usize>, Day11Error> {
+    if simulation.relief_divisor == 0 {
+        return Err(Day11Error::InvalidReliefDivisor);
+    }
+
+    // Reducing modulo the divisors is only sound if worry levels are never divided,
+    // and the saturating subtraction doesn't commute with remainders either
+    let preserves_remainders = monkeys
+        .iter()
+        .all(|monkey| monkey.operation.preserves_remainders());
+
+    Ok(
+        (simulation.relief_divisor == 1 && preserves_remainders).then(|| {
+            monkeys
+                .iter()
+                .map(|monkey| monkey.test.if_divisible_by)
+                .product()
+        }),
+    )
+}
+
+fn play_round(
+    monkeys: &mut [Monkey],
+    activity: &mut [usize],
+    round: usize,
+    relief_divisor: usize,
+    modulo: Option<usize>,
+    mut on_inspection: impl FnMut(Inspection),
+) -> Result<(), Day11Error> {
+    for i in 0..monkeys.len() {
+        let thrown_items = monkeys[i].take_turn(relief_divisor, modulo)?;
+        activity[i] += thrown_items.len();
+
+        for ThrownItem {
+            item,
+            inspected_worry_level,
+            thrown_to,
+        } in thrown_items
+        {
+            on_inspection(Inspection {
+                item_id: item.id,
+                round,
+                monkey: i,
+                worry_level_before: inspected_worry_level,
+                worry_level_after: item.worry_level,
+                thrown_to,
+            });
+
+            monkeys[thrown_to].items.push_back(item);
+        }
+    }
+
+    Ok(())
+}
+
+fn simulate_with_inspections(
+    input: &[String],
+    simulation: Simulation,
+    on_inspection: impl FnMut(Inspection),
+) -> Result<usize, Day11Error> {
+    simulate_with_hooks(input, simulation, on_inspection, |_, _, _| {})
+}
+
+fn simulate_with_hooks(
+    input: &[String],
+    simulation: Simulation,
+    mut on_inspection: impl FnMut(Inspection),
+    mut on_round: impl FnMut(usize, &[Monkey], &[usize]),
+) -> Result<usize, Day11Error> {
     let mut monkeys = parse_monkeys(input)?;
     let mut activity: Vec<usize> = monkeys.iter().map(|_| 0).collect();
 
-    for _ in 0..20 {
-        for i in 0..monkeys.len() {
-            let thrown_items = monkeys[i].take_turn();
-            activity[i] += thrown_items.len();
+    let modulo = worry_modulo(&monkeys, simulation)?;
 
-            for ThrownItem { item, thrown_to } in thrown_items {
-                monkeys[thrown_to].items.push_back(item);
-            }
-        }
+    for round in 1..=simulation.rounds {
+        play_round(
+            &mut monkeys,
+            &mut activity,
+            round,
+            simulation.relief_divisor,
+            modulo,
+            &mut on_inspection,
+        )?;
+
+        on_round(round, &monkeys, &activity);
     }
 
-    Ok(monkey_business(activity))
+    monkey_business(activity, simulation.top_monkeys)
 }
 
-fn part2(input: &Vec<String>) -> Result<usize, Day11Error> {
+fn simulate_extrapolated(input: &[String], simulation: Simulation) -> Result<usize, Day11Error> {
     let mut monkeys = parse_monkeys(input)?;
     let mut activity: Vec<usize> = monkeys.iter().map(|_| 0).collect();
 
-    let modulo: usize = monkeys
+    let modulo = worry_modulo(&monkeys, simulation)?;
+
+    let mut cycle_detector = CycleDetector::new();
+    let mut activity_memory: Vec<Vec<usize>> = vec![];
+
+    for round in 0..simulation.rounds {
+        let holdings: Vec<Vec<usize>> = monkeys
+            .iter()
+            .map(|monkey| monkey.items.iter().map(|item| item.worry_level).collect())
+            .collect();
+        activity_memory.push(activity.clone());
+
+        if let Some(cycle) = cycle_detector.observe(round, holdings) {
+            let (number_of_cycles, intermediate_round) = cycle.fold(simulation.rounds);
+
+            let activity = (0..monkeys.len())
+                .map(|i| {
+                    let activity_per_cycle = activity[i] - activity_memory[cycle.start][i];
+                    activity_memory[intermediate_round][i] + activity_per_cycle * number_of_cycles
+                })
+                .collect();
+
+            return monkey_business(activity, simulation.top_monkeys);
+        }
+
+        play_round(
+            &mut monkeys,
+            &mut activity,
+            round + 1,
+            simulation.relief_divisor,
+            modulo,
+            |_| {},
+        )?;
+    }
+
+    monkey_business(activity, simulation.top_monkeys)
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct RoundSnapshot<'a> {
+    round: usize,
+    monkeys: &'a [Monkey],
+    activity: &'a [usize],
+}
+
+#[cfg(feature = "serde")]
+fn round_snapshots(input: &[String], simulation: Simulation) -> Result<String, Day11Error> {
+    let mut snapshots = String::new();
+    let mut result = Ok(());
+
+    simulate_with_hooks(
+        input,
+        simulation,
+        |_| {},
+        |round, monkeys, activity| {
+            let snapshot = RoundSnapshot {
+                round,
+                monkeys,
+                activity,
+            };
+
+            match serde_json::to_string(&snapshot) {
+                Ok(line) => {
+                    snapshots.push_str(&line);
+                    snapshots.push('\n');
+                }
+                Err(e) => result = Err(e),
+            }
+        },
+    )?;
+    result?;
+
+    Ok(snapshots)
+}
+
+fn inspection_log(input: &[String], simulation: Simulation) -> Result<String, Day11Error> {
+    let mut inspections = vec![];
+    simulate_with_inspections(input, simulation, |inspection| inspections.push(inspection))?;
+
+    inspections.sort_by_key(|inspection| inspection.item_id);
+
+    let mut log = String::new();
+    for inspection in inspections {
+        log.push_str(&format!(
+            "Item {}: round {}, monkey {} inspected {} -> {}, threw to monkey {}\n",
+            inspection.item_id,
+            inspection.round,
+            inspection.monkey,
+            inspection.worry_level_before,
+            inspection.worry_level_after,
+            inspection.thrown_to
+        ));
+    }
+
+    Ok(log)
+}
+
+fn part1(input: &[String]) -> Result<usize, Day11Error> {
+    simulate(input, PART1)
+}
+
+fn part2(input: &[String]) -> Result<usize, Day11Error> {
+    simulate(input, PART2)
+}
+
+#[cfg(feature = "num-bigint")]
+fn exact_rounds(
+    input: &[String],
+    simulation: Simulation,
+) -> Result<(usize, Vec<VecDeque<BigUint>>), Day11Error> {
+    let monkeys = parse_monkeys(input)?;
+
+    if simulation.relief_divisor == 0 {
+        return Err(Day11Error::InvalidReliefDivisor);
+    }
+
+    let mut activity: Vec<usize> = monkeys.iter().map(|_| 0).collect();
+
+    let mut items: Vec<VecDeque<BigUint>> = monkeys
         .iter()
-        .map(|monkey| monkey.test.if_divisible_by)
-        .product();
+        .map(|monkey| {
+            monkey
+                .items
+                .iter()
+                .map(|item| BigUint::from(item.worry_level))
+                .collect()
+        })
+        .collect();
 
-    for _ in 0..10000 {
-        for i in 0..monkeys.len() {
-            let thrown_items = monkeys[i].take_turn_2(modulo);
-            activity[i] += thrown_items.len();
+    for _ in 0..simulation.rounds {
+        for (i, monkey) in monkeys.iter().enumerate() {
+            let held: Vec<_> = items[i].drain(..).collect();
+            activity[i] += held.len();
 
-            for ThrownItem { item, thrown_to } in thrown_items {
-                monkeys[thrown_to].items.push_back(item);
+            for item in held {
+                let item = monkey.operation.calculate_exact(&item)? / simulation.relief_divisor;
+                let thrown_to = monkey.test.apply_exact(&item);
+
+                items[thrown_to].push_back(item);
             }
         }
     }
 
-    Ok(monkey_business(activity))
+    Ok((monkey_business(activity, simulation.top_monkeys)?, items))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_monkey() {
-        let input: Vec<String> = "\
-Monkey 0:
-  Starting items: 79, 98
-  Operation: new = old * 19
-  Test: divisible by 23
-    If true: throw to monkey 2
-    If false: throw to monkey 3
-"
-        .lines()
-        .map(|s| s.to_owned())
-        .collect();
+    use aoc::to_lines;
 
-        assert_eq!(
-            Monkey {
-                items: vec![79, 98].into(),
-                operation: MonkeyOperation::Multiply(19),
-                test: MonkeyTest {
-                    if_divisible_by: 23,
-                    then_throw_to: 2,
-                    else_throw_to: 3
-                }
-            },
-            input.as_slice().try_into().unwrap()
-        );
-    }
-
-    #[test]
-    fn test_part1() {
-        let input: Vec<String> = "\
+    const EXAMPLE: &str = "\
 Monkey 0:
   Starting items: 79, 98
   Operation: new = old * 19
@@ -303,49 +799,401 @@ Monkey 3:
   Test: divisible by 17
     If true: throw to monkey 0
     If false: throw to monkey 1
-"
-        .lines()
-        .map(|s| s.to_owned())
-        .collect();
+";
 
-        assert_eq!(part1(&input).unwrap(), 10605);
+    #[test]
+    fn test_parse_monkey() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(
+            Monkey {
+                items: vec![
+                    Item {
+                        id: 0,
+                        worry_level: 79
+                    },
+                    Item {
+                        id: 1,
+                        worry_level: 98
+                    }
+                ]
+                .into(),
+                operation: Expression::Multiply(
+                    Box::new(Expression::Old),
+                    Box::new(Expression::Constant(19))
+                ),
+                test: MonkeyTest {
+                    if_divisible_by: 23,
+                    then_throw_to: 2,
+                    else_throw_to: 3
+                }
+            },
+            input[..6].try_into().unwrap()
+        );
     }
 
     #[test]
-    fn test_part2() {
-        let input: Vec<String> = "\
+    fn test_parse_monkey_operation() {
+        use Expression::*;
+
+        let old = || Box::new(Old);
+        let constant = |n| Box::new(Constant(n));
+
+        assert_eq!(
+            "old + old".parse::<Expression>().unwrap(),
+            Add(old(), old())
+        );
+        assert_eq!(
+            "old - 4".parse::<Expression>().unwrap(),
+            Subtract(old(), constant(4))
+        );
+        assert_eq!(
+            "old / 2".parse::<Expression>().unwrap(),
+            Divide(old(), constant(2))
+        );
+        assert_eq!(
+            "3 * old".parse::<Expression>().unwrap(),
+            Multiply(constant(3), old())
+        );
+        assert_eq!(
+            "old * 3 + 2".parse::<Expression>().unwrap(),
+            Add(Box::new(Multiply(old(), constant(3))), constant(2))
+        );
+        assert_eq!(
+            "(old + 1) * old".parse::<Expression>().unwrap(),
+            Multiply(Box::new(Add(old(), constant(1))), old())
+        );
+        assert_eq!(
+            "old - 1 - 1".parse::<Expression>().unwrap(),
+            Subtract(Box::new(Subtract(old(), constant(1))), constant(1))
+        );
+
+        for invalid in [
+            "old / 0",
+            "old - x",
+            "old % 3",
+            "",
+            "old +",
+            "(old + 1",
+            "old + 1)",
+            "old old",
+            "older",
+            "99999999999999999999999",
+        ] {
+            match invalid.parse::<Expression>() {
+                Err(Day11Error::InvalidMonkeyOperation(text)) => assert_eq!(text, invalid),
+                result => panic!("unexpected result for {:?}: {:?}", invalid, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_calculate() {
+        let calculate = |expression: &str, old| expression.parse::<Expression>()?.calculate(old);
+
+        assert_eq!(calculate("old * 3 + 2", 5).unwrap(), 17);
+        assert_eq!(calculate("2 + old * 3", 5).unwrap(), 17);
+        assert_eq!(calculate("(old + 1) * old", 5).unwrap(), 30);
+        assert_eq!(calculate("old * (old - 2) / 3", 5).unwrap(), 5);
+        assert_eq!(calculate("1 - old", 5).unwrap(), 0);
+        assert!(matches!(
+            calculate("old / (old - 5)", 5),
+            Err(Day11Error::DivisionByZero)
+        ));
+        assert!(matches!(
+            calculate("old * old", usize::MAX / 2),
+            Err(Day11Error::Overflow)
+        ));
+        assert!(matches!(
+            calculate("old + 1", usize::MAX),
+            Err(Day11Error::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_overflow_without_modulo() {
+        let input = to_lines(EXAMPLE);
+        let simulation = Simulation {
+            relief_divisor: 2,
+            rounds: 200,
+            top_monkeys: 2,
+        };
+
+        assert!(matches!(
+            simulate(&input, simulation),
+            Err(Day11Error::Overflow)
+        ));
+        assert!(matches!(
+            simulate_extrapolated(&input, simulation),
+            Err(Day11Error::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_worry_modulo() {
+        let modulo = |operation: &str| {
+            let input = to_lines(&EXAMPLE.replace("new = old * 19", operation));
+            worry_modulo(&parse_monkeys(&input).unwrap(), PART2).unwrap()
+        };
+
+        assert_eq!(modulo("new = old * 19"), Some(23 * 19 * 13 * 17));
+        assert_eq!(modulo("new = (old + 1) * old + 2"), Some(23 * 19 * 13 * 17));
+        for operation in [
+            "new = old / 2",
+            "new = old - 1",
+            "new = (old - 1) * old",
+            "new = old + old * 5 / 2",
+        ] {
+            assert_eq!(modulo(operation), None, "{}", operation);
+        }
+
+        let input = to_lines(EXAMPLE);
+        assert_eq!(
+            worry_modulo(&parse_monkeys(&input).unwrap(), PART1).unwrap(),
+            None
+        );
+    }
+
+    const SELF_THROWS: &str = "\
 Monkey 0:
-  Starting items: 79, 98
-  Operation: new = old * 19
-  Test: divisible by 23
-    If true: throw to monkey 2
-    If false: throw to monkey 3
+  Starting items: 1, 2
+  Operation: new = old + 1
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 1
 
 Monkey 1:
-  Starting items: 54, 65, 75, 74
-  Operation: new = old + 6
-  Test: divisible by 19
-    If true: throw to monkey 2
+  Starting items: 5
+  Operation: new = old * 1
+  Test: divisible by 7
+    If true: throw to monkey 0
     If false: throw to monkey 0
+";
 
-Monkey 2:
-  Starting items: 79, 60, 97
-  Operation: new = old * old
-  Test: divisible by 13
+    #[test]
+    fn test_invalid_throw_target() {
+        let input = to_lines(
+            &SELF_THROWS.replace("If false: throw to monkey 1", "If false: throw to monkey 2"),
+        );
+
+        assert!(matches!(
+            parse_monkeys(&input),
+            Err(Day11Error::InvalidThrowTarget {
+                monkey: 0,
+                target: 2,
+                monkeys: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_self_throws() {
+        let input = to_lines(SELF_THROWS);
+        let simulation = Simulation {
+            relief_divisor: 1,
+            rounds: 2,
+            top_monkeys: 2,
+        };
+
+        let mut rounds = vec![];
+        let monkey_business = simulate_with_hooks(
+            &input,
+            simulation,
+            |_| {},
+            |_, monkeys, activity| {
+                let holdings: Vec<Vec<usize>> = monkeys
+                    .iter()
+                    .map(|monkey| monkey.items.iter().map(|item| item.worry_level).collect())
+                    .collect();
+                rounds.push((holdings, activity.to_vec()));
+            },
+        )
+        .unwrap();
+
+        // 1 becomes 2 and comes back to monkey 0, which doesn't inspect it
+        // again until the next round.
+        assert_eq!(rounds[0], (vec![vec![2, 5, 3], vec![]], vec![2, 2]));
+        assert_eq!(rounds[1], (vec![vec![6, 4, 3], vec![]], vec![5, 3]));
+        assert_eq!(monkey_business, 15);
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part1(&input).unwrap(), 10605);
+    }
+
+    #[test]
+    fn test_monkey_business() {
+        assert_eq!(monkey_business(vec![101, 95, 7, 105], 2).unwrap(), 10605);
+        assert_eq!(monkey_business(vec![101, 95, 7, 105], 3).unwrap(), 1007475);
+        assert_eq!(monkey_business(vec![101, 95, 7, 105], 0).unwrap(), 1);
+        assert!(matches!(
+            monkey_business(vec![101], 2),
+            Err(Day11Error::NotEnoughMonkeys)
+        ));
+    }
+
+    #[test]
+    fn test_simulate_extrapolated() {
+        let input = to_lines(
+            "\
+Monkey 0:
+  Starting items: 1
+  Operation: new = old * 1
+  Test: divisible by 2
     If true: throw to monkey 1
-    If false: throw to monkey 3
+    If false: throw to monkey 1
 
-Monkey 3:
-  Starting items: 74
-  Operation: new = old + 3
-  Test: divisible by 17
+Monkey 1:
+  Starting items: 1
+  Operation: new = old * 1
+  Test: divisible by 3
     If true: throw to monkey 0
-    If false: throw to monkey 1
-"
-        .lines()
-        .map(|s| s.to_owned())
-        .collect();
+    If false: throw to monkey 0
+",
+        );
+
+        let simulation = Simulation {
+            relief_divisor: 1,
+            rounds: 1000,
+            top_monkeys: 2,
+        };
+        assert_eq!(
+            simulate_extrapolated(&input, simulation).unwrap(),
+            simulate(&input, simulation).unwrap()
+        );
+
+        let simulation = Simulation {
+            relief_divisor: 1,
+            rounds: 1000000000,
+            top_monkeys: 2,
+        };
+        assert_eq!(
+            simulate_extrapolated(&input, simulation).unwrap(),
+            2000000000 * 1999999999
+        );
+    }
+
+    #[test]
+    fn test_inspection_log() {
+        let input = to_lines(EXAMPLE);
+
+        let mut inspections = vec![];
+        simulate_with_inspections(&input, PART1, |inspection| {
+            if inspection.item_id == 0 {
+                inspections.push(inspection)
+            }
+        })
+        .unwrap();
+
+        assert_eq!(
+            inspections[..2],
+            [
+                Inspection {
+                    item_id: 0,
+                    round: 1,
+                    monkey: 0,
+                    worry_level_before: 79,
+                    worry_level_after: 500,
+                    thrown_to: 3
+                },
+                Inspection {
+                    item_id: 0,
+                    round: 1,
+                    monkey: 3,
+                    worry_level_before: 500,
+                    worry_level_after: 167,
+                    thrown_to: 1
+                }
+            ]
+        );
+
+        let log = inspection_log(&input, PART1).unwrap();
+        assert_eq!(
+            log.lines().next(),
+            Some("Item 0: round 1, monkey 0 inspected 79 -> 500, threw to monkey 3")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_round_snapshots() {
+        let input = to_lines(EXAMPLE);
+
+        let snapshots = round_snapshots(&input, PART1).unwrap();
+        assert_eq!(snapshots.lines().count(), 20);
+
+        let first: serde_json::Value =
+            serde_json::from_str(snapshots.lines().next().unwrap()).unwrap();
+        assert_eq!(first["round"], 1);
+        assert_eq!(first["activity"], serde_json::json!([2, 4, 3, 5]));
+
+        let worry_levels: Vec<_> = first["monkeys"][0]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["worry_level"].as_u64().unwrap())
+            .collect();
+        assert_eq!(worry_levels, vec![20, 23, 27, 26]);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = to_lines(EXAMPLE);
 
         assert_eq!(part2(&input).unwrap(), 2713310158);
+
+        let simulation = Simulation {
+            relief_divisor: 1,
+            rounds: 1000,
+            top_monkeys: 2,
+        };
+        assert_eq!(simulate(&input, simulation).unwrap(), 5204 * 5192);
+        assert_eq!(
+            simulate_extrapolated(&input, simulation).unwrap(),
+            5204 * 5192
+        );
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_exact_rounds() {
+        let input = to_lines(EXAMPLE);
+
+        let simulation = Simulation {
+            relief_divisor: 1,
+            rounds: 20,
+            top_monkeys: 2,
+        };
+
+        let (monkey_business, _) = exact_rounds(&input, simulation).unwrap();
+        assert_eq!(monkey_business, 103 * 99);
+        assert_eq!(monkey_business, simulate(&input, simulation).unwrap());
+
+        let (monkey_business, _) = exact_rounds(&input, PART1).unwrap();
+        assert_eq!(monkey_business, part1(&input).unwrap());
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_exact_rounds_with_division() {
+        let input = to_lines(
+            &EXAMPLE
+                .replace("new = old + 3", "new = old * 5 / 2")
+                .replace("new = old * old", "new = old * 2"),
+        );
+
+        for rounds in [3, 5, 11] {
+            let simulation = Simulation {
+                relief_divisor: 1,
+                rounds,
+                top_monkeys: 2,
+            };
+
+            let (monkey_business, _) = exact_rounds(&input, simulation).unwrap();
+            assert_eq!(simulate(&input, simulation).unwrap(), monkey_business);
+        }
     }
 }
@@ -1,17 +1,32 @@
 use std::{
-    collections::{BinaryHeap, HashMap},
-    io,
+    collections::{BinaryHeap, VecDeque},
+    fmt, io,
+    num::ParseIntError,
+    str::FromStr,
+    time::{Duration, Instant},
 };
 
-use aoc::read_lines;
+use aoc::{
+    cli::Args,
+    puzzles::part_label,
+    read_lines,
+    render::{InvalidPacking, Packing},
+};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day12Error {
     IoError(io::Error),
+    ParseIntError(ParseIntError),
     InconsistentRowWidth,
     NoStartPosition,
     NoEndPosition,
     NoPath,
+    InvalidPoint(String),
+    PointOutOfBounds((usize, usize)),
+    InvalidPacking(InvalidPacking),
+    InvalidAlgorithm(String),
+    AlgorithmsDisagree(Vec<Comparison>),
 }
 
 impl From<io::Error> for Day12Error {
@@ -20,13 +35,80 @@ impl From<io::Error> for Day12Error {
     }
 }
 
+impl From<ParseIntError> for Day12Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
+impl From<InvalidPacking> for Day12Error {
+    fn from(e: InvalidPacking) -> Self {
+        Self::InvalidPacking(e)
+    }
+}
+
 const INPUT_PATH: &str = "inputs/day12.txt";
 
 fn main() -> Result<(), Day12Error> {
+    let args = Args::from_env();
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    let default_rules = MovementRules::default();
+    let rules = MovementRules {
+        max_climb: args.parse("--max-climb")?.or(default_rules.max_climb),
+        max_descent: args.parse("--max-descent")?.or(default_rules.max_descent),
+        diagonal: args.flag("--diagonal"),
+    };
+
+    if args.flag("--compare") {
+        for part in [1, 2] {
+            let (distance, comparisons) = compare(&input, &rules, part)?;
+            println!("{}: {:?}", part_label(12, part), distance);
+            for comparison in comparisons {
+                println!("  {comparison}");
+            }
+        }
+
+        return Ok(());
+    }
+
+    let algorithm = args.parse("--algorithm")?.unwrap_or_default();
+
+    println!(
+        "{}: {:?}",
+        part_label(12, 1),
+        part1(&input, &rules, algorithm)?
+    );
+    println!(
+        "{}: {:?}",
+        part_label(12, 2),
+        part2(&input, &rules, algorithm)?
+    );
+
+    let packing = args.parse("--packing")?;
+
+    if args.flag("--render") {
+        print!("{}", render_part1(&input, &rules, packing)?);
+    }
+
+    let from = args
+        .values("--from")
+        .into_iter()
+        .map(parse_point)
+        .collect::<Result<Vec<_>, _>>()?;
+    let to = args
+        .values("--to")
+        .into_iter()
+        .map(parse_point)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !from.is_empty() || !to.is_empty() {
+        println!("Route: {:?}", custom_route(&input, &from, &to, &rules)?);
+    }
+
+    if args.flag("--heatmap") {
+        print!("{}", render_heatmap(&input, &rules, packing)?);
+    }
 
     Ok(())
 }
@@ -35,8 +117,15 @@ struct ElevationMap {
     width: usize,
     height: usize,
     storage: Vec<u8>,
+    starts: Vec<(usize, usize)>,
+    ends: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Route {
     start: (usize, usize),
     end: (usize, usize),
+    distance: usize,
 }
 
 impl TryFrom<&[String]> for ElevationMap {
@@ -48,19 +137,19 @@ impl TryFrom<&[String]> for ElevationMap {
         let mut width: Option<usize> = None;
         let height = value.len();
 
-        let mut start: Option<(usize, usize)> = None;
-        let mut end: Option<(usize, usize)> = None;
+        let mut starts: Vec<(usize, usize)> = vec![];
+        let mut ends: Vec<(usize, usize)> = vec![];
 
         for (y, row) in value.iter().enumerate() {
             let row_width = row.len();
             for (x, elevation) in row.bytes().enumerate() {
                 let elevation = match elevation {
                     b'S' => {
-                        start = Some((x, y));
+                        starts.push((x, y));
                         b'a'
                     }
                     b'E' => {
-                        end = Some((x, y));
+                        ends.push((x, y));
                         b'z'
                     }
                     e => e,
@@ -86,20 +175,140 @@ impl TryFrom<&[String]> for ElevationMap {
             storage,
             width,
             height,
-            start: start.ok_or(Self::Error::NoStartPosition)?,
-            end: end.ok_or(Self::Error::NoEndPosition)?,
+            starts,
+            ends,
         })
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct MovementRules {
+    max_climb: Option<u8>,
+    max_descent: Option<u8>,
+    diagonal: bool,
+}
+
+impl Default for MovementRules {
+    fn default() -> Self {
+        Self {
+            max_climb: Some(1),
+            max_descent: None,
+            diagonal: false,
+        }
+    }
+}
+
+impl MovementRules {
+    fn allows(&self, current_elevation: u8, target_elevation: u8) -> bool {
+        if target_elevation >= current_elevation {
+            self.max_climb
+                .is_none_or(|max_climb| target_elevation - current_elevation <= max_climb)
+        } else {
+            self.max_descent
+                .is_none_or(|max_descent| current_elevation - target_elevation <= max_descent)
+        }
+    }
+
+    fn reversed(&self) -> Self {
+        Self {
+            max_climb: self.max_descent,
+            max_descent: self.max_climb,
+            diagonal: self.diagonal,
+        }
+    }
+
+    fn moves<'a>(
+        &'a self,
+        map: &'a ElevationMap,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), usize)> + 'a {
+        let current_elevation = map.get(x, y).copied();
+
+        map.neighbours((x, y), self.diagonal)
+            .into_iter()
+            .filter_map(move |(x, y)| {
+                let target_elevation = map.get(x, y).copied();
+
+                if self.allows(current_elevation?, target_elevation?) {
+                    Some(((x, y), 1))
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Algorithm {
+    Bfs,
+    #[default]
+    Dijkstra,
+    AStar,
+}
+
+impl Algorithm {
+    const ALL: [Self; 3] = [Self::Bfs, Self::Dijkstra, Self::AStar];
+}
+
+impl FromStr for Algorithm {
+    type Err = Day12Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bfs" => Ok(Self::Bfs),
+            "dijkstra" => Ok(Self::Dijkstra),
+            "astar" => Ok(Self::AStar),
+            _ => Err(Self::Err::InvalidAlgorithm(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bfs => write!(f, "bfs"),
+            Self::Dijkstra => write!(f, "dijkstra"),
+            Self::AStar => write!(f, "astar"),
+        }
+    }
+}
+
+struct Search {
+    distances: Vec<Option<usize>>,
+    previous: Vec<Option<usize>>,
+    reached: Option<usize>,
+    expanded: usize,
+}
+
+#[derive(Debug)]
+struct Comparison {
+    algorithm: Algorithm,
+    distance: Option<usize>,
+    expanded: usize,
+    elapsed: Duration,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<8} {:>8} expanded in {:?}",
+            self.algorithm.to_string(),
+            self.expanded,
+            self.elapsed
+        )
+    }
+}
+
 struct PointWithTentativeDistance {
     point: (usize, usize),
     tentative_distance: usize,
+    estimate: usize,
 }
 
 impl PartialEq for PointWithTentativeDistance {
     fn eq(&self, other: &Self) -> bool {
-        self.tentative_distance == other.tentative_distance
+        (self.estimate, self.tentative_distance) == (other.estimate, other.tentative_distance)
     }
 }
 
@@ -107,15 +316,18 @@ impl Eq for PointWithTentativeDistance {}
 
 impl PartialOrd for PointWithTentativeDistance {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        other
-            .tentative_distance
-            .partial_cmp(&self.tentative_distance)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for PointWithTentativeDistance {
+    // Among equal estimates, points further along are popped first, which
+    // saves A* from expanding every tie on the way to the target.
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.tentative_distance.cmp(&self.tentative_distance)
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then(self.tentative_distance.cmp(&other.tentative_distance))
     }
 }
 
@@ -128,111 +340,486 @@ impl ElevationMap {
         self.storage.get(self.index_of(col_index, row_index))
     }
 
-    fn neighbours(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+    fn neighbours(&self, (x, y): (usize, usize), diagonal: bool) -> Vec<(usize, usize)> {
         let mut result = vec![];
 
-        if x > 0 {
-            result.push((x - 1, y));
-        }
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy) == (0, 0) || (!diagonal && dx != 0 && dy != 0) {
+                    continue;
+                }
 
-        if y > 0 {
-            result.push((x, y - 1));
+                match (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                    (Some(x), Some(y)) if x < self.width && y < self.height => result.push((x, y)),
+                    _ => {}
+                }
+            }
         }
 
-        if x + 1 < self.width {
-            result.push((x + 1, y));
-        }
+        result
+    }
 
-        if y + 1 < self.height {
-            result.push((x, y + 1));
-        }
+    fn point_of(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
 
-        result
+    fn search(
+        &self,
+        sources: &[(usize, usize)],
+        targets: &[(usize, usize)],
+        rules: &MovementRules,
+        algorithm: Algorithm,
+    ) -> Search {
+        match algorithm {
+            Algorithm::Bfs => self.breadth_first_search(sources, targets, rules),
+            Algorithm::Dijkstra => self.best_first_search(sources, targets, rules, |_| 0),
+            Algorithm::AStar => self.best_first_search(sources, targets, rules, |point| {
+                self.lower_bound(point, targets, rules)
+            }),
+        }
     }
 
-    fn neighbours_with_distances(
+    // Every move costs one step and covers one cell, and climbs or descends no
+    // more than the rules allow, so neither count can overestimate.
+    fn lower_bound(
         &self,
         (x, y): (usize, usize),
-    ) -> impl Iterator<Item = ((usize, usize), usize)> + '_ {
-        let current_elevation = self.get(x, y);
+        targets: &[(usize, usize)],
+        rules: &MovementRules,
+    ) -> usize {
+        let elevation = self.storage[self.index_of(x, y)];
 
-        self.neighbours((x, y))
-            .into_iter()
-            .filter_map(move |(x, y)| {
-                let target_elevation = self.get(x, y);
+        targets
+            .iter()
+            .map(|&(target_x, target_y)| {
+                let (dx, dy) = (x.abs_diff(target_x), y.abs_diff(target_y));
+                let steps = if rules.diagonal { dx.max(dy) } else { dx + dy };
 
-                if target_elevation? <= &(current_elevation? + 1) {
-                    Some(((x, y), 1))
+                let target_elevation = self.storage[self.index_of(target_x, target_y)];
+                let (change, limit) = if target_elevation >= elevation {
+                    (target_elevation - elevation, rules.max_climb)
                 } else {
-                    None
-                }
+                    (elevation - target_elevation, rules.max_descent)
+                };
+                let changes = match limit {
+                    Some(limit) if limit > 0 => change.div_ceil(limit) as usize,
+                    _ => 0,
+                };
+
+                steps.max(changes)
             })
+            .min()
+            .unwrap_or(0)
     }
 
-    fn length_of_shortest_path(&self, start: (usize, usize)) -> Option<usize> {
-        let mut queue = BinaryHeap::new();
-        queue.push(PointWithTentativeDistance {
-            point: start,
-            tentative_distance: 0,
-        });
+    fn best_first_search(
+        &self,
+        sources: &[(usize, usize)],
+        targets: &[(usize, usize)],
+        rules: &MovementRules,
+        heuristic: impl Fn((usize, usize)) -> usize,
+    ) -> Search {
+        let mut distances = vec![None; self.storage.len()];
+        let mut previous = vec![None; self.storage.len()];
+        let mut expanded = 0;
+
+        let mut is_target = vec![false; self.storage.len()];
+        for &(x, y) in targets {
+            is_target[self.index_of(x, y)] = true;
+        }
 
-        let mut previous: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        for &(x, y) in sources {
+            distances[self.index_of(x, y)] = Some(0);
+            queue.push(PointWithTentativeDistance {
+                point: (x, y),
+                tentative_distance: 0,
+                estimate: heuristic((x, y)),
+            });
+        }
 
         while let Some(PointWithTentativeDistance {
             point,
             tentative_distance,
+            ..
         }) = queue.pop()
         {
-            if point == self.end {
-                let mut length = 0;
-                let mut node = point;
-                while let Some(prev) = previous.remove(&node) {
-                    node = prev;
-                    length += 1;
+            let index = self.index_of(point.0, point.1);
+
+            if distances[index].is_some_and(|distance| tentative_distance > distance) {
+                continue;
+            }
+
+            expanded += 1;
+
+            if is_target[index] {
+                return Search {
+                    distances,
+                    previous,
+                    reached: Some(index),
+                    expanded,
+                };
+            }
+
+            for (neighbour, distance) in rules.moves(self, point) {
+                let neighbour_index = self.index_of(neighbour.0, neighbour.1);
+                let new_distance = tentative_distance + distance;
+
+                if distances[neighbour_index].is_none_or(|distance| new_distance < distance) {
+                    distances[neighbour_index] = Some(new_distance);
+                    previous[neighbour_index] = Some(index);
+                    queue.push(PointWithTentativeDistance {
+                        point: neighbour,
+                        tentative_distance: new_distance,
+                        estimate: new_distance + heuristic(neighbour),
+                    });
                 }
+            }
+        }
 
-                return Some(length);
-            } else {
-                for (neighbour, distance) in self.neighbours_with_distances(point) {
-                    if neighbour != start && !previous.contains_key(&neighbour) {
-                        previous.insert(neighbour.clone(), point.clone());
-                        let new_distance = tentative_distance + distance;
-                        queue.push(PointWithTentativeDistance {
-                            point: neighbour,
-                            tentative_distance: new_distance,
-                        });
-                    }
+        Search {
+            distances,
+            previous,
+            reached: None,
+            expanded,
+        }
+    }
+
+    // Only valid because every move costs the same, so the first visit to a
+    // point is always along a shortest path.
+    fn breadth_first_search(
+        &self,
+        sources: &[(usize, usize)],
+        targets: &[(usize, usize)],
+        rules: &MovementRules,
+    ) -> Search {
+        let mut distances = vec![None; self.storage.len()];
+        let mut previous = vec![None; self.storage.len()];
+        let mut expanded = 0;
+
+        let mut is_target = vec![false; self.storage.len()];
+        for &(x, y) in targets {
+            is_target[self.index_of(x, y)] = true;
+        }
+
+        let mut queue = VecDeque::new();
+        for &(x, y) in sources {
+            let index = self.index_of(x, y);
+            if distances[index].is_none() {
+                distances[index] = Some(0);
+                queue.push_back((x, y));
+            }
+        }
+
+        while let Some(point) = queue.pop_front() {
+            let index = self.index_of(point.0, point.1);
+            let distance = distances[index].unwrap_or(0);
+
+            expanded += 1;
+
+            if is_target[index] {
+                return Search {
+                    distances,
+                    previous,
+                    reached: Some(index),
+                    expanded,
+                };
+            }
+
+            for (neighbour, _) in rules.moves(self, point) {
+                let neighbour_index = self.index_of(neighbour.0, neighbour.1);
+
+                if distances[neighbour_index].is_none() {
+                    distances[neighbour_index] = Some(distance + 1);
+                    previous[neighbour_index] = Some(index);
+                    queue.push_back(neighbour);
                 }
             }
         }
 
-        None
+        Search {
+            distances,
+            previous,
+            reached: None,
+            expanded,
+        }
+    }
+
+    fn path_to(&self, search: &Search) -> Option<Vec<(usize, usize)>> {
+        let mut index = search.reached?;
+        let mut path = vec![self.point_of(index)];
+        while let Some(previous) = search.previous[index] {
+            index = previous;
+            path.push(self.point_of(index));
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    fn shortest_path(
+        &self,
+        starts: &[(usize, usize)],
+        ends: &[(usize, usize)],
+        rules: &MovementRules,
+        algorithm: Algorithm,
+    ) -> Option<Vec<(usize, usize)>> {
+        self.path_to(&self.search(starts, ends, rules, algorithm))
+    }
+
+    fn best_route(
+        &self,
+        starts: &[(usize, usize)],
+        ends: &[(usize, usize)],
+        rules: &MovementRules,
+        algorithm: Algorithm,
+    ) -> Result<Route, Day12Error> {
+        check_endpoints(starts, ends)?;
+
+        let path = self
+            .shortest_path(starts, ends, rules, algorithm)
+            .ok_or(Day12Error::NoPath)?;
+
+        Ok(Route {
+            start: path[0],
+            end: path[path.len() - 1],
+            distance: path.len() - 1,
+        })
     }
+
+    fn distance_field(&self, rules: &MovementRules) -> Vec<Option<usize>> {
+        self.search(&self.ends, &[], &rules.reversed(), Algorithm::default())
+            .distances
+    }
+
+    fn render_heatmap(&self, distances: &[Option<usize>]) -> String {
+        const RAMP: &[u8] = b" .:-=+*#%@";
+
+        let max_distance = distances.iter().flatten().max().copied().unwrap_or(0);
+
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in distances.chunks(self.width.max(1)) {
+            s.extend(row.iter().map(|distance| match distance {
+                Some(distance) => {
+                    let level = distance * (RAMP.len() - 1) / max_distance.max(1);
+                    RAMP[level] as char
+                }
+                None => '?',
+            }));
+            s.push('\n');
+        }
+
+        s
+    }
+
+    // Alternate bands of the heatmap ramp are filled, so they read as contours.
+    fn render_heatmap_packed(&self, distances: &[Option<usize>], packing: Packing) -> String {
+        const BANDS: usize = 10;
+
+        let max_distance = distances.iter().flatten().max().copied().unwrap_or(0);
+
+        packing.pack(self.width, self.height, |x, y| {
+            distances[self.index_of(x, y)]
+                .is_some_and(|distance| distance * (BANDS - 1) / max_distance.max(1) % 2 == 1)
+        })
+    }
+
+    fn render_path_packed(&self, path: &[(usize, usize)], packing: Packing) -> String {
+        let mut on_path = vec![false; self.storage.len()];
+        for &(x, y) in path {
+            on_path[self.index_of(x, y)] = true;
+        }
+
+        packing.pack(self.width, self.height, |x, y| on_path[self.index_of(x, y)])
+    }
+
+    fn render_path(&self, path: &[(usize, usize)]) -> String {
+        let mut cells: Vec<char> = self.storage.iter().map(|&e| e as char).collect();
+
+        for window in path.windows(2) {
+            let ((x, y), (next_x, next_y)) = (window[0], window[1]);
+
+            cells[self.index_of(x, y)] = if next_x > x {
+                '>'
+            } else if next_x < x {
+                '<'
+            } else if next_y > y {
+                'v'
+            } else {
+                '^'
+            };
+        }
+
+        if let Some(&(x, y)) = path.last() {
+            cells[self.index_of(x, y)] = 'E';
+        }
+
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in cells.chunks(self.width.max(1)) {
+            s.extend(row);
+            s.push('\n');
+        }
+
+        s
+    }
+}
+
+fn render_part1(
+    input: &[String],
+    rules: &MovementRules,
+    packing: Option<Packing>,
+) -> Result<String, Day12Error> {
+    let map: ElevationMap = input.try_into()?;
+
+    let path = map
+        .shortest_path(&map.starts, &map.ends, rules, Algorithm::default())
+        .ok_or(Day12Error::NoPath)?;
+
+    Ok(match packing {
+        Some(packing) => map.render_path_packed(&path, packing),
+        None => map.render_path(&path),
+    })
+}
+
+fn render_heatmap(
+    input: &[String],
+    rules: &MovementRules,
+    packing: Option<Packing>,
+) -> Result<String, Day12Error> {
+    let map: ElevationMap = input.try_into()?;
+    let distances = map.distance_field(rules);
+
+    Ok(match packing {
+        Some(packing) => map.render_heatmap_packed(&distances, packing),
+        None => map.render_heatmap(&distances),
+    })
 }
 
-fn part1(input: &Vec<String>) -> Result<usize, Day12Error> {
-    let map: ElevationMap = input.as_slice().try_into().unwrap();
+fn parse_point(s: &str) -> Result<(usize, usize), Day12Error> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| Day12Error::InvalidPoint(s.to_owned()))?;
 
-    map.length_of_shortest_path(map.start)
-        .ok_or(Day12Error::NoPath)
+    Ok((x.parse()?, y.parse()?))
 }
 
-fn part2(input: &Vec<String>) -> Result<usize, Day12Error> {
-    let map: ElevationMap = input.as_slice().try_into().unwrap();
+fn custom_route(
+    input: &[String],
+    from: &[(usize, usize)],
+    to: &[(usize, usize)],
+    rules: &MovementRules,
+) -> Result<Route, Day12Error> {
+    let map: ElevationMap = input.try_into()?;
+
+    // The searches index by position, so points off the map can't reach them.
+    if let Some(&point) = from
+        .iter()
+        .chain(to)
+        .find(|(x, y)| *x >= map.width || *y >= map.height)
+    {
+        return Err(Day12Error::PointOutOfBounds(point));
+    }
 
+    let starts = if from.is_empty() { &map.starts } else { from };
+    let ends = if to.is_empty() { &map.ends } else { to };
+
+    map.best_route(starts, ends, rules, Algorithm::default())
+}
+
+fn check_endpoints(starts: &[(usize, usize)], ends: &[(usize, usize)]) -> Result<(), Day12Error> {
+    if starts.is_empty() {
+        return Err(Day12Error::NoStartPosition);
+    }
+
+    if ends.is_empty() {
+        return Err(Day12Error::NoEndPosition);
+    }
+
+    Ok(())
+}
+
+fn lowest_points(map: &ElevationMap) -> Vec<(usize, usize)> {
     let positions = (0..map.width).flat_map(|x| (0..map.height).map(move |y| (x, y)));
+
     positions
         .filter(|(x, y)| map.get(*x, *y) == Some(&b'a'))
-        .filter_map(|point| map.length_of_shortest_path(point))
-        .min()
-        .ok_or(Day12Error::NoPath)
+        .collect()
+}
+
+// Runs every algorithm on the same part, failing if any of them finds a
+// different distance. The map is parsed once, so timings cover the search only.
+fn compare(
+    input: &[String],
+    rules: &MovementRules,
+    part: usize,
+) -> Result<(usize, Vec<Comparison>), Day12Error> {
+    let map: ElevationMap = input.try_into()?;
+
+    let starts = if part == 1 {
+        map.starts.clone()
+    } else {
+        lowest_points(&map)
+    };
+    check_endpoints(&starts, &map.ends)?;
+
+    let comparisons: Vec<_> = Algorithm::ALL
+        .into_iter()
+        .map(|algorithm| {
+            let start = Instant::now();
+            let search = map.search(&starts, &map.ends, rules, algorithm);
+            let elapsed = start.elapsed();
+
+            Comparison {
+                algorithm,
+                distance: search.reached.and_then(|index| search.distances[index]),
+                expanded: search.expanded,
+                elapsed,
+            }
+        })
+        .collect();
+
+    if comparisons
+        .iter()
+        .any(|comparison| comparison.distance != comparisons[0].distance)
+    {
+        return Err(Day12Error::AlgorithmsDisagree(comparisons));
+    }
+
+    let distance = comparisons[0].distance.ok_or(Day12Error::NoPath)?;
+
+    Ok((distance, comparisons))
+}
+
+fn part1(
+    input: &[String],
+    rules: &MovementRules,
+    algorithm: Algorithm,
+) -> Result<usize, Day12Error> {
+    let map: ElevationMap = input.try_into()?;
+
+    Ok(map
+        .best_route(&map.starts, &map.ends, rules, algorithm)?
+        .distance)
+}
+
+fn part2(
+    input: &[String],
+    rules: &MovementRules,
+    algorithm: Algorithm,
+) -> Result<usize, Day12Error> {
+    let map: ElevationMap = input.try_into()?;
+    let starts = lowest_points(&map);
+
+    Ok(map
+        .best_route(&starts, &map.ends, rules, algorithm)?
+        .distance)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use aoc::to_lines;
+    use aoc::{rng::XorShift, to_lines};
 
     const EXAMPLE: &str = "\
 Sabqponm
@@ -248,7 +835,7 @@ abdefghi
 
         let map: ElevationMap = input.as_slice().try_into().unwrap();
 
-        assert_eq!(map.start, (0, 0));
+        assert_eq!(map.starts, vec![(0, 0)]);
     }
 
     #[test]
@@ -257,20 +844,285 @@ abdefghi
 
         let map: ElevationMap = input.as_slice().try_into().unwrap();
 
-        assert_eq!(map.end, (5, 2));
+        assert_eq!(map.ends, vec![(5, 2)]);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let input = to_lines(EXAMPLE);
+
+        let map: ElevationMap = input.as_slice().try_into().unwrap();
+        let path = map
+            .shortest_path(
+                &map.starts,
+                &map.ends,
+                &MovementRules::default(),
+                Algorithm::default(),
+            )
+            .unwrap();
+
+        assert_eq!(path.len(), 32);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(5, 2)));
+
+        for window in path.windows(2) {
+            let ((x, y), (next_x, next_y)) = (window[0], window[1]);
+
+            assert_eq!(x.abs_diff(next_x) + y.abs_diff(next_y), 1);
+            assert!(map.get(next_x, next_y).unwrap() <= &(map.get(x, y).unwrap() + 1));
+        }
+    }
+
+    #[test]
+    fn test_render_path() {
+        let input = to_lines(EXAMPLE);
+
+        let rendered = render_part1(&input, &MovementRules::default(), None).unwrap();
+
+        assert_eq!(rendered.lines().count(), 5);
+        assert_eq!(rendered.chars().filter(|c| "<>^v".contains(*c)).count(), 31);
+        assert_eq!(rendered.lines().nth(2).unwrap().chars().nth(5), Some('E'));
+    }
+
+    #[test]
+    fn test_render_packed() {
+        let input = to_lines(EXAMPLE);
+        let rules = MovementRules::default();
+
+        let path = render_part1(&input, &rules, Some(Packing::HalfBlock)).unwrap();
+        assert_eq!(path.lines().count(), 3);
+        assert!(path.lines().all(|line| line.chars().count() == 8));
+        assert_eq!(path.lines().next(), Some("▀▀██████"));
+
+        let heatmap = render_heatmap(&input, &rules, Some(Packing::Braille)).unwrap();
+        assert_eq!(heatmap.lines().count(), 2);
+        assert!(heatmap.lines().all(|line| line.chars().count() == 4));
+    }
+
+    #[test]
+    fn test_multiple_starts_and_ends() {
+        let input = to_lines(
+            "\
+Sabqponm
+abcryxxl
+accsEExk
+acctuvwj
+Sbdefghi
+",
+        );
+
+        let map: ElevationMap = input.as_slice().try_into().unwrap();
+        assert_eq!(map.starts, vec![(0, 0), (0, 4)]);
+        assert_eq!(map.ends, vec![(4, 2), (5, 2)]);
+
+        let route = map
+            .best_route(
+                &map.starts,
+                &map.ends,
+                &MovementRules::default(),
+                Algorithm::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            route,
+            Route {
+                start: (0, 4),
+                end: (4, 2),
+                distance: 28
+            }
+        );
+    }
+
+    #[test]
+    fn test_custom_route() {
+        let input = to_lines(EXAMPLE);
+        let rules = MovementRules::default();
+
+        assert_eq!(
+            custom_route(&input, &[parse_point("0,4").unwrap()], &[], &rules).unwrap(),
+            Route {
+                start: (0, 4),
+                end: (5, 2),
+                distance: 29
+            }
+        );
+        let flat_rules = MovementRules {
+            max_climb: Some(0),
+            ..rules
+        };
+        assert!(matches!(
+            custom_route(&input, &[(0, 0)], &[], &flat_rules),
+            Err(Day12Error::NoPath)
+        ));
+        assert!(matches!(parse_point("3"), Err(Day12Error::InvalidPoint(_))));
+
+        for (from, to, point) in [
+            ((8, 0), (5, 2), (8, 0)),
+            ((0, 0), (0, 5), (0, 5)),
+            ((0, 0), (100, 100), (100, 100)),
+        ] {
+            assert!(matches!(
+                custom_route(&input, &[from], &[to], &rules),
+                Err(Day12Error::PointOutOfBounds(p)) if p == point
+            ));
+        }
+    }
+
+    #[test]
+    fn test_movement_rules() {
+        let rules = MovementRules {
+            max_climb: Some(2),
+            max_descent: Some(1),
+            diagonal: false,
+        };
+
+        assert!(rules.allows(b'a', b'c'));
+        assert!(!rules.allows(b'a', b'd'));
+        assert!(rules.allows(b'c', b'b'));
+        assert!(!rules.allows(b'c', b'a'));
+        assert!(MovementRules::default().allows(b'z', b'a'));
+    }
+
+    #[test]
+    fn test_movement_rules_diagonal() {
+        let input = to_lines(EXAMPLE);
+
+        let map: ElevationMap = input.as_slice().try_into().unwrap();
+        let rules = MovementRules {
+            diagonal: true,
+            ..MovementRules::default()
+        };
+
+        assert_eq!(map.neighbours((0, 0), true).len(), 3);
+        assert_eq!(map.neighbours((1, 1), true).len(), 8);
+        assert!(part1(&input, &rules, Algorithm::default()).unwrap() < 31);
+    }
+
+    #[test]
+    fn test_distance_field() {
+        let input = to_lines(EXAMPLE);
+
+        let map: ElevationMap = input.as_slice().try_into().unwrap();
+        let rules = MovementRules::default();
+        let distances = map.distance_field(&rules);
+
+        assert_eq!(distances[map.index_of(5, 2)], Some(0));
+        assert_eq!(distances[map.index_of(0, 0)], Some(31));
+        assert_eq!(
+            distances
+                .iter()
+                .zip(map.storage.iter())
+                .filter(|(_, &elevation)| elevation == b'a')
+                .filter_map(|(distance, _)| *distance)
+                .min(),
+            Some(29)
+        );
+    }
+
+    #[test]
+    fn test_render_heatmap() {
+        let input = to_lines(EXAMPLE);
+
+        let heatmap = render_heatmap(&input, &MovementRules::default(), None).unwrap();
+
+        assert_eq!(heatmap.lines().count(), 5);
+        assert_eq!(heatmap.lines().nth(2).unwrap().chars().nth(5), Some(' '));
+        assert_eq!(heatmap.lines().next().unwrap().chars().next(), Some('@'));
+    }
+
+    #[test]
+    fn test_parse_algorithm() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(
+                algorithm.to_string().parse::<Algorithm>().unwrap(),
+                algorithm
+            );
+        }
+        assert!(matches!(
+            "greedy".parse::<Algorithm>(),
+            Err(Day12Error::InvalidAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn test_compare() {
+        let input = to_lines(EXAMPLE);
+        let rules = MovementRules::default();
+
+        for (part, expected) in [(1, 31), (2, 29)] {
+            let (distance, comparisons) = compare(&input, &rules, part).unwrap();
+
+            assert_eq!(distance, expected);
+            assert_eq!(comparisons.len(), Algorithm::ALL.len());
+            assert!(comparisons
+                .iter()
+                .all(|comparison| comparison.distance == Some(expected)));
+
+            let expanded = |algorithm| {
+                comparisons
+                    .iter()
+                    .find(|comparison| comparison.algorithm == algorithm)
+                    .unwrap()
+                    .expanded
+            };
+            assert!(expanded(Algorithm::AStar) <= expanded(Algorithm::Dijkstra));
+        }
+    }
+
+    #[test]
+    fn test_algorithms_agree_on_random_maps() {
+        let mut rng = XorShift::new(0x9e3779b97f4a7c15);
+        let mut next = |bound: u64| rng.below(bound);
+
+        for _ in 0..200 {
+            let (width, height) = (1 + next(12) as usize, 1 + next(12) as usize);
+            let mut input: Vec<String> = (0..height)
+                .map(|_| (0..width).map(|_| (b'a' + next(6) as u8) as char).collect())
+                .collect();
+            input[next(height as u64) as usize].replace_range(0..1, "S");
+            input[next(height as u64) as usize].replace_range(width - 1..width, "E");
+
+            let rules = MovementRules {
+                max_climb: [None, Some(0), Some(1), Some(2)][next(4) as usize],
+                max_descent: [None, Some(1)][next(2) as usize],
+                diagonal: next(2) == 0,
+            };
+
+            for part in [1, 2] {
+                match compare(&input, &rules, part) {
+                    Ok(_)
+                    | Err(
+                        Day12Error::NoStartPosition
+                        | Day12Error::NoEndPosition
+                        | Day12Error::NoPath,
+                    ) => {}
+                    Err(e) => panic!("{e:?} on {input:?} with {rules:?}"),
+                }
+            }
+        }
     }
 
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part1(&input).unwrap(), 31);
+        for algorithm in Algorithm::ALL {
+            assert_eq!(
+                part1(&input, &MovementRules::default(), algorithm).unwrap(),
+                31
+            );
+        }
     }
 
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part2(&input).unwrap(), 29);
+        for algorithm in Algorithm::ALL {
+            assert_eq!(
+                part2(&input, &MovementRules::default(), algorithm).unwrap(),
+                29
+            );
+        }
     }
 }
@@ -1,14 +1,28 @@
-use std::{cmp::Ordering, collections::VecDeque, io, num::ParseIntError, str::FromStr};
-
-use aoc::read_lines;
+use std::{cmp::Ordering, fmt, io, num::ParseIntError, str::FromStr};
+
+use aoc::{cli::Args, puzzles::part_label, read_lines};
+use nom::{
+    branch::alt,
+    character::complete::{char, digit1},
+    combinator::{all_consuming, cut, map, map_res, opt, recognize, value},
+    multi::separated_list1,
+    sequence::{pair, preceded, terminated},
+    IResult,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day13Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
-    InvalidPackageValue,
+    InvalidPacket(usize),
+    #[cfg(feature = "serde")]
+    InvalidJsonPacket(serde_json::Value),
     InvalidNumberOfPackets,
     DividerNotFound,
+    PairNotFound(usize),
 }
 
 impl From<io::Error> for Day13Error {
@@ -26,67 +40,118 @@ impl From<ParseIntError> for Day13Error {
 const INPUT_PATH: &str = "inputs/day13.txt";
 
 fn main() -> Result<(), Day13Error> {
+    let args = Args::from_env();
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    if args.flag("--format") {
+        print!("{}", format_packets(&input)?);
+        return Ok(());
+    }
+
+    println!("{}: {:?}", part_label(13, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(13, 2), part2(&input)?);
+
+    if let Some(pair) = args.parse("--explain")? {
+        print!("{}", explain_pair(&input, pair)?);
+    }
 
     Ok(())
 }
 
+// `==` is structural, so `1 != [1]` even though `cmp` puts them level. Use
+// `cmp` to ask whether the puzzle's ordering can tell two packets apart.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
 enum PacketValue {
-    Integer(u32),
+    Integer(i64),
     List(Vec<PacketValue>),
 }
 
-fn parse_packet_value(buffer: &mut VecDeque<char>) -> Result<PacketValue, Day13Error> {
-    match buffer.front() {
-        Some('[') => {
-            buffer.pop_front();
-
-            let mut v = vec![];
+fn parse_packet_list(input: &str) -> IResult<&str, Vec<PacketValue>> {
+    preceded(
+        char('['),
+        cut(alt((
+            value(vec![], char(']')),
+            terminated(
+                separated_list1(char(','), cut(parse_packet_value)),
+                char(']'),
+            ),
+        ))),
+    )(input)
+}
 
-            loop {
-                if let Some(']') = buffer.front() {
-                    buffer.pop_front();
-                    break;
-                }
+fn parse_packet_value(input: &str) -> IResult<&str, PacketValue> {
+    alt((
+        map(
+            map_res(recognize(pair(opt(char('-')), digit1)), str::parse),
+            PacketValue::Integer,
+        ),
+        map(parse_packet_list, PacketValue::List),
+    ))(input)
+}
 
-                v.push(parse_packet_value(buffer)?);
+impl FromStr for PacketValue {
+    type Err = Day13Error;
 
-                match buffer.pop_front() {
-                    Some(',') => {}
-                    Some(']') => break,
-                    _ => return Err(Day13Error::InvalidPackageValue),
-                }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match all_consuming(parse_packet_value)(s) {
+            Ok((_, value)) => Ok(value),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                Err(Day13Error::InvalidPacket(s.len() - e.input.len()))
             }
-
-            Ok(PacketValue::List(v))
+            Err(nom::Err::Incomplete(_)) => Err(Day13Error::InvalidPacket(s.len())),
         }
-        Some(_) => {
-            let mut s = String::new();
-            while let Some(&c) = buffer.front() {
-                if c != ']' && c != ',' {
-                    buffer.pop_front();
-                    s.push(c);
-                } else {
-                    break;
-                }
-            }
+    }
+}
 
-            Ok(PacketValue::Integer(s.parse()?))
+#[cfg(feature = "serde")]
+impl TryFrom<serde_json::Value> for PacketValue {
+    type Error = Day13Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        use serde_json::Value;
+
+        match value {
+            Value::Number(ref n) => n
+                .as_i64()
+                .map(PacketValue::Integer)
+                .ok_or(Day13Error::InvalidJsonPacket(value)),
+            Value::Array(values) => Ok(PacketValue::List(
+                values
+                    .into_iter()
+                    .map(|value| value.try_into())
+                    .collect::<Result<_, _>>()?,
+            )),
+            value => Err(Day13Error::InvalidJsonPacket(value)),
         }
-        None => Err(Day13Error::InvalidPackageValue),
     }
 }
 
-impl FromStr for PacketValue {
-    type Err = Day13Error;
+#[cfg(feature = "serde")]
+impl From<PacketValue> for serde_json::Value {
+    fn from(value: PacketValue) -> Self {
+        match value {
+            PacketValue::Integer(n) => n.into(),
+            PacketValue::List(values) => values.into_iter().map(Self::from).collect(),
+        }
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut buffer = s.chars().collect();
-        parse_packet_value(&mut buffer)
+impl fmt::Display for PacketValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PacketValue::Integer(n) => write!(f, "{}", n),
+            PacketValue::List(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+        }
     }
 }
 
@@ -98,17 +163,72 @@ impl PartialOrd for PacketValue {
 
 impl Ord for PacketValue {
     fn cmp(&self, other: &Self) -> Ordering {
-        use PacketValue::*;
+        compare(self, other, 0, None)
+    }
+}
+
+fn trace_line(trace: &mut Option<&mut Vec<String>>, depth: usize, line: impl FnOnce() -> String) {
+    if let Some(trace) = trace {
+        trace.push(format!("{}- {}", "  ".repeat(depth), line()));
+    }
+}
 
-        fn compare_lists(left: &Vec<PacketValue>, right: &Vec<PacketValue>) -> Ordering {
+fn compare(
+    left: &PacketValue,
+    right: &PacketValue,
+    depth: usize,
+    mut trace: Option<&mut Vec<String>>,
+) -> Ordering {
+    use PacketValue::*;
+
+    trace_line(&mut trace, depth, || {
+        format!("Compare {} vs {}", left, right)
+    });
+
+    match (left, right) {
+        (Integer(l), Integer(r)) => {
+            let ordering = l.cmp(r);
+            match ordering {
+                Ordering::Less => trace_line(&mut trace, depth + 1, || {
+                    "Left side is smaller, so inputs are in the right order".to_owned()
+                }),
+                Ordering::Greater => trace_line(&mut trace, depth + 1, || {
+                    "Right side is smaller, so inputs are not in the right order".to_owned()
+                }),
+                Ordering::Equal => {}
+            }
+            ordering
+        }
+        (Integer(l), List(_)) => {
+            let left = List(vec![Integer(*l)]);
+            trace_line(&mut trace, depth + 1, || {
+                format!("Mixed types; convert left to {} and retry comparison", left)
+            });
+            compare(&left, right, depth + 1, trace)
+        }
+        (List(_), Integer(r)) => {
+            let right = List(vec![Integer(*r)]);
+            trace_line(&mut trace, depth + 1, || {
+                format!(
+                    "Mixed types; convert right to {} and retry comparison",
+                    right
+                )
+            });
+            compare(left, &right, depth + 1, trace)
+        }
+        (List(left), List(right)) => {
             for (i, left_value) in left.iter().enumerate() {
                 if let Some(right_value) = right.get(i) {
-                    match left_value.cmp(right_value) {
+                    match compare(left_value, right_value, depth + 1, trace.as_deref_mut()) {
                         Ordering::Less => return Ordering::Less,
                         Ordering::Equal => {}
                         Ordering::Greater => return Ordering::Greater,
                     }
                 } else {
+                    trace_line(&mut trace, depth + 1, || {
+                        "Right side ran out of items, so inputs are not in the right order"
+                            .to_owned()
+                    });
                     return Ordering::Greater;
                 }
             }
@@ -116,29 +236,28 @@ impl Ord for PacketValue {
             if left.len() == right.len() {
                 Ordering::Equal
             } else {
+                trace_line(&mut trace, depth + 1, || {
+                    "Left side ran out of items, so inputs are in the right order".to_owned()
+                });
                 Ordering::Less
             }
         }
-
-        match self {
-            Integer(left) => match other {
-                Integer(right) => left.cmp(right),
-                List(right) => {
-                    let left = vec![Integer(*left)];
-                    compare_lists(&left, right)
-                }
-            },
-            List(left) => match other {
-                Integer(right) => {
-                    let right = vec![Integer(*right)];
-                    compare_lists(left, &right)
-                }
-                List(right) => compare_lists(left, right),
-            },
-        }
     }
 }
 
+fn explain_pair(input: &[String], pair: usize) -> Result<String, Day13Error> {
+    let packet_pairs = parse_packet_pairs(input)?;
+    let (left, right) = pair
+        .checked_sub(1)
+        .and_then(|index| packet_pairs.get(index))
+        .ok_or(Day13Error::PairNotFound(pair))?;
+
+    let mut trace = vec![format!("== Pair {} ==", pair)];
+    compare(left, right, 0, Some(&mut trace));
+
+    Ok(trace.join("\n") + "\n")
+}
+
 fn parse_packet_pair(lines: &[String]) -> Result<(PacketValue, PacketValue), Day13Error> {
     match lines {
         [left, right] => Ok((left.parse()?, right.parse()?)),
@@ -146,14 +265,14 @@ fn parse_packet_pair(lines: &[String]) -> Result<(PacketValue, PacketValue), Day
     }
 }
 
-fn parse_packet_pairs(input: &Vec<String>) -> Result<Vec<(PacketValue, PacketValue)>, Day13Error> {
+fn parse_packet_pairs(input: &[String]) -> Result<Vec<(PacketValue, PacketValue)>, Day13Error> {
     input
         .split(|line| line.is_empty())
-        .map(|lines| parse_packet_pair(lines))
+        .map(parse_packet_pair)
         .collect()
 }
 
-fn part1(input: &Vec<String>) -> Result<usize, Day13Error> {
+fn part1(input: &[String]) -> Result<usize, Day13Error> {
     let packet_pairs = parse_packet_pairs(input)?;
 
     Ok(packet_pairs
@@ -164,7 +283,7 @@ fn part1(input: &Vec<String>) -> Result<usize, Day13Error> {
         .sum())
 }
 
-fn parse_packets(input: &Vec<String>) -> Result<Vec<PacketValue>, Day13Error> {
+fn parse_packets(input: &[String]) -> Result<Vec<PacketValue>, Day13Error> {
     input
         .iter()
         .filter(|line| !line.is_empty())
@@ -172,7 +291,21 @@ fn parse_packets(input: &Vec<String>) -> Result<Vec<PacketValue>, Day13Error> {
         .collect()
 }
 
-fn part2(input: &Vec<String>) -> Result<usize, Day13Error> {
+fn format_packets(input: &[String]) -> Result<String, Day13Error> {
+    let mut formatted = String::new();
+
+    for line in input {
+        if !line.is_empty() {
+            let packet: PacketValue = line.trim().parse()?;
+            formatted.push_str(&packet.to_string());
+        }
+        formatted.push('\n');
+    }
+
+    Ok(formatted)
+}
+
+fn part2(input: &[String]) -> Result<usize, Day13Error> {
     let mut packets = parse_packets(input)?;
     let divider_a: PacketValue = "[[2]]".parse()?;
     let divider_b: PacketValue = "[[6]]".parse()?;
@@ -206,6 +339,7 @@ mod tests {
     use super::*;
 
     use aoc::to_lines;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = "\
 [1,1,3,1,1]
@@ -248,6 +382,131 @@ mod tests {
         assert_eq!(value, expected)
     }
 
+    #[test]
+    fn test_parse_invalid_packet() {
+        for (packet, offset) in [
+            ("[1,2]x", 5),
+            ("[1,x]", 3),
+            ("[1,[2]", 6),
+            ("[1 2]", 2),
+            ("", 0),
+            ("[99999999999999999999]", 1),
+            ("[1,--2]", 3),
+            ("[+2]", 1),
+        ] {
+            match packet.parse::<PacketValue>() {
+                Err(Day13Error::InvalidPacket(o)) => assert_eq!(o, offset, "{}", packet),
+                result => panic!("unexpected result for {:?}: {:?}", packet, result),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_interop() {
+        let packet: PacketValue = "[1,[2,[3,[4,[5,6,7]]]],8,9]".parse().unwrap();
+
+        let json = serde_json::to_string(&packet).unwrap();
+        assert_eq!(json, "[1,[2,[3,[4,[5,6,7]]]],8,9]");
+        assert_eq!(serde_json::from_str::<PacketValue>(&json).unwrap(), packet);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(PacketValue::try_from(value.clone()).unwrap(), packet);
+        assert_eq!(serde_json::Value::from(packet), value);
+
+        assert!(matches!(
+            PacketValue::try_from(serde_json::json!([1, "2"])),
+            Err(Day13Error::InvalidJsonPacket(_))
+        ));
+        assert!(matches!(
+            PacketValue::try_from(serde_json::json!([1.5])),
+            Err(Day13Error::InvalidJsonPacket(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_negative_packet() {
+        let value: PacketValue = "[-3,[0,-12]]".parse().unwrap();
+        let expected = PacketValue::List(vec![
+            PacketValue::Integer(-3),
+            PacketValue::List(vec![PacketValue::Integer(0), PacketValue::Integer(-12)]),
+        ]);
+
+        assert_eq!(value, expected)
+    }
+
+    #[test]
+    fn test_negative_packet_cmp() {
+        for (left, right, expected) in [
+            ("[-1]", "[1]", Ordering::Less),
+            ("[-1]", "[-2]", Ordering::Greater),
+            ("[[-5],3]", "[-5,2]", Ordering::Greater),
+            ("[-7,-7]", "[[-7]]", Ordering::Greater),
+            ("[0]", "[-0]", Ordering::Equal),
+        ] {
+            let left: PacketValue = left.parse().unwrap();
+            let right: PacketValue = right.parse().unwrap();
+
+            assert_eq!(left.cmp(&right), expected, "{:?} vs {:?}", left, right);
+        }
+    }
+
+    #[test]
+    fn test_display_packet() {
+        for line in to_lines(EXAMPLE).iter().filter(|line| !line.is_empty()) {
+            let packet: PacketValue = line.parse().unwrap();
+
+            assert_eq!(&packet.to_string(), line);
+        }
+
+        let packet: PacketValue = "[-3,[[]]]".parse().unwrap();
+        assert_eq!(packet.to_string(), "[-3,[[]]]");
+    }
+
+    #[test]
+    fn test_format_packets() {
+        let input = to_lines("  [-0,[2]] \n\n[[]]\n");
+        assert_eq!(format_packets(&input).unwrap(), "[0,[2]]\n\n[[]]\n");
+
+        let input = to_lines(EXAMPLE);
+        assert_eq!(format_packets(&input).unwrap(), EXAMPLE);
+    }
+
+    #[test]
+    fn test_explain_pair() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(
+            explain_pair(&input, 2).unwrap(),
+            "\
+== Pair 2 ==
+- Compare [[1],[2,3,4]] vs [[1],4]
+  - Compare [1] vs [1]
+    - Compare 1 vs 1
+  - Compare [2,3,4] vs 4
+    - Mixed types; convert right to [4] and retry comparison
+    - Compare [2,3,4] vs [4]
+      - Compare 2 vs 4
+        - Left side is smaller, so inputs are in the right order
+"
+        );
+
+        assert_eq!(
+            explain_pair(&input, 7).unwrap(),
+            "\
+== Pair 7 ==
+- Compare [[[]]] vs [[]]
+  - Compare [[]] vs []
+    - Right side ran out of items, so inputs are not in the right order
+"
+        );
+
+        assert!(matches!(
+            explain_pair(&input, 9),
+            Err(Day13Error::PairNotFound(9))
+        ));
+    }
+
     #[test]
     fn test_packet_cmp() {
         let left: PacketValue = "[1,[2,[3,[4,[5,6,7]]]],8,9]".parse().unwrap();
@@ -256,6 +515,69 @@ mod tests {
         assert_eq!(left.cmp(&right), Ordering::Greater);
     }
 
+    fn packet() -> impl Strategy<Value = PacketValue> {
+        // Small integers and shallow lists make ties and mixed-type
+        // comparisons common, which is where `compare` is most delicate.
+        let integer = (-1i64..4).prop_map(PacketValue::Integer);
+
+        integer.prop_recursive(4, 32, 4, |inner| {
+            prop::collection::vec(inner, 0..4).prop_map(PacketValue::List)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_packet_round_trip(packet in packet()) {
+            let printed = packet.to_string();
+            let parsed: PacketValue = printed.parse().unwrap();
+
+            prop_assert_eq!(parsed, packet);
+        }
+
+        #[test]
+        fn test_packet_order_antisymmetric(a in packet(), b in packet()) {
+            let ordering = a.cmp(&b);
+
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+            prop_assert_eq!(b.cmp(&a), ordering.reverse());
+            prop_assert_eq!(a.partial_cmp(&b), Some(ordering));
+            // Identical packets must compare level, but not the other way round.
+            prop_assert!(a != b || ordering == Ordering::Equal);
+        }
+
+        #[test]
+        fn test_packet_order_transitive(packets in prop::collection::vec(packet(), 3..12)) {
+            for a in &packets {
+                for b in &packets {
+                    for c in &packets {
+                        if a <= b && b <= c {
+                            prop_assert!(a <= c, "{} <= {} <= {}", a, b, c);
+                            let level = |x: &PacketValue, y| x.cmp(y) == Ordering::Equal;
+                            prop_assert_eq!(level(a, c), level(a, b) && level(b, c));
+                        }
+                    }
+                }
+            }
+
+            let mut sorted = packets;
+            sorted.sort();
+            prop_assert!(sorted.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+
+    #[test]
+    fn test_packet_order_equivalence() {
+        let packet = |s: &str| s.parse::<PacketValue>().unwrap();
+
+        for (left, right) in [("1", "[1]"), ("[[2]]", "[2]"), ("[[],3]", "[[],[[3]]]")] {
+            assert_eq!(packet(left).cmp(&packet(right)), Ordering::Equal);
+            assert_ne!(packet(left), packet(right));
+        }
+        for (left, right) in [("[1,1]", "[1]"), ("[]", "[[]]")] {
+            assert_ne!(packet(left).cmp(&packet(right)), Ordering::Equal);
+        }
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
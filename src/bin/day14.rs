@@ -1,15 +1,29 @@
-use std::{collections::HashMap, io, num::ParseIntError, str::FromStr};
-
-use aoc::read_lines;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    io,
+    num::ParseIntError,
+    str::FromStr,
+};
+
+use aoc::{
+    cli::Args,
+    puzzles::part_label,
+    read_lines,
+    render::{InvalidPacking, Packing, Svg},
+};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day14Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
     InvalidPoint,
     NotEnoughPoints,
     InvalidRockPart,
-    InvalidBoundaries,
+    InvalidPart(usize),
+    InvalidPacking(InvalidPacking),
+    InvalidStopCondition(String),
 }
 
 impl From<io::Error> for Day14Error {
@@ -24,13 +38,99 @@ impl From<ParseIntError> for Day14Error {
     }
 }
 
+impl From<InvalidPacking> for Day14Error {
+    fn from(e: InvalidPacking) -> Self {
+        Self::InvalidPacking(e)
+    }
+}
+
+const AIR_COLOUR: &str = "#1b1b2f";
+const ROCK_COLOUR: &str = "#6b5b4b";
+const SAND_COLOUR: &str = "#e8c170";
+
 const INPUT_PATH: &str = "inputs/day14.txt";
 
 fn main() -> Result<(), Day14Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    let args = Args::from_env();
+
+    let mut sources: Vec<Point> = args
+        .values("--source")
+        .into_iter()
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+    if sources.is_empty() {
+        sources.push(SOURCE);
+    }
+
+    let part1_counts = part1_resuming(&input, &sources)?;
+    println!(
+        "{}: {:?}",
+        part_label(14, 1),
+        part1_counts.iter().sum::<usize>()
+    );
+    if sources.len() > 1 {
+        println!("Part 1 per source: {:?}", part1_counts);
+    }
+
+    println!(
+        "{}: {:?}",
+        part_label(14, 2),
+        part2_flood_fill(&input, &sources)?
+    );
+    if sources.len() > 1 {
+        println!("Part 2 per source: {:?}", part2(&input, &sources)?);
+    }
+
+    let render_path = args.value("--render");
+    let packing = args.parse("--packing")?;
+
+    if render_path.is_some() || packing.is_some() {
+        let cave_bottom = match args.parse("--part")?.unwrap_or(1) {
+            1 => CaveBottom::Abyss,
+            2 => CaveBottom::Floor(args.parse("--floor-offset")?.unwrap_or(FLOOR_OFFSET)),
+            part => return Err(Day14Error::InvalidPart(part)),
+        };
+        let stop = args.parse("--stop")?.unwrap_or_default();
+        let (cave, _) = simulate_resuming(&input, &sources, cave_bottom, stop)?;
+
+        if let Some(path) = render_path {
+            render_cave(&cave).save(path)?;
+        }
+        if let Some(packing) = packing {
+            print!("{}", render_cave_packed(&cave, packing));
+        }
+    }
+
+    let floor_offset = args.parse("--floor-offset")?;
+    let stop = args.parse("--stop")?;
+
+    if floor_offset.is_some() || stop.is_some() {
+        let floor_offset = floor_offset.unwrap_or(FLOOR_OFFSET);
+        let stop = stop.unwrap_or_default();
+
+        let (_, drops) =
+            simulate_resuming(&input, &sources, CaveBottom::Floor(floor_offset), stop)?;
+        println!(
+            "Variant (floor offset {}, stop when {}): {:?}",
+            floor_offset,
+            stop,
+            drops.sand_counts.iter().sum::<usize>()
+        );
+    }
+
+    if args.flag("--stats") {
+        for (part, method, stats) in simulation_stats(&input, &sources)? {
+            println!("Part {} ({}):", part, method);
+            print!("{}", stats);
+        }
+    }
+
+    if args.flag("--verify") {
+        println!("Part 1 (simulation): {:?}", part1(&input, &sources)?);
+        println!("Part 2 (simulation): {:?}", part2(&input, &sources)?);
+    }
 
     Ok(())
 }
@@ -38,6 +138,8 @@ fn main() -> Result<(), Day14Error> {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Point(usize, usize);
 
+const SOURCE: Point = Point(500, 0);
+
 impl FromStr for Point {
     type Err = Day14Error;
 
@@ -123,269 +225,605 @@ impl RockStructure {
     }
 }
 
-fn parse_rock_structures(input: &Vec<String>) -> Result<Vec<RockStructure>, Day14Error> {
+fn parse_rock_structures(input: &[String]) -> Result<Vec<RockStructure>, Day14Error> {
     input.iter().map(|line| line.parse()).collect()
 }
 
-#[derive(Debug)]
-struct Boundaries {
-    left: usize,
-    top: usize,
-    right: usize,
-    bottom: usize,
+fn find_bottom(rock_structures: &[RockStructure], sources: &[Point]) -> usize {
+    rock_structures
+        .iter()
+        .flat_map(|structure| &structure.points)
+        .chain(sources)
+        .map(|Point(_, y)| *y)
+        .max()
+        .unwrap_or(0)
 }
 
-fn find_boundaries(rock_structures: &Vec<RockStructure>) -> Boundaries {
-    let top = 0;
-    let mut right = 500;
-    let mut bottom = 0;
-    let mut left = 500;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TileContents {
+    Air,
+    Rock,
+    Sand,
+}
+
+// The floor lies this many rows below the lowest rock (or source).
+const FLOOR_OFFSET: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaveBottom {
+    Abyss,
+    Floor(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum StopCondition {
+    // Each source stops once a grain cannot come to rest below it.
+    #[default]
+    SourceBlocked,
+    // All sources stop once this many grains have come to rest in total.
+    Grains(usize),
+    // All sources stop as soon as a falling grain passes below this row.
+    PastY(usize),
+}
+
+impl FromStr for StopCondition {
+    type Err = Day14Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Day14Error::InvalidStopCondition(s.to_owned());
 
-    for structure in rock_structures {
-        for Point(x, y) in &structure.points {
-            right = right.max(*x);
-            bottom = bottom.max(*y);
-            left = left.min(*x);
+        match s.split_once(':') {
+            None if s == "blocked" => Ok(Self::SourceBlocked),
+            Some(("grains", n)) => Ok(Self::Grains(n.parse().map_err(|_| invalid())?)),
+            Some(("past", y)) => Ok(Self::PastY(y.parse().map_err(|_| invalid())?)),
+            _ => Err(invalid()),
         }
     }
+}
 
-    Boundaries {
-        left,
-        top,
-        right,
-        bottom,
+impl Display for StopCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SourceBlocked => write!(f, "blocked"),
+            Self::Grains(n) => write!(f, "grains:{}", n),
+            Self::PastY(y) => write!(f, "past:{}", y),
+        }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum TileContents {
-    Air,
-    Rock,
-    Sand,
+enum Grain {
+    Rested,
+    Blocked,
+    Fell,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 struct Cave {
-    left: usize,
-    top: usize,
-    width: usize,
-    height: usize,
-    contents: Vec<TileContents>,
+    bottom: usize,
+    cave_bottom: CaveBottom,
+    past_y: Option<usize>,
+    contents: HashMap<Point, TileContents>,
 }
 
 impl Cave {
-    fn new(
-        Boundaries {
-            left,
-            top,
-            right,
+    fn new(bottom: usize, cave_bottom: CaveBottom) -> Self {
+        Self {
             bottom,
-        }: Boundaries,
-    ) -> Result<Self, Day14Error> {
-        if left <= right && top <= bottom {
-            let width = right - left + 1;
-            let height = bottom - top + 1;
-
-            Ok(Self {
-                left,
-                top,
-                width,
-                height,
-                contents: vec![TileContents::Air; width * height],
-            })
-        } else {
-            Err(Day14Error::InvalidBoundaries)
+            cave_bottom,
+            past_y: None,
+            contents: HashMap::new(),
+        }
+    }
+
+    fn floor(&self) -> Option<usize> {
+        match self.cave_bottom {
+            CaveBottom::Abyss => None,
+            CaveBottom::Floor(offset) => Some(self.bottom + offset),
         }
     }
 
-    fn get_index(&self, Point(x, y): &Point) -> Option<usize> {
-        if x >= &self.left && y >= &self.top {
-            let x = x - self.left;
-            let y = y - self.top;
+    fn is_in_abyss(&self, Point(_, y): &Point) -> bool {
+        let abyss = match self.cave_bottom {
+            CaveBottom::Abyss => Some(self.bottom),
+            CaveBottom::Floor(_) => None,
+        };
+
+        [abyss, self.past_y]
+            .into_iter()
+            .flatten()
+            .any(|limit| *y > limit)
+    }
 
-            Some(x + y * self.width)
+    fn get(&self, point: &Point) -> TileContents {
+        if Some(point.1) == self.floor() {
+            TileContents::Rock
         } else {
-            None
+            self.contents
+                .get(point)
+                .copied()
+                .unwrap_or(TileContents::Air)
         }
     }
 
-    fn get(&self, point: &Point) -> Option<&TileContents> {
-        let index = self.get_index(point)?;
-
-        self.contents.get(index)
+    fn set(&mut self, point: Point, value: TileContents) {
+        self.contents.insert(point, value);
     }
 
-    fn get_mut(&mut self, point: &Point) -> Option<&mut TileContents> {
-        let index = self.get_index(point)?;
+    fn tiles(&self) -> Vec<(Point, TileContents)> {
+        self.contents
+            .iter()
+            .map(|(point, tile)| (point.clone(), *tile))
+            .collect()
+    }
 
-        self.contents.get_mut(index)
+    // Down, then down-left, then down-right. Nothing exists left of x = 0, so
+    // sand on that edge can only fall straight down or to the right.
+    fn below(Point(x, y): &Point) -> impl Iterator<Item = Point> {
+        [
+            Some(Point(*x, y + 1)),
+            x.checked_sub(1).map(|left| Point(left, y + 1)),
+            Some(Point(x + 1, y + 1)),
+        ]
+        .into_iter()
+        .flatten()
     }
 
-    fn simulate_sand(&mut self) -> bool {
+    // Both simulations also return the number of steps the grain took.
+    fn simulate_sand(&mut self, source: &Point) -> (Grain, usize) {
         use TileContents::*;
 
-        let mut x = 500;
-        let mut y = 0;
+        let mut position = source.clone();
+        let mut steps = 0;
 
         loop {
-            match self.get(&Point(x, y + 1)) {
-                Some(Air) => {
-                    y = y + 1;
-                }
-                Some(_) => match self.get(&Point(x - 1, y + 1)) {
-                    Some(Air) => {
-                        x = x - 1;
-                        y = y + 1;
+            steps += 1;
+
+            if self.is_in_abyss(&position) {
+                return (Grain::Fell, steps);
+            }
+
+            match Self::below(&position).find(|next| self.get(next) == Air) {
+                Some(next) => position = next,
+                None => match self.get(&position) {
+                    Air => {
+                        self.set(position, Sand);
+                        return (Grain::Rested, steps);
                     }
-                    Some(_) => match self.get(&Point(x + 1, y + 1)) {
-                        Some(Air) => {
-                            x = x + 1;
-                            y = y + 1;
-                        }
-                        Some(_) => match self.get_mut(&Point(x, y)) {
-                            Some(tile) => {
-                                debug_assert_eq!(*tile, Air);
-
-                                *tile = Sand;
-                                return true;
-                            }
-                            None => return false,
-                        },
-                        None => return false,
-                    },
-                    None => return false,
+                    _ => return (Grain::Blocked, steps),
                 },
-                None => return false,
             }
         }
     }
+
+    fn simulate_sand_resuming(&mut self, path: &mut Vec<Point>) -> (Grain, usize) {
+        use TileContents::*;
+
+        let mut steps = 0;
+
+        while let Some(Point(x, y)) = path.last().cloned() {
+            steps += 1;
+
+            if self.is_in_abyss(&Point(x, y)) {
+                return (Grain::Fell, steps);
+            }
+
+            let next = Self::below(&Point(x, y)).find(|next| self.get(next) == Air);
+
+            match next {
+                Some(next) => path.push(next),
+                None => {
+                    path.pop();
+
+                    if self.get(&Point(x, y)) == Air {
+                        self.set(Point(x, y), Sand);
+                        return (Grain::Rested, steps);
+                    }
+                    return (Grain::Blocked, steps);
+                }
+            }
+        }
+
+        (Grain::Blocked, steps)
+    }
+
+    // Sand from other sources can land on a saved path, so only the part
+    // above the first filled tile is still a valid place to resume from.
+    fn discard_stale_path(&self, path: &mut Vec<Point>) {
+        if let Some(filled) = path
+            .iter()
+            .position(|point| self.get(point) != TileContents::Air)
+        {
+            path.truncate(filled);
+        }
+    }
+
+    // Returns every tile sand comes to rest on, each visited exactly once.
+    fn flood_fill(&self, sources: &[Point]) -> HashSet<Point> {
+        let mut reached: HashSet<_> = sources
+            .iter()
+            .filter(|source| self.get(source) == TileContents::Air)
+            .cloned()
+            .collect();
+        let mut stack: Vec<_> = reached.iter().cloned().collect();
+
+        while let Some(point) = stack.pop() {
+            for next in Self::below(&point) {
+                if self.get(&next) == TileContents::Air
+                    && !self.is_in_abyss(&next)
+                    && !reached.contains(&next)
+                {
+                    reached.insert(next.clone());
+                    stack.push(next);
+                }
+            }
+        }
+
+        reached
+    }
 }
 
-fn parse_cave(input: &Vec<String>) -> Result<Cave, Day14Error> {
+fn parse_cave(
+    input: &[String],
+    sources: &[Point],
+    cave_bottom: CaveBottom,
+    stop: StopCondition,
+) -> Result<Cave, Day14Error> {
     let rock_structures = parse_rock_structures(input)?;
 
-    let boundaries = find_boundaries(&rock_structures);
-    let mut cave = Cave::new(boundaries)?;
+    let bottom = find_bottom(&rock_structures, sources);
+    let mut cave = Cave::new(bottom, cave_bottom);
+    if let StopCondition::PastY(y) = stop {
+        cave.past_y = Some(y);
+    }
 
     for rock_structure in rock_structures {
         for point in rock_structure.rock_points()? {
-            if let Some(tile) = cave.get_mut(&point) {
-                *tile = TileContents::Rock;
-            }
+            cave.set(point, TileContents::Rock);
         }
     }
 
     Ok(cave)
 }
 
-fn part1(input: &Vec<String>) -> Result<usize, Day14Error> {
-    let mut cave = parse_cave(input)?;
+#[derive(Debug, PartialEq, Eq)]
+struct Drops {
+    sand_counts: Vec<usize>,
+    // Every step taken, including by the grains that did not come to rest.
+    steps: usize,
+    // Steps taken by each grain that came to rest, in drop order.
+    grain_steps: Vec<usize>,
+}
+
+fn drop_round_robin(
+    sources: usize,
+    stop: StopCondition,
+    mut drop_sand: impl FnMut(usize) -> (Grain, usize),
+) -> Drops {
+    let mut sand_counts = vec![0; sources];
+    let mut active = vec![true; sources];
+    let mut total = 0;
+    let mut steps = 0;
+    let mut grain_steps = vec![];
+
+    'dropping: while active.contains(&true) {
+        for source in 0..sources {
+            if stop == StopCondition::Grains(total) {
+                break 'dropping;
+            }
+            if !active[source] {
+                continue;
+            }
+
+            let (grain, grain_step_count) = drop_sand(source);
+            steps += grain_step_count;
 
-    let mut sand_count = 0;
-    while cave.simulate_sand() {
-        sand_count += 1;
+            match grain {
+                Grain::Rested => {
+                    sand_counts[source] += 1;
+                    total += 1;
+                    grain_steps.push(grain_step_count);
+                }
+                Grain::Fell if matches!(stop, StopCondition::PastY(_)) => break 'dropping,
+                Grain::Blocked | Grain::Fell => active[source] = false,
+            }
+        }
     }
 
-    Ok(sand_count)
+    Drops {
+        sand_counts,
+        steps,
+        grain_steps,
+    }
 }
 
-struct Cave2 {
-    height: usize,
-    contents: HashMap<Point, TileContents>,
+fn simulate(
+    input: &[String],
+    sources: &[Point],
+    cave_bottom: CaveBottom,
+    stop: StopCondition,
+) -> Result<(Cave, Drops), Day14Error> {
+    let mut cave = parse_cave(input, sources, cave_bottom, stop)?;
+
+    let drops = drop_round_robin(sources.len(), stop, |source| {
+        cave.simulate_sand(&sources[source])
+    });
+
+    Ok((cave, drops))
 }
 
-impl Cave2 {
-    fn new(
-        Boundaries {
-            left,
-            top,
-            right,
-            bottom,
-        }: Boundaries,
-    ) -> Result<Self, Day14Error> {
-        if left <= right && top <= bottom {
-            let height = bottom - top + 1;
-
-            Ok(Self {
-                height,
-                contents: HashMap::new(),
-            })
-        } else {
-            Err(Day14Error::InvalidBoundaries)
+fn simulate_resuming(
+    input: &[String],
+    sources: &[Point],
+    cave_bottom: CaveBottom,
+    stop: StopCondition,
+) -> Result<(Cave, Drops), Day14Error> {
+    let mut cave = parse_cave(input, sources, cave_bottom, stop)?;
+    let mut paths: Vec<_> = sources.iter().map(|source| vec![source.clone()]).collect();
+
+    let drops = drop_round_robin(sources.len(), stop, |source| {
+        if sources.len() > 1 {
+            cave.discard_stale_path(&mut paths[source]);
         }
-    }
+        cave.simulate_sand_resuming(&mut paths[source])
+    });
 
-    fn get(&self, point: &Point) -> &TileContents {
-        if point.1 == self.height + 1 {
-            &TileContents::Rock
-        } else {
-            self.contents.get(point).unwrap_or(&TileContents::Air)
+    Ok((cave, drops))
+}
+
+fn part1(input: &[String], sources: &[Point]) -> Result<Vec<usize>, Day14Error> {
+    let (_, drops) = simulate(
+        input,
+        sources,
+        CaveBottom::Abyss,
+        StopCondition::SourceBlocked,
+    )?;
+
+    Ok(drops.sand_counts)
+}
+
+fn part1_resuming(input: &[String], sources: &[Point]) -> Result<Vec<usize>, Day14Error> {
+    let (_, drops) = simulate_resuming(
+        input,
+        sources,
+        CaveBottom::Abyss,
+        StopCondition::SourceBlocked,
+    )?;
+
+    Ok(drops.sand_counts)
+}
+
+fn part2(input: &[String], sources: &[Point]) -> Result<Vec<usize>, Day14Error> {
+    let (_, drops) = simulate(
+        input,
+        sources,
+        CaveBottom::Floor(FLOOR_OFFSET),
+        StopCondition::SourceBlocked,
+    )?;
+
+    Ok(drops.sand_counts)
+}
+
+fn part2_flood_fill(input: &[String], sources: &[Point]) -> Result<usize, Day14Error> {
+    let cave = parse_cave(
+        input,
+        sources,
+        CaveBottom::Floor(FLOOR_OFFSET),
+        StopCondition::SourceBlocked,
+    )?;
+
+    Ok(cave.flood_fill(sources).len())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Work {
+    Drops {
+        steps: usize,
+        grain_steps: Vec<usize>,
+    },
+    // The fill never drops a grain, so it only has cells to count.
+    FloodFill {
+        cells_visited: usize,
+    },
+}
+
+struct SimulationStats {
+    grains: usize,
+    work: Work,
+    // Rows between the highest grain and the floor, or the lowest rock.
+    max_height: usize,
+    // Top-left and bottom-right corners of the settled sand.
+    sand_bounds: Option<(Point, Point)>,
+}
+
+impl SimulationStats {
+    fn new<'a>(cave: &Cave, sand: impl IntoIterator<Item = &'a Point>, work: Work) -> Self {
+        let mut grains = 0;
+        let mut sand_bounds: Option<(Point, Point)> = None;
+
+        for &Point(x, y) in sand {
+            grains += 1;
+            sand_bounds = Some(match sand_bounds {
+                None => (Point(x, y), Point(x, y)),
+                Some((Point(left, top), Point(right, bottom))) => (
+                    Point(left.min(x), top.min(y)),
+                    Point(right.max(x), bottom.max(y)),
+                ),
+            });
         }
-    }
 
-    fn set(&mut self, point: Point, value: TileContents) {
-        self.contents.insert(point, value);
+        let base = cave.floor().unwrap_or(cave.bottom + 1);
+        let max_height = sand_bounds
+            .as_ref()
+            .map_or(0, |(Point(_, top), _)| base - top);
+
+        Self {
+            grains,
+            work,
+            max_height,
+            sand_bounds,
+        }
     }
 
-    fn simulate_sand(&mut self) -> bool {
-        use TileContents::*;
+    fn from_drops(cave: &Cave, drops: Drops) -> Self {
+        let sand: Vec<_> = cave
+            .tiles()
+            .into_iter()
+            .filter(|(_, tile)| *tile == TileContents::Sand)
+            .map(|(point, _)| point)
+            .collect();
+
+        let work = Work::Drops {
+            steps: drops.steps,
+            grain_steps: drops.grain_steps,
+        };
+        Self::new(cave, &sand, work)
+    }
 
-        let mut x = 500;
-        let mut y = 0;
+    fn from_flood_fill(cave: &Cave, reached: &HashSet<Point>) -> Self {
+        let work = Work::FloodFill {
+            cells_visited: reached.len(),
+        };
+        Self::new(cave, reached, work)
+    }
+}
 
-        loop {
-            match self.get(&Point(x, y + 1)) {
-                Air => {
-                    y = y + 1;
+impl Display for SimulationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Grains: {}", self.grains)?;
+        match &self.work {
+            Work::Drops { steps, grain_steps } => {
+                writeln!(f, "Steps: {}", steps)?;
+
+                let mut grain_steps = grain_steps.clone();
+                grain_steps.sort_unstable();
+                match (grain_steps.first(), grain_steps.last()) {
+                    (Some(min), Some(max)) => writeln!(
+                        f,
+                        "Steps per grain: min {}, median {}, mean {:.1}, max {}",
+                        min,
+                        grain_steps[grain_steps.len() / 2],
+                        grain_steps.iter().sum::<usize>() as f64 / grain_steps.len() as f64,
+                        max
+                    )?,
+                    _ => writeln!(f, "Steps per grain: no grains")?,
                 }
-                _ => match self.get(&Point(x - 1, y + 1)) {
-                    Air => {
-                        x = x - 1;
-                        y = y + 1;
-                    }
-                    _ => match self.get(&Point(x + 1, y + 1)) {
-                        Air => {
-                            x = x + 1;
-                            y = y + 1;
-                        }
-                        _ => match self.get(&Point(x, y)) {
-                            Air => {
-                                self.set(Point(x, y), Sand);
-                                return true;
-                            }
-                            _ => return false,
-                        },
-                    },
-                },
             }
+            Work::FloodFill { cells_visited } => writeln!(f, "Cells visited: {}", cells_visited)?,
+        }
+
+        writeln!(f, "Max pile height: {}", self.max_height)?;
+        match &self.sand_bounds {
+            Some((Point(left, top), Point(right, bottom))) => writeln!(
+                f,
+                "Sand bounding box: {},{} to {},{}",
+                left, top, right, bottom
+            ),
+            None => writeln!(f, "Sand bounding box: empty"),
         }
     }
 }
 
-fn parse_cave_2(input: &Vec<String>) -> Result<Cave2, Day14Error> {
-    let rock_structures = parse_rock_structures(input)?;
+fn simulation_stats(
+    input: &[String],
+    sources: &[Point],
+) -> Result<Vec<(usize, &'static str, SimulationStats)>, Day14Error> {
+    let stop = StopCondition::SourceBlocked;
+    let mut stats = vec![];
+
+    for (part, cave_bottom) in [(1, CaveBottom::Abyss), (2, CaveBottom::Floor(FLOOR_OFFSET))] {
+        let (cave, drops) = simulate(input, sources, cave_bottom, stop)?;
+        stats.push((
+            part,
+            "simulation",
+            SimulationStats::from_drops(&cave, drops),
+        ));
+
+        let (cave, drops) = simulate_resuming(input, sources, cave_bottom, stop)?;
+        stats.push((
+            part,
+            "path resume",
+            SimulationStats::from_drops(&cave, drops),
+        ));
+    }
 
-    let boundaries = find_boundaries(&rock_structures);
-    let mut cave = Cave2::new(boundaries)?;
+    let cave = parse_cave(input, sources, CaveBottom::Floor(FLOOR_OFFSET), stop)?;
+    let reached = cave.flood_fill(sources);
+    stats.push((
+        2,
+        "flood fill",
+        SimulationStats::from_flood_fill(&cave, &reached),
+    ));
 
-    for rock_structure in rock_structures {
-        for point in rock_structure.rock_points()? {
-            cave.set(point, TileContents::Rock);
-        }
-    }
+    Ok(stats)
+}
 
-    Ok(cave)
+// Returns the left, top, right and bottom edges of the rendered area.
+fn cave_bounds(cave: &Cave) -> (i64, i64, i64, i64) {
+    let tiles = cave.tiles();
+    let floor = cave.floor();
+
+    let xs = tiles.iter().map(|(Point(x, _), _)| *x as i64);
+    let ys = tiles.iter().map(|(Point(_, y), _)| *y as i64);
+
+    let left = xs.clone().min().unwrap_or(0) - 1;
+    let right = xs.max().unwrap_or(0) + 1;
+    let top = ys.clone().min().unwrap_or(0).min(0);
+    let bottom = ys
+        .max()
+        .unwrap_or(0)
+        .max(floor.map_or(0, |floor| floor as i64));
+
+    (left, top, right, bottom)
 }
 
-fn part2(input: &Vec<String>) -> Result<usize, Day14Error> {
-    let mut cave = parse_cave_2(input)?;
+fn render_cave(cave: &Cave) -> Svg {
+    let tiles = cave.tiles();
+    let floor = cave.floor();
+    let (left, top, right, bottom) = cave_bounds(cave);
 
-    let mut sand_count = 0;
-    while cave.simulate_sand() {
-        sand_count += 1;
+    let width = right - left + 1;
+    let height = bottom - top + 1;
+
+    let mut svg = Svg::new(left, top, width, height);
+    svg.rect(left, top, width, height, AIR_COLOUR);
+
+    for (Point(x, y), tile) in tiles {
+        let colour = match tile {
+            TileContents::Air => continue,
+            TileContents::Rock => ROCK_COLOUR,
+            TileContents::Sand => SAND_COLOUR,
+        };
+
+        svg.rect(x as i64, y as i64, 1, 1, colour);
     }
 
-    Ok(sand_count)
+    if let Some(floor) = floor {
+        svg.rect(left, floor as i64, width, 1, ROCK_COLOUR);
+    }
+
+    svg
+}
+
+// Rock, sand and the floor are all drawn filled; only air is left blank.
+fn render_cave_packed(cave: &Cave, packing: Packing) -> String {
+    let (left, top, right, bottom) = cave_bounds(cave);
+    let floor = cave.floor();
+
+    let contents: HashMap<_, _> = cave.tiles().into_iter().collect();
+
+    let width = (right - left + 1) as usize;
+    let height = (bottom - top + 1) as usize;
+
+    packing.pack(width, height, |x, y| {
+        let x = (left + x as i64) as usize;
+        let y = (top + y as i64) as usize;
+
+        floor == Some(y)
+            || contents
+                .get(&Point(x, y))
+                .is_some_and(|tile| *tile != TileContents::Air)
+    })
 }
 
 #[cfg(test)]
@@ -431,36 +869,355 @@ mod tests {
 
         let input = to_lines(EXAMPLE);
 
+        let rocks = [
+            Point(498, 4),
+            Point(498, 5),
+            Point(498, 6),
+            Point(497, 6),
+            Point(496, 6),
+            Point(503, 4),
+            Point(502, 4),
+            Point(502, 5),
+            Point(502, 6),
+            Point(502, 7),
+            Point(502, 8),
+            Point(502, 9),
+            Point(501, 9),
+            Point(500, 9),
+            Point(499, 9),
+            Point(498, 9),
+            Point(497, 9),
+            Point(496, 9),
+            Point(495, 9),
+            Point(494, 9),
+        ];
         let expected = Cave {
-            left: 494,
-            top: 0,
-            width: 10,
-            height: 10,
-            contents: vec![
-                Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air,
-                Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air,
-                Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Air, Rock, Air, Air, Air,
-                Rock, Rock, Air, Air, Air, Air, Rock, Air, Air, Air, Rock, Air, Air, Air, Rock,
-                Rock, Rock, Air, Air, Air, Rock, Air, Air, Air, Air, Air, Air, Air, Air, Air, Rock,
-                Air, Air, Air, Air, Air, Air, Air, Air, Air, Rock, Air, Rock, Rock, Rock, Rock,
-                Rock, Rock, Rock, Rock, Rock, Air,
-            ],
+            bottom: 9,
+            cave_bottom: CaveBottom::Abyss,
+            past_y: None,
+            contents: rocks.into_iter().map(|point| (point, Rock)).collect(),
         };
 
-        assert_eq!(parse_cave(&input).unwrap(), expected);
+        assert_eq!(
+            parse_cave(
+                &input,
+                &[SOURCE],
+                CaveBottom::Abyss,
+                StopCondition::SourceBlocked
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_cave(
+                &input,
+                &[SOURCE],
+                CaveBottom::Floor(FLOOR_OFFSET),
+                StopCondition::SourceBlocked
+            )
+            .unwrap()
+            .floor(),
+            Some(11)
+        );
     }
 
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part1(&input).unwrap(), 24);
+        assert_eq!(part1(&input, &[SOURCE]).unwrap(), vec![24]);
+    }
+
+    #[test]
+    fn test_part1_resuming() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(
+            part1_resuming(&input, &[SOURCE]).unwrap(),
+            part1(&input, &[SOURCE]).unwrap()
+        );
+        assert_eq!(
+            parse_cave(
+                &input,
+                &[SOURCE],
+                CaveBottom::Abyss,
+                StopCondition::SourceBlocked
+            )
+            .map(|mut cave| {
+                let mut path = vec![SOURCE];
+                while cave.simulate_sand_resuming(&mut path).0 == Grain::Rested {}
+                cave
+            })
+            .unwrap(),
+            parse_cave(
+                &input,
+                &[SOURCE],
+                CaveBottom::Abyss,
+                StopCondition::SourceBlocked
+            )
+            .map(|mut cave| {
+                while cave.simulate_sand(&SOURCE).0 == Grain::Rested {}
+                cave
+            })
+            .unwrap()
+        );
     }
 
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part2(&input).unwrap(), 93);
+        assert_eq!(part2(&input, &[SOURCE]).unwrap(), vec![93]);
+    }
+
+    #[test]
+    fn test_part2_flood_fill() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(
+            part2_flood_fill(&input, &[SOURCE]).unwrap(),
+            part2(&input, &[SOURCE]).unwrap().iter().sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_multiple_sources() {
+        let input = to_lines(EXAMPLE);
+        let sources = [SOURCE, Point(497, 0)];
+
+        let part1_counts = part1(&input, &sources).unwrap();
+        assert_eq!(part1_resuming(&input, &sources).unwrap(), part1_counts);
+        assert_eq!(part1_counts, vec![22, 2]);
+
+        let part2_counts = part2(&input, &sources).unwrap();
+        assert_eq!(
+            part2_flood_fill(&input, &sources).unwrap(),
+            part2_counts.iter().sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_multiple_sources_sharing_paths() {
+        let input = to_lines(EXAMPLE);
+        let stop = StopCondition::SourceBlocked;
+
+        for (sources, abyss, floor) in [
+            (vec![SOURCE, Point(501, 0)], vec![14, 12], vec![47, 57]),
+            (
+                vec![Point(499, 0), SOURCE, Point(501, 0)],
+                vec![9, 9, 8],
+                vec![34, 36, 45],
+            ),
+        ] {
+            for (cave_bottom, expected) in [
+                (CaveBottom::Abyss, abyss),
+                (CaveBottom::Floor(FLOOR_OFFSET), floor),
+            ] {
+                let (cave, drops) = simulate(&input, &sources, cave_bottom, stop).unwrap();
+                let (resumed_cave, resumed) =
+                    simulate_resuming(&input, &sources, cave_bottom, stop).unwrap();
+
+                assert_eq!(drops.sand_counts, expected, "{:?}", sources);
+                assert_eq!(resumed.sand_counts, expected, "{:?}", sources);
+                assert_eq!(resumed_cave, cave);
+            }
+        }
+    }
+
+    #[test]
+    fn test_source_on_left_edge() {
+        let sources = [Point(0, 0)];
+
+        // Sand piles against x = 0 instead of spilling further left.
+        for (input, abyss, floor) in [("0,3 -> 3,3", 6, 6), ("9,3 -> 9,3", 0, 15)] {
+            let input = to_lines(input);
+
+            assert_eq!(part1(&input, &sources).unwrap(), vec![abyss]);
+            assert_eq!(part1_resuming(&input, &sources).unwrap(), vec![abyss]);
+            assert_eq!(part2(&input, &sources).unwrap(), vec![floor]);
+            assert_eq!(part2_flood_fill(&input, &sources).unwrap(), floor);
+        }
+    }
+
+    #[test]
+    fn test_parse_stop_condition() {
+        assert_eq!(
+            "blocked".parse::<StopCondition>().unwrap(),
+            StopCondition::SourceBlocked
+        );
+        assert_eq!(
+            "grains:10".parse::<StopCondition>().unwrap(),
+            StopCondition::Grains(10)
+        );
+        assert_eq!(
+            "past:7".parse::<StopCondition>().unwrap(),
+            StopCondition::PastY(7)
+        );
+
+        for stop in ["", "grains", "grains:", "past:-1", "forever:3"] {
+            assert!(matches!(
+                stop.parse::<StopCondition>(),
+                Err(Day14Error::InvalidStopCondition(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_floor_offset() {
+        let input = to_lines(EXAMPLE);
+        let floor_counts = |offset| {
+            let stop = StopCondition::SourceBlocked;
+            let (_, drops) = simulate(&input, &[SOURCE], CaveBottom::Floor(offset), stop).unwrap();
+            let (_, resumed) =
+                simulate_resuming(&input, &[SOURCE], CaveBottom::Floor(offset), stop).unwrap();
+            assert_eq!(drops.sand_counts, resumed.sand_counts);
+            drops.sand_counts
+        };
+
+        // The puzzle's floor is two rows below the lowest rock, at y = 11.
+        assert_eq!(floor_counts(FLOOR_OFFSET), vec![93]);
+        assert_ne!(floor_counts(FLOOR_OFFSET - 1), vec![93]);
+        assert_ne!(floor_counts(FLOOR_OFFSET + 1), vec![93]);
+    }
+
+    #[test]
+    fn test_stop_conditions() {
+        let input = to_lines(EXAMPLE);
+        let floor = CaveBottom::Floor(FLOOR_OFFSET);
+        let run = |cave_bottom, stop| {
+            let (_, drops) = simulate(&input, &[SOURCE], cave_bottom, stop).unwrap();
+            let (_, resumed) = simulate_resuming(&input, &[SOURCE], cave_bottom, stop).unwrap();
+            assert_eq!(drops.sand_counts, resumed.sand_counts);
+            drops.sand_counts
+        };
+
+        assert_eq!(run(floor, StopCondition::Grains(0)), vec![0]);
+        assert_eq!(run(floor, StopCondition::Grains(10)), vec![10]);
+        assert_eq!(run(floor, StopCondition::Grains(1000)), vec![93]);
+
+        // Stopping at the first grain below the lowest rock reproduces part 1.
+        assert_eq!(run(floor, StopCondition::PastY(9)), vec![24]);
+        assert_eq!(run(CaveBottom::Abyss, StopCondition::PastY(100)), vec![24]);
+        assert_eq!(run(floor, StopCondition::PastY(100)), vec![93]);
+
+        let sources = [SOURCE, Point(497, 0)];
+        let (_, drops) = simulate(&input, &sources, floor, StopCondition::Grains(5)).unwrap();
+        assert_eq!(drops.sand_counts, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_simulation_stats() {
+        let input = to_lines(EXAMPLE);
+
+        let stats = simulation_stats(&input, &[SOURCE]).unwrap();
+        let methods: Vec<_> = stats
+            .iter()
+            .map(|(part, method, stats)| (*part, *method, stats.grains))
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                (1, "simulation", 24),
+                (1, "path resume", 24),
+                (2, "simulation", 93),
+                (2, "path resume", 93),
+                (2, "flood fill", 93),
+            ]
+        );
+
+        let (_, _, part1) = &stats[0];
+        assert_eq!(part1.max_height, 8);
+        assert_eq!(part1.sand_bounds, Some((Point(495, 2), Point(501, 8))));
+
+        let (_, _, part2) = &stats[2];
+        assert_eq!(part2.max_height, 11);
+        assert_eq!(part2.sand_bounds, Some((Point(490, 0), Point(510, 10))));
+
+        // Resuming from saved paths needs strictly fewer steps than dropping
+        // every grain from the source.
+        for part in [&stats[0..2], &stats[2..4]] {
+            let steps: Vec<_> = part
+                .iter()
+                .map(|(_, _, stats)| match &stats.work {
+                    Work::Drops { steps, grain_steps } => {
+                        assert_eq!(grain_steps.len(), stats.grains);
+                        *steps
+                    }
+                    Work::FloodFill { .. } => panic!("expected a drop simulation"),
+                })
+                .collect();
+            assert!(steps[0] > steps[1]);
+            assert_eq!(part[1].2.sand_bounds, part[0].2.sand_bounds);
+        }
+
+        let (_, _, flood_fill) = &stats[4];
+        assert!(matches!(
+            flood_fill.work,
+            Work::FloodFill { cells_visited: 93 }
+        ));
+        assert_eq!(flood_fill.sand_bounds, part2.sand_bounds);
+
+        let report = flood_fill.to_string();
+        assert!(report.contains("Cells visited: 93"));
+        assert!(!report.contains("Steps"));
+    }
+
+    #[test]
+    fn test_render() {
+        let input = to_lines(EXAMPLE);
+
+        let (cave, _) = simulate_resuming(
+            &input,
+            &[SOURCE],
+            CaveBottom::Abyss,
+            StopCondition::SourceBlocked,
+        )
+        .unwrap();
+        let part1 = render_cave(&cave).to_text();
+        assert!(part1.contains(r#"viewBox="493 0 12 10""#));
+        assert_eq!(part1.matches(SAND_COLOUR).count(), 24);
+        assert_eq!(part1.matches(ROCK_COLOUR).count(), 20);
+
+        let (cave, _) = simulate_resuming(
+            &input,
+            &[SOURCE],
+            CaveBottom::Floor(FLOOR_OFFSET),
+            StopCondition::SourceBlocked,
+        )
+        .unwrap();
+        let part2 = render_cave(&cave).to_text();
+        assert!(part2.contains(r#"viewBox="489 0 23 12""#));
+        assert_eq!(part2.matches(SAND_COLOUR).count(), 93);
+        assert_eq!(part2.matches(ROCK_COLOUR).count(), 21);
+    }
+
+    #[test]
+    fn test_render_packed() {
+        let input = to_lines(EXAMPLE);
+
+        let (cave, _) = simulate_resuming(
+            &input,
+            &[SOURCE],
+            CaveBottom::Abyss,
+            StopCondition::SourceBlocked,
+        )
+        .unwrap();
+        let packed = render_cave_packed(&cave, Packing::HalfBlock);
+
+        assert_eq!(packed.lines().count(), 5);
+        assert!(packed.lines().all(|line| line.chars().count() == 12));
+        assert_eq!(packed.lines().nth(1), Some("      ▄█▄   "));
+
+        let (cave, _) = simulate_resuming(
+            &input,
+            &[SOURCE],
+            CaveBottom::Floor(FLOOR_OFFSET),
+            StopCondition::SourceBlocked,
+        )
+        .unwrap();
+        let packed = render_cave_packed(&cave, Packing::Braille);
+
+        assert_eq!(packed.lines().count(), 3);
+        assert!(packed.lines().all(|line| line.chars().count() == 12));
     }
 }
@@ -1,15 +1,24 @@
 use std::{io, num::ParseIntError, ops::RangeInclusive, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{
+    cli::Args,
+    puzzles::part_label,
+    read_lines,
+    render::Svg,
+    sensor::{row_coverage, Point, SensorError, SensorReport},
+};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day15Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
-    InvalidPoint,
-    InvalidSensorReport,
+    SensorError(SensorError),
     EmptyInput,
     BeaconNotFound,
+    UnexpectedUncoveredCount(i64),
+    FrequencyOverflow(Point),
+    InvalidAlgorithm(String),
 }
 
 impl From<io::Error> for Day15Error {
@@ -24,104 +33,67 @@ impl From<ParseIntError> for Day15Error {
     }
 }
 
+impl From<SensorError> for Day15Error {
+    fn from(e: SensorError) -> Self {
+        Self::SensorError(e)
+    }
+}
+
 const INPUT_PATH: &str = "inputs/day15.txt";
 
+const BACKGROUND_COLOUR: &str = "#1b1b2f";
+const SEARCH_AREA_COLOUR: &str = "#2e2e4f";
+const COVERAGE_COLOUR: &str = "#4a90d9";
+const SENSOR_COLOUR: &str = "#f5f5f5";
+const BEACON_COLOUR: &str = "#e8c170";
+const DISTRESS_BEACON_COLOUR: &str = "#e04848";
+
+const ROW: i64 = 2000000;
+const SEARCH_MIN: i64 = 0;
+const SEARCH_MAX: i64 = 4000000;
+const TUNING_MULTIPLIER: i64 = 4000000;
+
 fn main() -> Result<(), Day15Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input, 2000000)?);
-    println!("Part 2: {:?}", part2(&input, 0, 4000000)?);
-
-    Ok(())
-}
+    let args = Args::from_env();
+    let row = args.parse("--row")?.unwrap_or(ROW);
+    let search_min = args.parse("--min")?.unwrap_or(SEARCH_MIN);
+    let search_max = args.parse("--max")?.unwrap_or(SEARCH_MAX);
+    let multiplier = args.parse("--multiplier")?.unwrap_or(TUNING_MULTIPLIER);
+    let algorithm = args.parse("--algorithm")?.unwrap_or_default();
 
-#[derive(Debug, PartialEq, Eq)]
-struct Point {
-    x: i64,
-    y: i64,
-}
+    println!("{}: {:?}", part_label(15, 1), part1(&input, row)?);
 
-impl Point {
-    fn manhattan_distance(&self, other: &Self) -> i64 {
-        (self.x - other.x).abs() + (self.y - other.y).abs()
-    }
-}
+    let (beacon, frequency) = part2(&input, search_min, search_max, multiplier, algorithm)?;
+    println!("{}: {:?}", part_label(15, 2), frequency);
+    println!("Distress beacon: x={}, y={}", beacon.x, beacon.y);
 
-impl FromStr for Point {
-    type Err = Day15Error;
+    if args.flag("--all") {
+        let sensor_reports = parse_sensor_reports(&input)?;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v: Vec<_> = s.split(&['=', ',']).collect();
-        match v.as_slice() {
-            ["x", x, " y", y] => Ok(Self {
-                x: x.parse()?,
-                y: y.parse()?,
-            }),
-            _ => Err(Self::Err::InvalidPoint),
+        for (y, gap) in uncovered_positions(&sensor_reports, search_min, search_max) {
+            println!("Uncovered: x={}..={}, y={}", gap.start(), gap.end(), y);
         }
-    }
-}
 
-#[derive(Debug, PartialEq, Eq)]
-struct SensorReport {
-    sensor_position: Point,
-    beacon_position: Point,
-    exclusion_radius: i64,
-}
-
-impl SensorReport {
-    fn new(sensor_position: Point, beacon_position: Point) -> Self {
-        let exclusion_radius = sensor_position.manhattan_distance(&beacon_position);
-
-        Self {
-            sensor_position,
-            beacon_position,
-            exclusion_radius,
-        }
+        println!(
+            "Part 2 (exhaustive): {:?}",
+            part2_exhaustive(&input, search_min, search_max, multiplier)?
+        );
     }
 
-    fn range_at(&self, y: i64) -> Option<RangeInclusive<i64>> {
-        let Point {
-            x: sensor_x,
-            y: sensor_y,
-        } = self.sensor_position;
-
-        let remaining_distance = self.exclusion_radius - (y - sensor_y).abs();
-        if remaining_distance >= 0 {
-            Some(sensor_x - remaining_distance..=sensor_x + remaining_distance)
-        } else {
-            None
-        }
+    if let Some(path) = args.value("--render") {
+        render_coverage(&input, search_min, search_max, algorithm)?.save(path)?;
     }
-}
 
-impl FromStr for SensorReport {
-    type Err = Day15Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v: Vec<_> = s.split(':').collect();
-        match v.as_slice() {
-            [sensor, beacon] => {
-                let sensor_position = sensor
-                    .strip_prefix("Sensor at ")
-                    .ok_or(Self::Err::InvalidSensorReport)?;
-
-                let beacon_position = beacon
-                    .strip_prefix(" closest beacon is at ")
-                    .ok_or(Self::Err::InvalidSensorReport)?;
-
-                Ok(Self::new(
-                    sensor_position.parse()?,
-                    beacon_position.parse()?,
-                ))
-            }
-            _ => Err(Self::Err::InvalidSensorReport),
-        }
-    }
+    Ok(())
 }
 
-fn parse_sensor_reports(input: &Vec<String>) -> Result<Vec<SensorReport>, Day15Error> {
-    input.iter().map(|line| line.parse()).collect()
+fn parse_sensor_reports(input: &[String]) -> Result<Vec<SensorReport>, Day15Error> {
+    Ok(input
+        .iter()
+        .map(|line| line.parse())
+        .collect::<Result<_, _>>()?)
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -132,7 +104,7 @@ struct Boundaries {
     bottom: i64,
 }
 
-fn find_boundaries(sensor_reports: &Vec<SensorReport>) -> Option<Boundaries> {
+fn find_boundaries(sensor_reports: &[SensorReport]) -> Option<Boundaries> {
     let mut left: Option<i64> = None;
     let mut top: Option<i64> = None;
     let mut right: Option<i64> = None;
@@ -170,7 +142,7 @@ enum TileContents {
     Unknown,
 }
 
-fn get_tile_contents(sensor_reports: &Vec<SensorReport>, position: Point) -> TileContents {
+fn get_tile_contents(sensor_reports: &[SensorReport], position: Point) -> TileContents {
     use TileContents::*;
 
     for report in sensor_reports {
@@ -186,7 +158,7 @@ fn get_tile_contents(sensor_reports: &Vec<SensorReport>, position: Point) -> Til
     Unknown
 }
 
-fn part1(input: &Vec<String>, y: i64) -> Result<usize, Day15Error> {
+fn part1(input: &[String], y: i64) -> Result<usize, Day15Error> {
     let sensor_reports = parse_sensor_reports(input)?;
     let boundaries = find_boundaries(&sensor_reports).ok_or(Day15Error::EmptyInput)?;
 
@@ -199,47 +171,225 @@ fn part1(input: &Vec<String>, y: i64) -> Result<usize, Day15Error> {
         .count())
 }
 
-fn is_contained(ranges: &Vec<RangeInclusive<i64>>, candidate: &i64) -> bool {
-    for range in ranges {
-        if range.contains(candidate) {
-            return true;
+fn uncovered_positions(
+    sensor_reports: &[SensorReport],
+    search_min: i64,
+    search_max: i64,
+) -> Vec<(i64, RangeInclusive<i64>)> {
+    let mut uncovered = vec![];
+
+    for y in search_min..=search_max {
+        for gap in row_coverage(sensor_reports, y).gaps(search_min, search_max) {
+            uncovered.push((y, gap));
         }
     }
 
-    false
+    uncovered
+}
+
+fn tuning_frequency(beacon: &Point, multiplier: i64) -> Result<i64, Day15Error> {
+    beacon
+        .x
+        .checked_mul(multiplier)
+        .and_then(|frequency| frequency.checked_add(beacon.y))
+        .ok_or_else(|| Day15Error::FrequencyOverflow(beacon.clone()))
+}
+
+fn part2_exhaustive(
+    input: &[String],
+    search_min: i64,
+    search_max: i64,
+    multiplier: i64,
+) -> Result<i64, Day15Error> {
+    let sensor_reports = parse_sensor_reports(input)?;
+    let uncovered = uncovered_positions(&sensor_reports, search_min, search_max);
+
+    let count: i64 = uncovered
+        .iter()
+        .map(|(_, gap)| gap.end() - gap.start() + 1)
+        .sum();
+
+    match uncovered.as_slice() {
+        [(y, gap)] if count == 1 => tuning_frequency(
+            &Point {
+                x: *gap.start(),
+                y: *y,
+            },
+            multiplier,
+        ),
+        _ => Err(Day15Error::UnexpectedUncoveredCount(count)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Algorithm {
+    RowScan,
+    #[default]
+    Perimeter,
 }
 
-fn find_gap(ranges: &Vec<RangeInclusive<i64>>, search_min: i64, search_max: i64) -> Option<i64> {
-    for range in ranges {
-        let candidate_x = range.start() - 1;
-        if candidate_x >= search_min && !is_contained(ranges, &candidate_x) {
-            return Some(candidate_x);
+impl FromStr for Algorithm {
+    type Err = Day15Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "row-scan" => Ok(Self::RowScan),
+            "perimeter" => Ok(Self::Perimeter),
+            _ => Err(Self::Err::InvalidAlgorithm(s.to_owned())),
         }
+    }
+}
 
-        let candidate_x = range.end() + 1;
-        if candidate_x <= search_max && !is_contained(ranges, &candidate_x) {
-            return Some(candidate_x);
+fn find_beacon_row_scan(
+    sensor_reports: &[SensorReport],
+    search_min: i64,
+    search_max: i64,
+) -> Option<Point> {
+    for y in search_min..=search_max {
+        if let Some(gap) = row_coverage(sensor_reports, y)
+            .gaps(search_min, search_max)
+            .first()
+        {
+            return Some(Point { x: *gap.start(), y });
         }
     }
 
     None
 }
 
-fn part2(input: &Vec<String>, search_min: i64, search_max: i64) -> Result<i64, Day15Error> {
-    let sensor_reports = parse_sensor_reports(input)?;
+fn find_beacon_perimeter(
+    sensor_reports: &[SensorReport],
+    search_min: i64,
+    search_max: i64,
+) -> Option<Point> {
+    // Lines just outside each diamond, as y = x + a and y = -x + b.
+    let mut ascending = vec![];
+    let mut descending = vec![];
 
-    for y in search_min..=search_max {
-        let ranges = sensor_reports
-            .iter()
-            .filter_map(|report| report.range_at(y))
-            .collect();
+    for report in sensor_reports {
+        let Point { x, y } = report.sensor_position;
+        let radius = report.exclusion_radius + 1;
+
+        ascending.extend([y - x - radius, y - x + radius]);
+        descending.extend([y + x - radius, y + x + radius]);
+    }
+
+    for a in &ascending {
+        for b in &descending {
+            if (b - a) % 2 != 0 {
+                continue;
+            }
 
-        if let Some(x) = find_gap(&ranges, search_min, search_max) {
-            return Ok(x * 4000000 + y);
+            let candidate = Point {
+                x: (b - a) / 2,
+                y: (a + b) / 2,
+            };
+
+            let in_bounds = (search_min..=search_max).contains(&candidate.x)
+                && (search_min..=search_max).contains(&candidate.y);
+
+            if in_bounds
+                && sensor_reports.iter().all(|report| {
+                    candidate.manhattan_distance(&report.sensor_position) > report.exclusion_radius
+                })
+            {
+                return Some(candidate);
+            }
         }
     }
 
-    Err(Day15Error::BeaconNotFound)
+    None
+}
+
+fn find_beacon(
+    sensor_reports: &[SensorReport],
+    search_min: i64,
+    search_max: i64,
+    algorithm: Algorithm,
+) -> Option<Point> {
+    match algorithm {
+        Algorithm::RowScan => None,
+        // The gap can only miss every boundary intersection when it lies on
+        // the edge of the search area, so fall back to scanning rows.
+        Algorithm::Perimeter => find_beacon_perimeter(sensor_reports, search_min, search_max),
+    }
+    .or_else(|| find_beacon_row_scan(sensor_reports, search_min, search_max))
+}
+
+// The beacon comes back with its frequency, as it is easier to check by hand.
+fn part2(
+    input: &[String],
+    search_min: i64,
+    search_max: i64,
+    multiplier: i64,
+    algorithm: Algorithm,
+) -> Result<(Point, i64), Day15Error> {
+    let sensor_reports = parse_sensor_reports(input)?;
+
+    let beacon = find_beacon(&sensor_reports, search_min, search_max, algorithm)
+        .ok_or(Day15Error::BeaconNotFound)?;
+    let frequency = tuning_frequency(&beacon, multiplier)?;
+
+    Ok((beacon, frequency))
+}
+
+fn render_coverage(
+    input: &[String],
+    search_min: i64,
+    search_max: i64,
+    algorithm: Algorithm,
+) -> Result<Svg, Day15Error> {
+    let sensor_reports = parse_sensor_reports(input)?;
+    let Boundaries {
+        left,
+        top,
+        right,
+        bottom,
+    } = find_boundaries(&sensor_reports).ok_or(Day15Error::EmptyInput)?;
+
+    let width = right - left + 1;
+    let height = bottom - top + 1;
+    let marker_radius = (width.max(height) / 200).max(1);
+
+    let mut svg = Svg::new(left, top, width, height);
+    svg.rect(left, top, width, height, BACKGROUND_COLOUR);
+    svg.rect(
+        search_min,
+        search_min,
+        search_max - search_min + 1,
+        search_max - search_min + 1,
+        SEARCH_AREA_COLOUR,
+    );
+
+    for report in &sensor_reports {
+        let Point { x, y } = report.sensor_position;
+        let radius = report.exclusion_radius;
+
+        svg.polygon(
+            &[
+                (x, y - radius),
+                (x + radius, y),
+                (x, y + radius),
+                (x - radius, y),
+            ],
+            COVERAGE_COLOUR,
+            0.4,
+        );
+    }
+
+    for report in &sensor_reports {
+        let sensor = &report.sensor_position;
+        let beacon = &report.beacon_position;
+
+        svg.circle(sensor.x, sensor.y, marker_radius, SENSOR_COLOUR);
+        svg.circle(beacon.x, beacon.y, marker_radius, BEACON_COLOUR);
+    }
+
+    if let Some(Point { x, y }) = find_beacon(&sensor_reports, search_min, search_max, algorithm) {
+        svg.circle(x, y, marker_radius * 2, DISTRESS_BEACON_COLOUR);
+    }
+
+    Ok(svg)
 }
 
 #[cfg(test)]
@@ -302,10 +452,75 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3
         assert_eq!(part1(&input, 10).unwrap(), 26);
     }
 
+    #[test]
+    fn test_find_beacon_perimeter() {
+        let input = to_lines(EXAMPLE);
+        let sensor_reports = parse_sensor_reports(&input).unwrap();
+
+        assert_eq!(
+            find_beacon_perimeter(&sensor_reports, 0, 20),
+            Some(Point { x: 14, y: 11 })
+        );
+    }
+
+    #[test]
+    fn test_render_coverage() {
+        let input = to_lines(EXAMPLE);
+
+        let svg = render_coverage(&input, 0, 20, Algorithm::Perimeter)
+            .unwrap()
+            .to_text();
+
+        assert!(svg.contains(r#"viewBox="-8 -10 37 37""#));
+        assert_eq!(svg.matches("<polygon").count(), 14);
+        assert!(svg.contains(&format!(
+            r#"<circle cx="14" cy="11" r="2" fill="{}"/>"#,
+            DISTRESS_BEACON_COLOUR
+        )));
+    }
+
+    #[test]
+    fn test_uncovered_positions() {
+        let input = to_lines(EXAMPLE);
+        let sensor_reports = parse_sensor_reports(&input).unwrap();
+
+        assert_eq!(
+            uncovered_positions(&sensor_reports, 0, 20),
+            vec![(11, 14..=14)]
+        );
+        assert_eq!(
+            part2_exhaustive(&input, 0, 20, TUNING_MULTIPLIER).unwrap(),
+            56000011
+        );
+        assert!(matches!(
+            part2_exhaustive(&input, 0, 30, TUNING_MULTIPLIER),
+            Err(Day15Error::UnexpectedUncoveredCount(_))
+        ));
+    }
+
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part2(&input, 0, 20).unwrap(), 56000011);
+        for algorithm in [Algorithm::RowScan, Algorithm::Perimeter] {
+            assert_eq!(
+                part2(&input, 0, 20, TUNING_MULTIPLIER, algorithm).unwrap(),
+                (Point { x: 14, y: 11 }, 56000011)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tuning_frequency() {
+        let input = to_lines(EXAMPLE);
+
+        let (beacon, frequency) = part2(&input, 0, 20, 1, Algorithm::default()).unwrap();
+        assert_eq!(frequency, 25);
+
+        assert_eq!(tuning_frequency(&beacon, 100).unwrap(), 1411);
+        assert!(matches!(
+            tuning_frequency(&beacon, i64::MAX),
+            Err(Day15Error::FrequencyOverflow(_))
+        ));
     }
 }
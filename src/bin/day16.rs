@@ -1,20 +1,34 @@
 use std::{
-    collections::{HashMap, HashSet},
-    io,
+    collections::{BTreeMap, HashMap},
+    fmt, io,
     num::ParseIntError,
     str::FromStr,
+    time::Instant,
 };
 
-use aoc::read_lines;
-use regex::Regex;
+#[cfg(feature = "interrupt")]
+use std::sync::{
+    atomic::{self, AtomicBool},
+    Arc,
+};
+
+use aoc::{cli::Args, puzzles::part_label, read_lines, timing::time_repeated};
+
+#[cfg(feature = "webhook")]
+use aoc::webhook;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day16Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
     ParseValveError,
     EmptyInput,
     ValveNotFound,
+    TooManyValves,
+    NoAgents,
+    #[cfg(feature = "interrupt")]
+    CtrlcError(ctrlc::Error),
 }
 
 impl From<io::Error> for Day16Error {
@@ -29,13 +43,115 @@ impl From<ParseIntError> for Day16Error {
     }
 }
 
+#[cfg(feature = "interrupt")]
+impl From<ctrlc::Error> for Day16Error {
+    fn from(e: ctrlc::Error) -> Self {
+        Self::CtrlcError(e)
+    }
+}
+
 const INPUT_PATH: &str = "inputs/day16.txt";
 
 fn main() -> Result<(), Day16Error> {
+    let args = Args::from_env();
+
+    // Parses generated lines, so it doesn't need an input.
+    if let Some(lines) = args.parse("--parse-benchmark")? {
+        parse_benchmark(lines)?;
+        return Ok(());
+    }
+
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    if let Some(runs) = args.parse("--repeat")? {
+        if let Some(timings) = time_repeated(runs, || part1(&input)) {
+            println!("{}: {timings}", part_label(16, 1));
+        }
+        if let Some(timings) = time_repeated(runs, || part2(&input)) {
+            println!("{}: {timings}", part_label(16, 2));
+        }
+
+        return Ok(());
+    }
+
+    // The anytime search stands in for the full solve, which is the slow part
+    // worth interrupting.
+    #[cfg(feature = "interrupt")]
+    if args.flag("--anytime") {
+        let agents = args.parse("--agents")?.unwrap_or(1);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handler_stop = stop.clone();
+        ctrlc::set_handler(move || handler_stop.store(true, atomic::Ordering::Relaxed))?;
+
+        let pressure_released = anytime_max_pressure(
+            &input,
+            agents,
+            args.parse("--time")?.unwrap_or(30),
+            &stop,
+            // Routes are only split between several agents once the search stops.
+            |best| match agents {
+                1 => println!("Best pressure so far: {}", best),
+                _ => println!(
+                    "Best single-agent route so far: {} (combined for {} agents at the end)",
+                    best, agents
+                ),
+            },
+        )?;
+
+        if stop.load(atomic::Ordering::Relaxed) {
+            println!("Interrupted, best found: {}", pressure_released);
+        } else {
+            println!("Search complete: {}", pressure_released);
+        }
+
+        return Ok(());
+    }
+
+    #[cfg(not(feature = "interrupt"))]
+    if args.flag("--anytime") {
+        eprintln!("--anytime requires the interrupt feature");
+    }
+
+    #[cfg(feature = "webhook")]
+    let start = Instant::now();
+    let answer = part1(&input)?;
+    println!("{}: {:?}", part_label(16, 1), answer);
+    #[cfg(feature = "webhook")]
+    webhook::notify_completion(16, 1, &answer, start.elapsed());
+
+    #[cfg(feature = "webhook")]
+    let start = Instant::now();
+    let answer = part2(&input)?;
+    println!("{}: {:?}", part_label(16, 2), answer);
+    #[cfg(feature = "webhook")]
+    webhook::notify_completion(16, 2, &answer, start.elapsed());
+
+    let agents = args.parse("--agents")?;
+    let time_limit = args.parse("--time")?;
+    if agents.is_some() || time_limit.is_some() {
+        let agents = agents.unwrap_or(1);
+        let time_limit = time_limit.unwrap_or(30);
+
+        println!(
+            "{} agent(s), {} minutes: {:?}",
+            agents,
+            time_limit,
+            max_pressure(&input, agents, time_limit)?
+        );
+    }
+
+    if args.flag("--plan") {
+        let plan = optimal_plan(&input, agents.unwrap_or(1), time_limit.unwrap_or(30))?;
+
+        print!("{}", plan);
+        println!("Total pressure released: {}", plan.pressure_released);
+    }
+
+    if args.flag("--stats") {
+        let (_, stats) = part1_with_stats(&input)?;
+        println!("Part 1 cache: {} hits, {} misses", stats.hits, stats.misses);
+    }
 
     Ok(())
 }
@@ -51,90 +167,78 @@ impl FromStr for Valve {
     type Err = Day16Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let valve_regex =
-            Regex::new(r"^Valve ([A-Z]+) has flow rate=([0-9]+); tunnels? leads? to valves? ([A-Z]+(?:, [A-Z]+)*)$")
-                .unwrap();
-
-        if let Some(captures) = valve_regex.captures(s) {
-            let label = captures[1].to_string();
-            let flow_rate = captures[2].parse()?;
-            let tunnels = captures[3].split(", ").map(|s| s.to_string()).collect();
-
-            Ok(Self {
-                label,
-                flow_rate,
-                tunnels,
-            })
-        } else {
-            Err(Self::Err::ParseValveError)
+        fn is_label(s: &str) -> bool {
+            !s.is_empty() && s.bytes().all(|b| b.is_ascii_uppercase())
         }
-    }
-}
 
-fn parse_valves(input: &Vec<String>) -> Result<HashMap<String, Valve>, Day16Error> {
-    input
-        .iter()
-        .map(|line| line.parse())
-        .map(|maybe_valve| maybe_valve.map(|valve: Valve| (valve.label.clone(), valve)))
-        .collect()
-}
+        let (label, rest) = s
+            .strip_prefix("Valve ")
+            .and_then(|rest| rest.split_once(" has flow rate="))
+            .ok_or(Self::Err::ParseValveError)?;
+        let (flow_rate, tunnels) = rest.split_once("; ").ok_or(Self::Err::ParseValveError)?;
+        let tunnels = tunnels
+            .strip_prefix("tunnels lead to valves ")
+            .or_else(|| tunnels.strip_prefix("tunnel leads to valve "))
+            .ok_or(Self::Err::ParseValveError)?;
+
+        let tunnels: Vec<_> = tunnels.split(", ").map(|s| s.to_string()).collect();
+        if !is_label(label) || !tunnels.iter().all(|tunnel| is_label(tunnel)) {
+            return Err(Self::Err::ParseValveError);
+        }
 
-#[derive(Debug, Clone)]
-enum Action {
-    OpenValve,
-    MoveTo(String),
-    NoOp,
+        Ok(Self {
+            label: label.to_string(),
+            flow_rate: flow_rate.parse()?,
+            tunnels,
+        })
+    }
 }
 
-#[derive(Debug, Clone)]
-struct VolcanoState<'a> {
-    valves: &'a HashMap<String, Valve>,
-    current_position: &'a Valve,
-    open_valves: HashSet<&'a Valve>,
-    pressure_released: u64,
+fn generate_valve_lines(lines: usize) -> Vec<String> {
+    let label = |i: usize| {
+        let i = i % (26 * 26);
+        format!(
+            "{}{}",
+            (b'A' + (i / 26) as u8) as char,
+            (b'A' + (i % 26) as u8) as char
+        )
+    };
+
+    (0..lines)
+        .map(|i| {
+            format!(
+                "Valve {} has flow rate={}; tunnels lead to valves {}, {}",
+                label(i),
+                i % 25,
+                label(i + 1),
+                label(i + 2)
+            )
+        })
+        .collect()
 }
 
-impl<'a> VolcanoState<'a> {
-    fn new(
-        valves: &'a HashMap<String, Valve>,
-        starting_position: &String,
-    ) -> Result<Self, Day16Error> {
-        let current_position = valves
-            .get(starting_position)
-            .ok_or(Day16Error::EmptyInput)?;
+fn parse_benchmark(lines: usize) -> Result<(), Day16Error> {
+    let input = generate_valve_lines(lines);
 
-        Ok(Self {
-            valves,
-            current_position,
-            open_valves: HashSet::new(),
-            pressure_released: 0,
-        })
+    let start = Instant::now();
+    for line in &input {
+        line.parse::<Valve>()?;
     }
+    println!("Parsed {} valves in {:?}", lines, start.elapsed());
 
-    fn tick(&mut self, action: Action) -> Result<(), Day16Error> {
-        for v in &self.open_valves {
-            self.pressure_released += v.flow_rate;
-        }
-
-        match action {
-            Action::OpenValve => {
-                self.open_valves.insert(self.current_position);
-                Ok(())
-            }
-            Action::MoveTo(label) => {
-                debug_assert!(self.current_position.tunnels.contains(&label));
+    Ok(())
+}
 
-                let next_position = self.valves.get(&label).ok_or(Day16Error::ValveNotFound)?;
-                self.current_position = next_position;
-                Ok(())
-            }
-            Action::NoOp => Ok(()),
-        }
-    }
+fn parse_valves(input: &[String]) -> Result<BTreeMap<String, Valve>, Day16Error> {
+    input
+        .iter()
+        .map(|line| line.parse())
+        .map(|maybe_valve| maybe_valve.map(|valve: Valve| (valve.label.clone(), valve)))
+        .collect()
 }
 
 fn all_shortest_paths(
-    valves: &HashMap<String, Valve>,
+    valves: &BTreeMap<String, Valve>,
 ) -> HashMap<String, HashMap<String, Vec<String>>> {
     let mut distances: HashMap<(&String, &String), u64> = HashMap::new();
     let mut next: HashMap<(&String, &String), &String> = HashMap::new();
@@ -215,263 +319,587 @@ fn all_shortest_paths(
     result
 }
 
-fn generate_strategies(
-    time_limit: usize,
-    valves: &HashMap<String, Valve>,
-    valves_to_open: HashSet<&Valve>,
-    current_position: &String,
-) -> Result<Vec<Vec<Action>>, Day16Error> {
-    let shortest_paths = all_shortest_paths(valves);
-    let mut strategies = vec![];
+#[derive(Debug, PartialEq, Eq)]
+struct ContractedGraph {
+    labels: Vec<String>,
+    flow_rates: Vec<u64>,
+    distances: Vec<Vec<Option<u64>>>,
+    start: usize,
+}
+
+impl ContractedGraph {
+    fn new(
+        valves: &BTreeMap<String, Valve>,
+        starting_position: &String,
+    ) -> Result<Self, Day16Error> {
+        let starting_valve = valves
+            .get(starting_position)
+            .ok_or(Day16Error::ValveNotFound)?;
+
+        let mut nodes: Vec<_> = valves
+            .values()
+            .filter(|valve| valve.flow_rate > 0)
+            .collect();
+        nodes.sort_by(|a, b| a.label.cmp(&b.label));
+
+        if nodes.len() > 64 {
+            return Err(Day16Error::TooManyValves);
+        }
+
+        // Useful valves come first so that their index doubles as their bit in
+        // the opened-valve mask.
+        let start = match nodes
+            .iter()
+            .position(|valve| valve.label == *starting_position)
+        {
+            Some(start) => start,
+            None => {
+                nodes.push(starting_valve);
+                nodes.len() - 1
+            }
+        };
+
+        let shortest_paths = all_shortest_paths(valves);
+
+        let distances = nodes
+            .iter()
+            .map(|from| {
+                let shortest_paths_from = shortest_paths.get(&from.label);
+
+                nodes
+                    .iter()
+                    .map(|to| {
+                        shortest_paths_from
+                            .and_then(|paths| paths.get(&to.label))
+                            .map(|path| path.len() as u64)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            labels: nodes.iter().map(|valve| valve.label.clone()).collect(),
+            flow_rates: nodes.iter().map(|valve| valve.flow_rate).collect(),
+            distances,
+            start,
+        })
+    }
+
+    fn useful_valves(&self) -> usize {
+        if self.flow_rates[self.start] == 0 {
+            self.labels.len() - 1
+        } else {
+            self.labels.len()
+        }
+    }
+}
+
+fn best_pressures(graph: &ContractedGraph, time_limit: u64) -> HashMap<u64, u64> {
+    let mut best = HashMap::new();
 
     fn aux(
-        time_limit: usize,
-        valves: &HashMap<String, Valve>,
-        shortest_paths: &HashMap<String, HashMap<String, Vec<String>>>,
-        current_position: &String,
-        valves_to_open: HashSet<&Valve>,
-        strategy: Vec<Action>,
-        strategies: &mut Vec<Vec<Action>>,
-    ) -> Result<(), Day16Error> {
-        let mut stuck = true;
-
-        if let Some(shortest_paths_from_current) = shortest_paths.get(current_position) {
-            for next_valve in &valves_to_open {
-                // for (next_position, next_valve) in valves {
-                //     if !opened_valves.contains(next_position) && next_valve.flow_rate > 0 {
-                let next_position = &next_valve.label;
-                if let Some(path) = shortest_paths_from_current.get(next_position) {
-                    if strategy.len() + path.len() + 1 <= time_limit {
-                        let mut valves_to_open = valves_to_open.clone();
-                        valves_to_open.remove(next_valve);
-
-                        let mut strategy = strategy.clone();
-                        strategy.extend(path.iter().map(|step| Action::MoveTo(step.clone())));
-                        strategy.push(Action::OpenValve);
-
-                        aux(
-                            time_limit,
-                            valves,
-                            shortest_paths,
-                            next_position,
-                            valves_to_open,
-                            strategy,
-                            strategies,
-                        )?;
-
-                        stuck = false;
-                    }
+        graph: &ContractedGraph,
+        current: usize,
+        time_left: u64,
+        opened: u64,
+        pressure_released: u64,
+        best: &mut HashMap<u64, u64>,
+    ) {
+        let entry = best.entry(opened).or_insert(0);
+        *entry = (*entry).max(pressure_released);
+
+        for next in 0..graph.useful_valves() {
+            if opened & (1 << next) != 0 {
+                continue;
+            }
+
+            if let Some(distance) = graph.distances[current][next] {
+                let cost = distance + 1;
+
+                if cost < time_left {
+                    let time_left = time_left - cost;
+
+                    aux(
+                        graph,
+                        next,
+                        time_left,
+                        opened | (1 << next),
+                        pressure_released + graph.flow_rates[next] * time_left,
+                        best,
+                    );
                 }
             }
         }
+    }
 
-        if stuck {
-            let mut strategy = strategy;
-            strategy.resize(time_limit, Action::NoOp);
+    #[cfg(not(feature = "parallel"))]
+    aux(graph, graph.start, time_limit, 0, 0, &mut best);
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        // Run each first valve as its own branch, then merge the per-branch
+        // results by keeping the best pressure for every opened set.
+        best.insert(0, 0);
+
+        let branches: Vec<_> = (0..graph.useful_valves())
+            .into_par_iter()
+            .filter_map(|first| {
+                let distance = graph.distances[graph.start][first]?;
+                let time_left = time_limit
+                    .checked_sub(distance + 1)
+                    .filter(|&time_left| time_left > 0)?;
+
+                let mut best = HashMap::new();
+                aux(
+                    graph,
+                    first,
+                    time_left,
+                    1 << first,
+                    graph.flow_rates[first] * time_left,
+                    &mut best,
+                );
+
+                Some(best)
+            })
+            .collect();
 
-            strategies.push(strategy);
+        for branch in branches {
+            for (opened, pressure_released) in branch {
+                let entry = best.entry(opened).or_insert(0);
+                *entry = (*entry).max(pressure_released);
+            }
         }
-
-        Ok(())
     }
 
-    aux(
-        time_limit,
-        valves,
-        &shortest_paths,
-        current_position,
-        valves_to_open,
-        vec![],
-        &mut strategies,
-    )?;
+    best
+}
 
-    Ok(strategies)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct CacheStats {
+    hits: u64,
+    misses: u64,
 }
 
-fn play_strategy(
-    valves: &HashMap<String, Valve>,
-    starting_position: &String,
-    strategy: Vec<Action>,
-) -> Result<u64, Day16Error> {
-    let mut state = VolcanoState::new(valves, starting_position)?;
+struct Search<'a> {
+    graph: &'a ContractedGraph,
+    cache: HashMap<(usize, u64, u64), u64>,
+    stats: CacheStats,
+}
 
-    for action in strategy {
-        state.tick(action)?;
+impl<'a> Search<'a> {
+    fn new(graph: &'a ContractedGraph) -> Self {
+        Self {
+            graph,
+            cache: HashMap::new(),
+            stats: CacheStats::default(),
+        }
     }
 
-    Ok(state.pressure_released)
+    fn best_remaining(&mut self, current: usize, time_left: u64, opened: u64) -> u64 {
+        let key = (current, time_left, opened);
+        if let Some(&best) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return best;
+        }
+        self.stats.misses += 1;
+
+        let mut best = 0;
+
+        for next in 0..self.graph.useful_valves() {
+            if opened & (1 << next) != 0 {
+                continue;
+            }
+
+            if let Some(distance) = self.graph.distances[current][next] {
+                let cost = distance + 1;
+
+                if cost < time_left {
+                    let time_left = time_left - cost;
+
+                    let pressure_released = self.graph.flow_rates[next] * time_left
+                        + self.best_remaining(next, time_left, opened | (1 << next));
+
+                    best = best.max(pressure_released);
+                }
+            }
+        }
+
+        self.cache.insert(key, best);
+
+        best
+    }
+
+    fn best_route(
+        &mut self,
+        mut current: usize,
+        mut time_left: u64,
+        mut opened: u64,
+    ) -> Vec<usize> {
+        let mut route = vec![];
+
+        loop {
+            let best = self.best_remaining(current, time_left, opened);
+            if best == 0 {
+                return route;
+            }
+
+            let next = (0..self.graph.useful_valves()).find_map(|next| {
+                if opened & (1 << next) != 0 {
+                    return None;
+                }
+
+                let distance = self.graph.distances[current][next]?;
+                let time_left = time_left
+                    .checked_sub(distance + 1)
+                    .filter(|&time_left| time_left > 0)?;
+
+                let pressure_released = self.graph.flow_rates[next] * time_left
+                    + self.best_remaining(next, time_left, opened | (1 << next));
+
+                (pressure_released == best).then_some((next, time_left))
+            });
+
+            let Some((next, next_time_left)) = next else {
+                return route;
+            };
+
+            route.push(next);
+            current = next;
+            time_left = next_time_left;
+            opened |= 1 << next;
+        }
+    }
 }
 
-fn part1(input: &Vec<String>) -> Result<u64, Day16Error> {
+fn part1_with_stats(input: &[String]) -> Result<(u64, CacheStats), Day16Error> {
     let valves = parse_valves(input)?;
     let starting_position = String::from("AA");
 
-    let valves_to_open = valves
-        .values()
-        .filter(|valve| valve.flow_rate > 0)
-        .collect();
-    let strategies = generate_strategies(30, &valves, valves_to_open, &starting_position)?;
+    let graph = ContractedGraph::new(&valves, &starting_position)?;
 
-    let scores = strategies
-        .into_iter()
-        .map(|strategy| play_strategy(&valves, &starting_position, strategy))
-        .collect::<Result<Vec<_>, _>>()?;
+    let mut search = Search::new(&graph);
+    let best = search.best_remaining(graph.start, 30, 0);
 
-    scores.into_iter().max().ok_or(Day16Error::EmptyInput)
+    Ok((best, search.stats))
 }
 
-#[derive(Debug, Clone)]
-struct VolcanoState2<'a> {
-    valves: &'a HashMap<String, Valve>,
-    my_position: &'a Valve,
-    elephant_position: &'a Valve,
-    open_valves: HashSet<&'a Valve>,
-    pressure_released: u64,
+fn part1(input: &[String]) -> Result<u64, Day16Error> {
+    let (best, _) = part1_with_stats(input)?;
+
+    Ok(best)
 }
 
-impl<'a> VolcanoState2<'a> {
-    fn new(
-        valves: &'a HashMap<String, Valve>,
-        starting_position: &String,
-    ) -> Result<Self, Day16Error> {
-        let current_position = valves
-            .get(starting_position)
-            .ok_or(Day16Error::EmptyInput)?;
+fn combine_agents(single: &[u64], others: &[u64]) -> Vec<(u64, usize)> {
+    let best_split = |opened: usize| {
+        let mut best = (0, 0);
+        let mut subset = opened;
 
-        Ok(Self {
-            valves,
-            my_position: current_position,
-            elephant_position: current_position,
-            open_valves: HashSet::new(),
-            pressure_released: 0,
-        })
-    }
+        loop {
+            let pressure_released = single[subset] + others[opened ^ subset];
+            if pressure_released > best.0 {
+                best = (pressure_released, subset);
+            }
 
-    fn tick(&mut self, my_action: &Action, elephant_action: &Action) -> Result<(), Day16Error> {
-        for v in &self.open_valves {
-            self.pressure_released += v.flow_rate;
+            if subset == 0 {
+                return best;
+            }
+            subset = (subset - 1) & opened;
         }
+    };
 
-        match my_action {
-            Action::OpenValve => {
-                self.open_valves.insert(self.my_position);
-            }
-            Action::MoveTo(label) => {
-                debug_assert!(self.my_position.tunnels.contains(label));
+    #[cfg(not(feature = "parallel"))]
+    return (0..single.len()).map(best_split).collect();
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        (0..single.len()).into_par_iter().map(best_split).collect()
+    }
+}
+
+fn assign_valves(
+    graph: &ContractedGraph,
+    best: &HashMap<u64, u64>,
+    agents: usize,
+) -> Result<(u64, Vec<u64>), Day16Error> {
+    if agents == 0 {
+        return Err(Day16Error::NoAgents);
+    }
 
-                let next_position = self.valves.get(label).ok_or(Day16Error::ValveNotFound)?;
-                self.my_position = next_position;
+    // All agents start together, so each one opens a disjoint subset of the
+    // useful valves. Spread every subset's best score to its supersets, so
+    // that each entry is the best a single agent can do within that subset.
+    let useful_valves = graph.useful_valves();
+    let subsets = 1usize
+        .checked_shl(useful_valves as u32)
+        .ok_or(Day16Error::TooManyValves)?;
+
+    let mut best_within = vec![0; subsets];
+    for (&opened, &pressure_released) in best {
+        best_within[opened as usize] = pressure_released;
+    }
+    for i in 0..useful_valves {
+        for opened in 0..best_within.len() {
+            if opened & (1 << i) != 0 {
+                best_within[opened] = best_within[opened].max(best_within[opened ^ (1 << i)]);
             }
-            Action::NoOp => {}
+        }
+    }
+
+    let mut combined = best_within.clone();
+    let mut choices: Vec<Vec<usize>> = vec![];
+    for _ in 1..agents {
+        let (pressures, subsets) = combine_agents(&best_within, &combined).into_iter().unzip();
+
+        combined = pressures;
+        choices.push(subsets);
+    }
+
+    let mut remaining = subsets - 1;
+    let mut assignment = vec![];
+    for subsets in choices.iter().rev() {
+        let subset = subsets[remaining];
+
+        assignment.push(subset as u64);
+        remaining ^= subset;
+    }
+    assignment.push(remaining as u64);
+
+    Ok((combined[subsets - 1], assignment))
+}
+
+#[cfg(feature = "interrupt")]
+struct AnytimeSearch<'a, F> {
+    graph: &'a ContractedGraph,
+    stop: &'a AtomicBool,
+    on_improvement: F,
+    best: HashMap<u64, u64>,
+    incumbent: u64,
+}
+
+#[cfg(feature = "interrupt")]
+impl<F: FnMut(u64)> AnytimeSearch<'_, F> {
+    // Same search as `best_pressures`, but reporting the best single-agent
+    // pressure as it improves and bailing out once `stop` is set.
+    fn search(&mut self, current: usize, time_left: u64, opened: u64, pressure_released: u64) {
+        if self.stop.load(atomic::Ordering::Relaxed) {
+            return;
+        }
+
+        let entry = self.best.entry(opened).or_insert(0);
+        *entry = (*entry).max(pressure_released);
+
+        if pressure_released > self.incumbent {
+            self.incumbent = pressure_released;
+            (self.on_improvement)(pressure_released);
         }
 
-        match elephant_action {
-            Action::OpenValve => {
-                self.open_valves.insert(self.elephant_position);
+        for next in 0..self.graph.useful_valves() {
+            if opened & (1 << next) != 0 {
+                continue;
             }
-            Action::MoveTo(label) => {
-                debug_assert!(self.elephant_position.tunnels.contains(label));
 
-                let next_position = self.valves.get(label).ok_or(Day16Error::ValveNotFound)?;
-                self.elephant_position = next_position;
+            if let Some(distance) = self.graph.distances[current][next] {
+                let cost = distance + 1;
+
+                if cost < time_left {
+                    let time_left = time_left - cost;
+
+                    self.search(
+                        next,
+                        time_left,
+                        opened | (1 << next),
+                        pressure_released + self.graph.flow_rates[next] * time_left,
+                    );
+                }
             }
-            Action::NoOp => {}
         }
-
-        Ok(())
     }
 }
 
-fn play_strategy_2(
-    valves: &HashMap<String, Valve>,
-    starting_position: &String,
-    my_actions: &Vec<Action>,
-    elephant_actions: &Vec<Action>,
+#[cfg(feature = "interrupt")]
+fn anytime_max_pressure(
+    input: &[String],
+    agents: usize,
+    time_limit: u64,
+    stop: &AtomicBool,
+    on_improvement: impl FnMut(u64),
 ) -> Result<u64, Day16Error> {
-    let mut state = VolcanoState2::new(valves, starting_position)?;
+    let valves = parse_valves(input)?;
+    let starting_position = String::from("AA");
 
-    for (my_action, elephant_action) in my_actions.iter().zip(elephant_actions) {
-        state.tick(my_action, elephant_action)?;
-    }
+    let graph = ContractedGraph::new(&valves, &starting_position)?;
 
-    Ok(state.pressure_released)
-}
+    let mut search = AnytimeSearch {
+        graph: &graph,
+        stop,
+        on_improvement,
+        best: HashMap::new(),
+        incumbent: 0,
+    };
+    search.search(graph.start, time_limit, 0, 0);
 
-// fn partition_valves_to_open<'a>(valves_to_open: &HashSet<&'a Valve>) -> Vec<HashSet<&'a Valve>> {
-//     (0..2usize.pow(valves_to_open.len() as u32))
-//         .map(|i| {
-//             valves_to_open
-//                 .iter()
-//                 .enumerate()
-//                 .filter(|&(t, _)| (i >> t) % 2 == 1)
-//                 .map(|(_, &valve)| valve)
-//                 .collect()
-//         })
-//         .collect()
-// }
+    // Every recorded subset is achievable, so combining a partial table still
+    // gives a valid (if not optimal) answer for several agents.
+    let (pressure_released, _) = assign_valves(&graph, &search.best, agents)?;
 
-fn part2(input: &Vec<String>) -> Result<u64, Day16Error> {
+    Ok(pressure_released)
+}
+
+fn max_pressure(input: &[String], agents: usize, time_limit: u64) -> Result<u64, Day16Error> {
     let valves = parse_valves(input)?;
     let starting_position = String::from("AA");
 
-    let mut max_score: Option<u64> = None;
+    let graph = ContractedGraph::new(&valves, &starting_position)?;
 
-    let valves_to_open: HashSet<_> = valves
-        .values()
-        .filter(|valve| valve.flow_rate > 0)
-        .collect();
+    let best = best_pressures(&graph, time_limit);
+    let (pressure_released, _) = assign_valves(&graph, &best, agents)?;
 
-    // TODO very slow (at least 30 minutes)
-    let strategies = generate_strategies(26, &valves, valves_to_open, &starting_position)?;
+    Ok(pressure_released)
+}
 
-    let total = strategies.len() * strategies.len();
-    let mut last_percent = 0;
-    let mut progress: usize = 0;
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    MoveTo(String),
+    Open(String),
+    Idle,
+}
 
-    for my_actions in &strategies {
-        for elephant_actions in &strategies {
-            let score = play_strategy_2(&valves, &starting_position, my_actions, elephant_actions)?;
+#[derive(Debug)]
+struct Plan {
+    pressure_released: u64,
+    flow_rates: HashMap<String, u64>,
+    actions: Vec<Vec<Action>>,
+}
+
+impl Plan {
+    fn open_valves(&self, minute: usize) -> Vec<&String> {
+        let mut open_valves: Vec<_> = self
+            .actions
+            .iter()
+            .flat_map(|actions| &actions[..minute])
+            .filter_map(|action| match action {
+                Action::Open(label) => Some(label),
+                _ => None,
+            })
+            .collect();
+        open_valves.sort();
+
+        open_valves
+    }
+
+    fn released_at(&self, minute: usize) -> u64 {
+        self.open_valves(minute)
+            .into_iter()
+            .map(|label| self.flow_rates[label])
+            .sum()
+    }
+}
 
-            max_score = Some(max_score.map_or(score, |other| other.max(score)));
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.actions.first().map_or(0, |actions| actions.len());
+
+        for minute in 0..minutes {
+            writeln!(f, "== Minute {} ==", minute + 1)?;
+
+            let open_valves = self.open_valves(minute);
+            let released = self.released_at(minute);
+            match open_valves.as_slice() {
+                [] => writeln!(f, "No valves are open.")?,
+                [valve] => writeln!(
+                    f,
+                    "Valve {} is open, releasing {} pressure.",
+                    valve, released
+                )?,
+                [valves @ .., last] => {
+                    let valves: Vec<_> = valves.iter().map(|valve| valve.as_str()).collect();
+                    let separator = if valves.len() > 1 { "," } else { "" };
+
+                    writeln!(
+                        f,
+                        "Valves {}{} and {} are open, releasing {} pressure.",
+                        valves.join(", "),
+                        separator,
+                        last,
+                        released
+                    )?
+                }
+            }
 
-            progress += 1;
-            let percent = (progress * 100) / total;
-            if percent > last_percent {
-                println!("{}% done", percent);
-                last_percent = percent;
+            for (agent, actions) in self.actions.iter().enumerate() {
+                match &actions[minute] {
+                    Action::MoveTo(label) => {
+                        writeln!(f, "Agent {} moves to valve {}.", agent + 1, label)?
+                    }
+                    Action::Open(label) => {
+                        writeln!(f, "Agent {} opens valve {}.", agent + 1, label)?
+                    }
+                    Action::Idle => {}
+                }
             }
+
+            writeln!(f)?;
         }
+
+        Ok(())
     }
+}
 
-    // Thought this would be better but it's actually a lot worse (couple hours)
+fn optimal_plan(input: &[String], agents: usize, time_limit: u64) -> Result<Plan, Day16Error> {
+    let valves = parse_valves(input)?;
+    let starting_position = String::from("AA");
 
-    // let partition = partition_valves_to_open(&valves_to_open);
+    let graph = ContractedGraph::new(&valves, &starting_position)?;
+    let shortest_paths = all_shortest_paths(&valves);
 
-    // for my_valves_to_open in partition {
-    //     if my_valves_to_open.len() == 0 || my_valves_to_open.len() * 2 > valves_to_open.len() {
-    //         continue;
-    //     }
+    let best = best_pressures(&graph, time_limit);
+    let (pressure_released, assignment) = assign_valves(&graph, &best, agents)?;
+    let all_useful = (1u64 << graph.useful_valves()) - 1;
 
-    //     let elephant_valves_to_open = &valves_to_open - &my_valves_to_open;
+    let mut search = Search::new(&graph);
+    let mut actions = vec![];
 
-    //     let my_strategies =
-    //         generate_strategies(26, &valves, my_valves_to_open, &starting_position)?;
-    //     let elephant_strategies =
-    //         generate_strategies(26, &valves, elephant_valves_to_open, &starting_position)?;
+    for subset in assignment {
+        let route = search.best_route(graph.start, time_limit, all_useful & !subset);
 
-    //     println!("{} {}", my_strategies.len(), elephant_strategies.len());
+        let mut agent_actions = vec![];
+        let mut position = &starting_position;
 
-    //     for my_actions in &my_strategies {
-    //         for elephant_actions in &elephant_strategies {
-    //             let score =
-    //                 play_strategy_2(&valves, &starting_position, my_actions, elephant_actions)?;
+        for next in route {
+            let label = &graph.labels[next];
+            let path = shortest_paths
+                .get(position)
+                .and_then(|paths| paths.get(label))
+                .ok_or(Day16Error::ValveNotFound)?;
 
-    //             max_score = Some(max_score.map_or(score, |other| other.max(score)));
-    //         }
-    //     }
-    // }
+            agent_actions.extend(path.iter().map(|step| Action::MoveTo(step.clone())));
+            agent_actions.push(Action::Open(label.clone()));
+
+            position = label;
+        }
+
+        agent_actions.resize(time_limit as usize, Action::Idle);
+        actions.push(agent_actions);
+    }
 
-    max_score.ok_or(Day16Error::EmptyInput)
+    let flow_rates = valves
+        .values()
+        .map(|valve| (valve.label.clone(), valve.flow_rate))
+        .collect();
+
+    Ok(Plan {
+        pressure_released,
+        flow_rates,
+        actions,
+    })
+}
+
+fn part2(input: &[String]) -> Result<u64, Day16Error> {
+    max_pressure(input, 2, 26)
 }
 
 #[cfg(test)]
@@ -493,6 +921,33 @@ Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II
 ";
 
+    #[test]
+    fn test_generate_valve_lines() {
+        let valves = parse_valves(&generate_valve_lines(30)).unwrap();
+
+        assert_eq!(valves.len(), 30);
+        assert_eq!(valves["AB"].tunnels, vec!["AC", "AD"]);
+    }
+
+    #[test]
+    fn test_parse_invalid_valve() {
+        for line in [
+            "Valve aa has flow rate=0; tunnels lead to valves BB",
+            "Valve AA has flow rate=0; tunnels lead to valves BB,CC",
+            "Valve AA has flow rate=0 tunnels lead to valves BB",
+        ] {
+            assert!(matches!(
+                line.parse::<Valve>(),
+                Err(Day16Error::ParseValveError)
+            ));
+        }
+
+        assert!(matches!(
+            "Valve AA has flow rate=x; tunnels lead to valves BB".parse::<Valve>(),
+            Err(Day16Error::ParseIntError(_))
+        ));
+    }
+
     #[test]
     fn test_parse_valve_single_tunnel() {
         let valve: Valve = "Valve HH has flow rate=22; tunnel leads to valve GG"
@@ -546,6 +1001,22 @@ Valve JJ has flow rate=21; tunnel leads to valve II
         );
     }
 
+    #[test]
+    fn test_contracted_graph() {
+        let input = to_lines(EXAMPLE);
+        let valves = parse_valves(&input).unwrap();
+
+        let graph = ContractedGraph::new(&valves, &String::from("AA")).unwrap();
+
+        assert_eq!(graph.labels, vec!["BB", "CC", "DD", "EE", "HH", "JJ", "AA"]);
+        assert_eq!(graph.flow_rates, vec![13, 2, 20, 3, 22, 21, 0]);
+        assert_eq!(graph.start, 6);
+        assert_eq!(graph.useful_valves(), 6);
+        assert_eq!(graph.distances[6][4], Some(5));
+        assert_eq!(graph.distances[5][4], Some(7));
+        assert_eq!(graph.distances[2][2], Some(0));
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
@@ -553,6 +1024,108 @@ Valve JJ has flow rate=21; tunnel leads to valve II
         assert_eq!(part1(&input).unwrap(), 1651);
     }
 
+    #[test]
+    fn test_search_cache() {
+        let input = to_lines(EXAMPLE);
+        let valves = parse_valves(&input).unwrap();
+        let graph = ContractedGraph::new(&valves, &String::from("AA")).unwrap();
+
+        let mut search = Search::new(&graph);
+        assert_eq!(search.best_remaining(graph.start, 30, 0), 1651);
+
+        let stats = search.stats;
+        assert!(stats.hits > 0);
+        assert_eq!(stats.misses as usize, search.cache.len());
+
+        assert_eq!(search.best_remaining(graph.start, 30, 0), 1651);
+        assert_eq!(search.stats.hits, stats.hits + 1);
+        assert_eq!(search.stats.misses, stats.misses);
+    }
+
+    #[test]
+    fn test_max_pressure() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(max_pressure(&input, 1, 30).unwrap(), 1651);
+        assert_eq!(max_pressure(&input, 2, 26).unwrap(), 1707);
+        assert_eq!(max_pressure(&input, 6, 3).unwrap(), 20 + 13);
+        assert!(matches!(
+            max_pressure(&input, 0, 30),
+            Err(Day16Error::NoAgents)
+        ));
+    }
+
+    #[test]
+    fn test_optimal_plan() {
+        let input = to_lines(EXAMPLE);
+
+        let plan = optimal_plan(&input, 1, 30).unwrap();
+        assert_eq!(plan.pressure_released, 1651);
+        assert_eq!(
+            plan.actions[0][..2],
+            [
+                Action::MoveTo(String::from("DD")),
+                Action::Open(String::from("DD")),
+            ]
+        );
+        assert_eq!(
+            plan.actions[0][3..5],
+            [
+                Action::MoveTo(String::from("BB")),
+                Action::Open(String::from("BB")),
+            ]
+        );
+        assert_eq!(
+            (0..30).map(|minute| plan.released_at(minute)).sum::<u64>(),
+            1651
+        );
+        assert!(plan.to_string().starts_with(
+            "\
+== Minute 1 ==
+No valves are open.
+Agent 1 moves to valve DD.
+
+== Minute 2 ==
+No valves are open.
+Agent 1 opens valve DD.
+
+== Minute 3 ==
+Valve DD is open, releasing 20 pressure.
+"
+        ));
+
+        let plan = optimal_plan(&input, 2, 26).unwrap();
+        assert_eq!(plan.pressure_released, 1707);
+        assert_eq!(plan.actions.len(), 2);
+        assert_eq!(
+            (0..26).map(|minute| plan.released_at(minute)).sum::<u64>(),
+            1707
+        );
+        assert!(plan
+            .to_string()
+            .contains("Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure."));
+    }
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_anytime_max_pressure() {
+        let input = to_lines(EXAMPLE);
+
+        let mut improvements = vec![];
+        let stop = AtomicBool::new(false);
+        let pressure_released =
+            anytime_max_pressure(&input, 1, 30, &stop, |best| improvements.push(best)).unwrap();
+
+        assert_eq!(pressure_released, 1651);
+        assert_eq!(improvements.last(), Some(&1651));
+        assert!(improvements.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let stop = AtomicBool::new(true);
+        let pressure_released = anytime_max_pressure(&input, 2, 26, &stop, |_| {}).unwrap();
+
+        assert_eq!(pressure_released, 0);
+    }
+
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
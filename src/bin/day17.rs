@@ -1,16 +1,24 @@
-use std::{
-    collections::{HashMap, HashSet},
-    io,
+use std::{io, num::ParseIntError};
+
+use aoc::{
+    animation::Animation,
+    cli::Args,
+    puzzles::part_label,
+    read_lines,
+    render::{InvalidPacking, Packing},
+    tower::{
+        self, default_rock_shapes, parse_rock_shapes, CaveState, CycleStats, RockShape, TowerError,
+        CHAMBER_WIDTH, CYCLE_SEARCH_LIMIT,
+    },
 };
 
-use aoc::read_lines;
-
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day17Error {
     IoError(io::Error),
-    InvalidJetError(char),
-    EmptyInput,
-    EmptyJetPattern,
+    TowerError(TowerError),
+    ParseIntError(ParseIntError),
+    InvalidPacking(InvalidPacking),
 }
 
 impl From<io::Error> for Day17Error {
@@ -19,329 +27,306 @@ impl From<io::Error> for Day17Error {
     }
 }
 
-const INPUT_PATH: &str = "inputs/day17.txt";
-
-fn main() -> Result<(), Day17Error> {
-    let input = read_lines(INPUT_PATH)?;
-
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
-
-    Ok(())
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Jet {
-    Left,
-    Right,
+impl From<TowerError> for Day17Error {
+    fn from(e: TowerError) -> Self {
+        Self::TowerError(e)
+    }
 }
 
-impl TryFrom<char> for Jet {
-    type Error = Day17Error;
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            '<' => Ok(Self::Left),
-            '>' => Ok(Self::Right),
-            _ => Err(Self::Error::InvalidJetError(value)),
-        }
+impl From<ParseIntError> for Day17Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
     }
 }
 
-fn parse_jet_pattern(input: &Vec<String>) -> Result<Vec<Jet>, Day17Error> {
-    let input = input.first().ok_or(Day17Error::EmptyInput)?;
-
-    input.chars().map(|c| c.try_into()).collect()
+impl From<InvalidPacking> for Day17Error {
+    fn from(e: InvalidPacking) -> Self {
+        Self::InvalidPacking(e)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: u64,
-    y: u64,
-}
+const INPUT_PATH: &str = "inputs/day17.txt";
 
-#[derive(Debug, Clone, Copy)]
-enum RockShape {
-    Horizontal,
-    Plus,
-    Wedge,
-    Vertical,
-    Square,
-}
+fn main() -> Result<(), Day17Error> {
+    let input = read_lines(INPUT_PATH)?;
 
-const ROCK_SHAPES: [RockShape; 5] = [
-    RockShape::Horizontal,
-    RockShape::Plus,
-    RockShape::Wedge,
-    RockShape::Vertical,
-    RockShape::Square,
-];
-
-impl RockShape {
-    fn points(&self, bottom_left: &Point) -> Vec<Point> {
-        use RockShape::*;
-        let Point { x, y } = bottom_left;
-
-        match self {
-            Horizontal => vec![
-                Point { x: *x, y: *y },
-                Point { x: *x + 1, y: *y },
-                Point { x: *x + 2, y: *y },
-                Point { x: *x + 3, y: *y },
-            ],
-            Plus => vec![
-                Point { x: *x + 1, y: *y },
-                Point { x: *x, y: *y + 1 },
-                Point {
-                    x: *x + 1,
-                    y: *y + 1,
-                },
-                Point {
-                    x: *x + 2,
-                    y: *y + 1,
-                },
-                Point {
-                    x: *x + 1,
-                    y: *y + 2,
-                },
-            ],
-            Wedge => vec![
-                Point { x: *x, y: *y },
-                Point { x: *x + 1, y: *y },
-                Point { x: *x + 2, y: *y },
-                Point {
-                    x: *x + 2,
-                    y: *y + 1,
-                },
-                Point {
-                    x: *x + 2,
-                    y: *y + 2,
-                },
-            ],
-            Vertical => vec![
-                Point { x: *x, y: *y },
-                Point { x: *x, y: *y + 1 },
-                Point { x: *x, y: *y + 2 },
-                Point { x: *x, y: *y + 3 },
-            ],
-            Square => vec![
-                Point { x: *x, y: *y },
-                Point { x: *x + 1, y: *y },
-                Point { x: *x, y: *y + 1 },
-                Point {
-                    x: *x + 1,
-                    y: *y + 1,
-                },
-            ],
+    let args = Args::from_env();
+
+    let shapes = match args.value("--shapes") {
+        Some(path) => parse_rock_shapes(&read_lines(path)?)?,
+        None => default_rock_shapes(),
+    };
+    let width = args.parse("--width")?.unwrap_or(CHAMBER_WIDTH);
+
+    println!(
+        "{}: {:?}",
+        part_label(17, 1),
+        part1(&input, &shapes, width)?
+    );
+    println!(
+        "{}: {:?}",
+        part_label(17, 2),
+        part2(&input, &shapes, width)?
+    );
+
+    if args.flag("--stats") {
+        let (_, stats) = part2_with_stats(&input, &shapes, width)?;
+        match stats.cycle {
+            Some(cycle) => println!(
+                "Part 2 cycle: starts after {} rocks, repeats every {} rocks",
+                cycle.start, cycle.length
+            ),
+            None => println!(
+                "Part 2 cycle: none found, simulated all {} rocks",
+                stats.rocks_simulated
+            ),
         }
     }
-}
 
-struct CaveState {
-    shape_index: usize,
-    jet_index: usize,
-    jet_pattern: Vec<Jet>,
-    fallen_rocks: HashSet<Point>,
-    heights: [u64; 7],
-}
+    let rows = args.parse("--tower")?;
+    let packing = args.parse("--packing")?;
 
-impl CaveState {
-    fn new(jet_pattern: Vec<Jet>) -> Result<Self, Day17Error> {
-        if jet_pattern.len() == 0 {
-            Err(Day17Error::EmptyJetPattern)
-        } else {
-            Ok(Self {
-                shape_index: 0,
-                jet_index: 0,
-                jet_pattern,
-                fallen_rocks: HashSet::new(),
-                heights: [0; 7],
-            })
-        }
+    if let Some(rows) = rows {
+        print!(
+            "{}",
+            render_frame(&simulate(&input, &shapes, width, 2022)?, rows, &[], packing)
+        );
     }
 
-    fn height(&self) -> u64 {
-        self.heights.into_iter().max().unwrap()
+    if let Some(path) = args.value("--render") {
+        simulate(&input, &shapes, width, 2022)?
+            .render_svg(rows.unwrap_or(40))
+            .save(path)?;
     }
 
-    fn next_shape(&mut self) -> RockShape {
-        let shape = ROCK_SHAPES[self.shape_index];
-        self.shape_index = (self.shape_index + 1) % ROCK_SHAPES.len();
+    if let Some(path) = args.value("--animate") {
+        let rocks = args.parse("--rocks")?.unwrap_or(5);
 
-        shape
+        animate_tower(&input, &shapes, width, rocks, rows.unwrap_or(20), packing)?.save(path)?;
     }
 
-    fn next_jet(&mut self) -> Jet {
-        let jet = self.jet_pattern[self.jet_index];
-        self.jet_index = (self.jet_index + 1) % self.jet_pattern.len();
+    Ok(())
+}
 
-        jet
-    }
+fn parse_jet_pattern(input: &[String]) -> Result<Vec<tower::Jet>, Day17Error> {
+    Ok(tower::parse_jet_pattern(input)?)
+}
 
-    fn drop_rock(&mut self) -> Result<(), Day17Error> {
-        use Jet::*;
-        // Floor is y = 0
-        // Left wall is x = 0
-        // Right wall is x = 8
+fn part1(input: &[String], shapes: &[RockShape], width: u64) -> Result<u64, Day17Error> {
+    let jet_pattern = parse_jet_pattern(input)?;
+    let mut heights = tower::TowerHeights::with_width(jet_pattern, shapes.to_vec(), width)?;
 
-        fn collides(points: Vec<Point>, fallen_rocks: &HashSet<Point>) -> bool {
-            points.iter().any(|point| fallen_rocks.contains(point))
-        }
+    Ok(heights.nth(2021).unwrap())
+}
 
-        let shape = self.next_shape();
-        let mut bottom_left = Point {
-            x: 3,
-            y: self.height() + 4,
-        };
-
-        loop {
-            match self.next_jet() {
-                Left => {
-                    if bottom_left.x > 1 {
-                        let new_bottom_left = Point {
-                            x: bottom_left.x - 1,
-                            y: bottom_left.y,
-                        };
-
-                        if !collides(shape.points(&new_bottom_left), &self.fallen_rocks) {
-                            bottom_left = new_bottom_left;
-                        }
-                    }
-                }
-                Right => {
-                    let new_bottom_left = Point {
-                        x: bottom_left.x + 1,
-                        y: bottom_left.y,
-                    };
-
-                    let points = shape.points(&new_bottom_left);
-
-                    if points.iter().all(|point| point.x <= 7)
-                        && !collides(points, &self.fallen_rocks)
-                    {
-                        bottom_left = new_bottom_left;
-                    }
-                }
-            };
-
-            if bottom_left.y > 1 {
-                let new_bottom_left = Point {
-                    x: bottom_left.x,
-                    y: bottom_left.y - 1,
-                };
-
-                if !collides(shape.points(&new_bottom_left), &self.fallen_rocks) {
-                    bottom_left = new_bottom_left;
-                    continue;
-                }
-            }
-
-            let points = shape.points(&bottom_left);
-            for point in &points {
-                if point.y > self.heights[point.x as usize - 1] {
-                    self.heights[point.x as usize - 1] = point.y;
-                }
-            }
-
-            self.fallen_rocks.extend(points);
-            break;
-        }
+fn simulate(
+    input: &[String],
+    shapes: &[RockShape],
+    width: u64,
+    rocks: usize,
+) -> Result<CaveState, Day17Error> {
+    let jet_pattern = parse_jet_pattern(input)?;
+    let mut cave_state = CaveState::with_width(jet_pattern, shapes.to_vec(), width)?;
 
-        Ok(())
+    for _ in 0..rocks {
+        cave_state.drop_rock();
     }
 
-    fn surface(&self) -> [u64; 7] {
-        let min = self.heights.into_iter().min().unwrap();
+    Ok(cave_state)
+}
 
-        self.heights.map(|height| height - min)
+fn render_frame(
+    cave_state: &CaveState,
+    rows: u64,
+    falling: &[tower::Point],
+    packing: Option<Packing>,
+) -> String {
+    match packing {
+        Some(packing) => cave_state.render_packed(rows, falling, packing),
+        None => cave_state.render_rows(rows, falling),
     }
 }
 
-fn part1(input: &Vec<String>) -> Result<u64, Day17Error> {
+fn animate_tower(
+    input: &[String],
+    shapes: &[RockShape],
+    width: u64,
+    rocks: usize,
+    rows: u64,
+    packing: Option<Packing>,
+) -> Result<Animation, Day17Error> {
     let jet_pattern = parse_jet_pattern(input)?;
-    let mut cave_state = CaveState::new(jet_pattern)?;
+    let mut cave_state = CaveState::with_width(jet_pattern, shapes.to_vec(), width)?;
 
-    for _ in 0..2022 {
-        cave_state.drop_rock()?;
+    let mut animation = Animation::new();
+
+    for _ in 0..rocks {
+        cave_state.drop_rock_with(|cave_state, shape, bottom_left| {
+            let falling = shape.points(bottom_left);
+            animation.push_frame(render_frame(cave_state, rows, &falling, packing));
+        });
     }
+    animation.push_frame(render_frame(&cave_state, rows, &[], packing));
 
-    Ok(cave_state.height())
+    Ok(animation)
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct CycleState {
-    surface: [u64; 7],
-    shape_index: usize,
-    jet_index: usize,
+fn part2_with_stats(
+    input: &[String],
+    shapes: &[RockShape],
+    width: u64,
+) -> Result<(u64, CycleStats), Day17Error> {
+    let jet_pattern = parse_jet_pattern(input)?;
+
+    Ok(tower::height_after_with_stats(
+        jet_pattern,
+        shapes.to_vec(),
+        width,
+        1000000000000,
+        CYCLE_SEARCH_LIMIT,
+    )?)
 }
 
-fn part2(input: &Vec<String>) -> Result<u64, Day17Error> {
-    let jet_pattern = parse_jet_pattern(input)?;
-    let mut cave_state = CaveState::new(jet_pattern)?;
+fn part2(input: &[String], shapes: &[RockShape], width: u64) -> Result<u64, Day17Error> {
+    let (height, _) = part2_with_stats(input, shapes, width)?;
 
-    // Map each state to the index where it was encountered
-    let mut cycle_memory: HashMap<CycleState, usize> = HashMap::new();
-    let mut height_memory: Vec<u64> = vec![];
+    Ok(height)
+}
 
-    let n: usize = 1000000000000;
+#[cfg(test)]
+mod tests {
+    use aoc::{to_lines, tower::ROCK_COLOUR};
 
-    for i in 0..n {
-        let cycle_state = CycleState {
-            surface: cave_state.surface(),
-            shape_index: cave_state.shape_index,
-            jet_index: cave_state.jet_index,
-        };
-        if let Some(previous_i) = cycle_memory.get(&cycle_state) {
-            let cycle_length = i - previous_i;
+    use super::*;
 
-            let current_height = cave_state.height();
-            let previous_height = height_memory[*previous_i];
-            let height_gain_per_cycle = current_height - previous_height;
+    const EXAMPLE: &str = "\
+>>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
+";
+    #[test]
+    fn test_render_rows() {
+        let input = to_lines(EXAMPLE);
 
-            let number_of_cycles = (n - i) / cycle_length;
-            let remainder = (n - i) % cycle_length;
+        assert_eq!(
+            simulate(&input, &default_rock_shapes(), CHAMBER_WIDTH, 2)
+                .unwrap()
+                .render_rows(10, &[]),
+            "\
+|...#...|
+|..###..|
+|...#...|
+|..####.|
++-------+
+"
+        );
+        assert_eq!(
+            simulate(&input, &default_rock_shapes(), CHAMBER_WIDTH, 2)
+                .unwrap()
+                .render_rows(2, &[]),
+            "\
+|...#...|
+|..###..|
+"
+        );
+    }
 
-            let intermediate_index = previous_i + remainder;
-            let intermediate_height = height_memory[intermediate_index];
-            let intermediate_height_gain = intermediate_height - previous_height;
+    #[test]
+    fn test_animate_tower() {
+        let input = to_lines(EXAMPLE);
 
-            let total_height = current_height
-                + height_gain_per_cycle * (number_of_cycles as u64)
-                + intermediate_height_gain;
+        let animation =
+            animate_tower(&input, &default_rock_shapes(), CHAMBER_WIDTH, 1, 10, None).unwrap();
+        let frames = animation.frames();
+
+        assert_eq!(
+            frames[0],
+            "\
+|..@@@@.|
+|.......|
+|.......|
+|.......|
++-------+
+"
+        );
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[4], "|..####.|\n+-------+\n");
+    }
 
-            return Ok(total_height);
-        }
+    #[test]
+    fn test_render_packed() {
+        let input = to_lines(EXAMPLE);
+        let cave_state = simulate(&input, &default_rock_shapes(), CHAMBER_WIDTH, 2).unwrap();
+
+        assert_eq!(
+            render_frame(&cave_state, 10, &[], Some(Packing::HalfBlock)),
+            "\
+█  ▄█▄  █
+█  ▄█▄▄ █
+▀▀▀▀▀▀▀▀▀
+"
+        );
+        assert_eq!(
+            render_frame(&cave_state, 10, &[], Some(Packing::Braille)),
+            "\u{2847}\u{2890}\u{28d7}\u{2840}\u{2847}\n\u{2809}\u{2809}\u{2809}\u{2809}\u{2801}\n"
+        );
+    }
 
-        height_memory.push(cave_state.height());
-        cycle_memory.insert(cycle_state, i);
+    #[test]
+    fn test_render_svg() {
+        let input = to_lines(EXAMPLE);
 
-        cave_state.drop_rock()?;
+        let svg = simulate(&input, &default_rock_shapes(), CHAMBER_WIDTH, 2)
+            .unwrap()
+            .render_svg(10)
+            .to_text();
+
+        assert!(svg.contains(r#"viewBox="0 0 9 5""#));
+        assert_eq!(svg.matches(ROCK_COLOUR).count(), 9);
     }
 
-    Ok(cave_state.height())
-}
+    #[test]
+    fn test_custom_rock_shapes() {
+        let input = to_lines(EXAMPLE);
+        let shapes = parse_rock_shapes(&to_lines("..#\n\n#####\n")).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert_eq!(part1(&input, &shapes[1..], CHAMBER_WIDTH).unwrap(), 2022);
+    }
 
-    use aoc::to_lines;
+    #[test]
+    fn test_chamber_width() {
+        let input = to_lines(EXAMPLE);
 
-    const EXAMPLE: &str = "\
->>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
-";
+        assert_eq!(
+            simulate(&input, &default_rock_shapes(), 9, 1)
+                .unwrap()
+                .render_rows(10, &[]),
+            "\
+|....####.|
++---------+
+"
+        );
+
+        let height = part1(&input, &default_rock_shapes(), 9).unwrap();
+        assert!(height < 3068);
+        assert!(part2(&input, &default_rock_shapes(), 9).is_ok());
+    }
 
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part1(&input).unwrap(), 3068);
+        assert_eq!(
+            part1(&input, &default_rock_shapes(), CHAMBER_WIDTH).unwrap(),
+            3068
+        );
     }
 
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part2(&input).unwrap(), 1514285714288);
+        assert_eq!(
+            part2(&input, &default_rock_shapes(), CHAMBER_WIDTH).unwrap(),
+            1514285714288
+        );
     }
 }
@@ -1,13 +1,21 @@
-use std::{collections::HashSet, io, num::ParseIntError, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs, io,
+    num::ParseIntError,
+    str::FromStr,
+    time::Instant,
+};
 
-use aoc::read_lines;
+use aoc::{cli::Args, puzzles::part_label, read_lines};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day18Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
     InvalidCube,
     EmptyInput,
+    InvalidDimensions(usize),
 }
 
 impl From<io::Error> for Day18Error {
@@ -25,41 +33,75 @@ impl From<ParseIntError> for Day18Error {
 const INPUT_PATH: &str = "inputs/day18.txt";
 
 fn main() -> Result<(), Day18Error> {
+    let args = Args::from_env();
+
+    // Benchmarks run on a generated droplet, so they don't need an input.
+    if let Some(radius) = args.parse("--benchmark")? {
+        return benchmark(radius);
+    }
+
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    let dimensions = args.parse("--dimensions")?.unwrap_or(3);
+    let (part1, part2) = match dimensions {
+        2 => surface_areas::<2>(&input)?,
+        3 => surface_areas::<3>(&input)?,
+        4 => surface_areas::<4>(&input)?,
+        _ => return Err(Day18Error::InvalidDimensions(dimensions)),
+    };
+
+    println!("{}: {:?}", part_label(18, 1), part1);
+    println!("{}: {:?}", part_label(18, 2), part2);
+
+    if args.flag("--stats") {
+        print!("{}", stats_report(&input, dimensions)?);
+    }
+
+    if args.flag("--slices") {
+        print!("{}", render_slices(&input)?);
+    }
+
+    if args.flag("--pockets") {
+        let pockets = interior_pockets(&input)?;
+
+        println!("{} interior pockets", pockets.len());
+        for (i, pocket) in pockets.iter().enumerate() {
+            println!(
+                "Pocket {}: volume {}, surface area {}",
+                i + 1,
+                pocket.volume,
+                pocket.surface_area
+            );
+        }
+    }
+
+    if let Some(path) = args.value("--obj") {
+        fs::write(path, to_obj(&exterior_surface(&input)?))?;
+    }
 
     Ok(())
 }
 
+// 3D by default, but the surface area questions make sense in any dimension.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Cube {
-    x: u64,
-    y: u64,
-    z: u64,
-}
+struct Cube<const D: usize = 3>([u64; D]);
 
-impl Cube {
+impl<const D: usize> Cube<D> {
     fn neighbours(&self) -> Vec<Self> {
-        let &Cube { x, y, z } = self;
+        let mut neighbours = Vec::with_capacity(2 * D);
 
-        let mut neighbours = vec![
-            Self { x: x + 1, y, z },
-            Self { x, y: y + 1, z },
-            Self { x, y, z: z + 1 },
-        ];
-
-        if x > 0 {
-            neighbours.push(Self { x: x - 1, y, z });
-        }
-
-        if y > 0 {
-            neighbours.push(Self { x, y: y - 1, z });
+        for axis in 0..D {
+            let mut neighbour = *self;
+            neighbour.0[axis] += 1;
+            neighbours.push(neighbour);
         }
 
-        if z > 0 {
-            neighbours.push(Self { x, y, z: z - 1 });
+        for axis in 0..D {
+            if let Some(coordinate) = self.0[axis].checked_sub(1) {
+                let mut neighbour = *self;
+                neighbour.0[axis] = coordinate;
+                neighbours.push(neighbour);
+            }
         }
 
         neighbours
@@ -68,114 +110,178 @@ impl Cube {
     fn number_of_exposed_sides(&self, others: &HashSet<Self>) -> usize {
         let neighbours = self.neighbours();
 
-        (6 - neighbours.len())
+        (2 * D - neighbours.len())
             + neighbours
                 .into_iter()
                 .filter(|neighbour| !others.contains(neighbour))
                 .count()
     }
 
-    fn number_of_exposed_sides_2(&self, exterior: &HashSet<Self>) -> usize {
-        let neighbours = self.neighbours();
+    fn faces(&self) -> impl Iterator<Item = Face<D>> + '_ {
+        (0..D).flat_map(move |axis| {
+            [true, false].map(|positive| Face {
+                cube: *self,
+                axis,
+                positive,
+            })
+        })
+    }
+}
 
-        (6 - neighbours.len())
-            + neighbours
-                .into_iter()
-                .filter(|neighbour| exterior.contains(neighbour))
-                .count()
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Face<const D: usize = 3> {
+    cube: Cube<D>,
+    axis: usize,
+    positive: bool,
+}
+
+impl<const D: usize> Face<D> {
+    fn neighbour(&self) -> Option<Cube<D>> {
+        let mut neighbour = self.cube;
+
+        if self.positive {
+            neighbour.0[self.axis] += 1;
+        } else {
+            neighbour.0[self.axis] = neighbour.0[self.axis].checked_sub(1)?;
+        }
+
+        Some(neighbour)
     }
 }
 
-impl FromStr for Cube {
+impl Face {
+    // Counter-clockwise when seen from outside the cube, so normals point out.
+    fn corners(&self) -> [[u64; 3]; 4] {
+        let (a, b, c) = (self.axis, (self.axis + 1) % 3, (self.axis + 2) % 3);
+
+        let mut corners = [self.cube.0; 4];
+        for (corner, (db, dc)) in corners.iter_mut().zip([(0, 0), (1, 0), (1, 1), (0, 1)]) {
+            corner[a] += self.positive as u64;
+            corner[b] += db;
+            corner[c] += dc;
+        }
+
+        if !self.positive {
+            corners.reverse();
+        }
+
+        corners
+    }
+}
+
+impl<const D: usize> FromStr for Cube<D> {
     type Err = Day18Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v: Vec<_> = s.split(",").collect();
-        match v.as_slice() {
-            [x, y, z] => Ok(Self {
-                x: x.parse()?,
-                y: y.parse()?,
-                z: z.parse()?,
-            }),
-            _ => Err(Self::Err::InvalidCube),
+        let coordinates = s
+            .split(',')
+            .map(|coordinate| coordinate.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match coordinates.try_into() {
+            Ok(coordinates) => Ok(Self(coordinates)),
+            Err(_) => Err(Self::Err::InvalidCube),
         }
     }
 }
 
-fn parse_cubes(input: &Vec<String>) -> Result<HashSet<Cube>, Day18Error> {
+fn parse_cubes<const D: usize>(input: &[String]) -> Result<HashSet<Cube<D>>, Day18Error> {
     input.iter().map(|line| line.parse()).collect()
 }
 
-fn part1(input: &Vec<String>) -> Result<usize, Day18Error> {
-    let cubes = parse_cubes(input)?;
+fn surface_area<const D: usize>(input: &[String]) -> Result<usize, Day18Error> {
+    let cubes = parse_cubes::<D>(input)?;
 
-    Ok(cubes
+    #[cfg(not(feature = "parallel"))]
+    let exposed_sides = cubes
         .iter()
         .map(|cube| cube.number_of_exposed_sides(&cubes))
-        .sum())
+        .sum();
+
+    #[cfg(feature = "parallel")]
+    let exposed_sides = {
+        use rayon::prelude::*;
+
+        cubes
+            .par_iter()
+            .map(|cube| cube.number_of_exposed_sides(&cubes))
+            .sum()
+    };
+
+    Ok(exposed_sides)
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct Boundaries {
-    min_x: u64,
-    min_y: u64,
-    min_z: u64,
-    max_x: u64,
-    max_y: u64,
-    max_z: u64,
+fn part1(input: &[String]) -> Result<usize, Day18Error> {
+    surface_area::<3>(input)
 }
 
-impl From<&Cube> for Boundaries {
-    fn from(cube: &Cube) -> Self {
-        let &Cube { x, y, z } = cube;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Boundaries<const D: usize = 3> {
+    min: [u64; D],
+    max: [u64; D],
+}
 
+impl<const D: usize> From<&Cube<D>> for Boundaries<D> {
+    fn from(cube: &Cube<D>) -> Self {
         Self {
-            min_x: x,
-            min_y: y,
-            min_z: z,
-            max_x: x,
-            max_y: y,
-            max_z: z,
+            min: cube.0,
+            max: cube.0,
         }
     }
 }
 
-impl Boundaries {
-    fn contains(&self, cube: &Cube) -> bool {
-        cube.x >= self.min_x
-            && cube.x <= self.max_x
-            && cube.y >= self.min_y
-            && cube.y <= self.max_y
-            && cube.z >= self.min_z
-            && cube.z <= self.max_z
+impl<const D: usize> Boundaries<D> {
+    fn contains(&self, cube: &Cube<D>) -> bool {
+        (0..D).all(|axis| cube.0[axis] >= self.min[axis] && cube.0[axis] <= self.max[axis])
     }
 
-    fn update(mut self, cube: &Cube) -> Self {
-        let &Cube { x, y, z } = cube;
-
-        self.min_x = self.min_x.min(x);
-        self.min_y = self.min_y.min(y);
-        self.min_z = self.min_z.min(z);
-        self.max_x = self.max_x.max(x);
-        self.max_y = self.max_y.max(y);
-        self.max_z = self.max_z.max(z);
+    fn update(mut self, cube: &Cube<D>) -> Self {
+        for axis in 0..D {
+            self.min[axis] = self.min[axis].min(cube.0[axis]);
+            self.max[axis] = self.max[axis].max(cube.0[axis]);
+        }
 
         self
     }
 
+    fn dimensions(&self) -> [u64; D] {
+        std::array::from_fn(|axis| self.max[axis] - self.min[axis] + 1)
+    }
+
+    fn volume(&self) -> Option<u64> {
+        self.dimensions()
+            .into_iter()
+            .try_fold(1u64, |volume, length| volume.checked_mul(length))
+    }
+
+    fn cubes(&self) -> impl Iterator<Item = Cube<D>> + '_ {
+        let dimensions = self.dimensions();
+
+        // The first axis varies fastest.
+        (0..self.volume().expect("boundaries should be enumerable")).map(move |mut index| {
+            let mut cube = Cube(self.min);
+            for (coordinate, length) in cube.0.iter_mut().zip(dimensions) {
+                *coordinate += index % length;
+                index /= length;
+            }
+
+            cube
+        })
+    }
+
     fn expand_1(mut self) -> Self {
-        self.min_x = self.min_x.saturating_sub(1);
-        self.min_y = self.min_y.saturating_sub(1);
-        self.min_z = self.min_z.saturating_sub(1);
-        self.max_x = self.max_x.saturating_add(1);
-        self.max_y = self.max_y.saturating_add(1);
-        self.max_z = self.max_z.saturating_add(1);
+        for axis in 0..D {
+            self.min[axis] = self.min[axis].saturating_sub(1);
+            self.max[axis] = self.max[axis].saturating_add(1);
+        }
 
         self
     }
 }
 
-fn compute_boundaries(cubes: &HashSet<Cube>) -> Result<Boundaries, Day18Error> {
+fn compute_boundaries<const D: usize>(
+    cubes: &HashSet<Cube<D>>,
+) -> Result<Boundaries<D>, Day18Error> {
     let mut cubes = cubes.iter();
 
     let first = cubes.next().ok_or(Day18Error::EmptyInput)?;
@@ -183,15 +289,14 @@ fn compute_boundaries(cubes: &HashSet<Cube>) -> Result<Boundaries, Day18Error> {
     Ok(cubes.fold(first.into(), Boundaries::update))
 }
 
-fn find_exterior(cubes: &HashSet<Cube>, boundaries: Boundaries) -> HashSet<Cube> {
+fn find_exterior_sparse<const D: usize>(
+    cubes: &HashSet<Cube<D>>,
+    boundaries: Boundaries<D>,
+) -> HashSet<Cube<D>> {
     // Add a 1 unit gap on all sides to make sure there is a single contiguous exterior
     let boundaries = boundaries.expand_1();
 
-    let start = Cube {
-        x: boundaries.min_x,
-        y: boundaries.min_y,
-        z: boundaries.min_z,
-    };
+    let start = Cube(boundaries.min);
 
     assert!(!cubes.contains(&start));
 
@@ -216,16 +321,404 @@ fn find_exterior(cubes: &HashSet<Cube>, boundaries: Boundaries) -> HashSet<Cube>
     exterior
 }
 
-fn part2(input: &Vec<String>) -> Result<usize, Day18Error> {
-    let cubes = parse_cubes(input)?;
+struct DenseGrid<const D: usize = 3> {
+    boundaries: Boundaries<D>,
+    cells: Vec<bool>,
+}
+
+impl<const D: usize> DenseGrid<D> {
+    fn new(boundaries: Boundaries<D>) -> Self {
+        let volume = boundaries
+            .volume()
+            .expect("dense grid should fit in memory");
+
+        Self {
+            boundaries,
+            cells: vec![false; volume as usize],
+        }
+    }
+
+    fn index(&self, cube: &Cube<D>) -> Option<usize> {
+        if !self.boundaries.contains(cube) {
+            return None;
+        }
+
+        let dimensions = self.boundaries.dimensions();
+
+        let index = (0..D).rev().fold(0, |index, axis| {
+            index * dimensions[axis] + (cube.0[axis] - self.boundaries.min[axis])
+        });
+
+        Some(index as usize)
+    }
+
+    fn contains(&self, cube: &Cube<D>) -> bool {
+        self.index(cube).is_some_and(|index| self.cells[index])
+    }
+
+    // Returns whether the cube was newly inserted, like `HashSet::insert`.
+    fn insert(&mut self, cube: &Cube<D>) -> bool {
+        match self.index(cube) {
+            Some(index) => !std::mem::replace(&mut self.cells[index], true),
+            None => false,
+        }
+    }
+}
+
+fn find_exterior_dense<const D: usize>(
+    cubes: &HashSet<Cube<D>>,
+    boundaries: Boundaries<D>,
+) -> DenseGrid<D> {
+    let boundaries = boundaries.expand_1();
+
+    let mut lava = DenseGrid::new(boundaries);
+    for cube in cubes {
+        lava.insert(cube);
+    }
+
+    let start = Cube(boundaries.min);
+
+    assert!(!lava.contains(&start));
+
+    let mut exterior = DenseGrid::new(boundaries);
+    exterior.insert(&start);
+    let mut to_visit = vec![start];
+
+    while let Some(cube) = to_visit.pop() {
+        for neighbour in cube.neighbours() {
+            if !lava.contains(&neighbour) && exterior.insert(&neighbour) {
+                to_visit.push(neighbour);
+            }
+        }
+    }
+
+    exterior
+}
+
+// Above this many cells in the bounding box, fall back to hashing the exterior.
+const DENSE_VOLUME_LIMIT: u64 = 1 << 27;
+
+enum Exterior<const D: usize = 3> {
+    Sparse(HashSet<Cube<D>>),
+    Dense(DenseGrid<D>),
+}
+
+impl<const D: usize> Exterior<D> {
+    fn contains(&self, cube: &Cube<D>) -> bool {
+        match self {
+            Self::Sparse(exterior) => exterior.contains(cube),
+            Self::Dense(exterior) => exterior.contains(cube),
+        }
+    }
+}
+
+fn find_exterior<const D: usize>(
+    cubes: &HashSet<Cube<D>>,
+    boundaries: Boundaries<D>,
+) -> Exterior<D> {
+    match boundaries.expand_1().volume() {
+        Some(volume) if volume <= DENSE_VOLUME_LIMIT => {
+            Exterior::Dense(find_exterior_dense(cubes, boundaries))
+        }
+        _ => Exterior::Sparse(find_exterior_sparse(cubes, boundaries)),
+    }
+}
+
+fn exterior_surface<const D: usize>(input: &[String]) -> Result<Vec<Face<D>>, Day18Error> {
+    let cubes = parse_cubes::<D>(input)?;
     let boundaries = compute_boundaries(&cubes)?;
 
     let exterior = find_exterior(&cubes, boundaries);
 
-    Ok(cubes
+    let mut cubes: Vec<_> = cubes.into_iter().collect();
+    cubes.sort_by(|a, b| a.0.iter().rev().cmp(b.0.iter().rev()));
+
+    let is_exterior = |face: &Face<D>| match face.neighbour() {
+        Some(neighbour) => exterior.contains(&neighbour),
+        None => true,
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let faces = cubes
         .iter()
-        .map(|cube| cube.number_of_exposed_sides_2(&exterior))
-        .sum())
+        .flat_map(Cube::faces)
+        .filter(is_exterior)
+        .collect();
+
+    #[cfg(feature = "parallel")]
+    let faces = {
+        use rayon::prelude::*;
+
+        cubes
+            .par_iter()
+            .flat_map_iter(Cube::faces)
+            .filter(is_exterior)
+            .collect()
+    };
+
+    Ok(faces)
+}
+
+fn generate_droplet(radius: u64) -> Vec<String> {
+    // A solid ball, shifted so that every coordinate is positive.
+    let centre = radius + 1;
+    let range = 1..=2 * radius + 1;
+
+    let mut droplet = vec![];
+    for z in range.clone() {
+        for y in range.clone() {
+            for x in range.clone() {
+                let distance = [x, y, z]
+                    .map(|c| c.abs_diff(centre).pow(2))
+                    .iter()
+                    .sum::<u64>();
+
+                if distance <= radius * radius {
+                    droplet.push(format!("{x},{y},{z}"));
+                }
+            }
+        }
+    }
+
+    droplet
+}
+
+fn benchmark(radius: u64) -> Result<(), Day18Error> {
+    let droplet = generate_droplet(radius);
+    println!("Generated droplet of {} cubes", droplet.len());
+
+    let start = Instant::now();
+    let exposed = part1(&droplet)?;
+    println!("Part 1: {exposed} in {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let exposed = part2(&droplet)?;
+    println!("Part 2: {exposed} in {:?}", start.elapsed());
+
+    Ok(())
+}
+
+fn part2(input: &[String]) -> Result<usize, Day18Error> {
+    Ok(exterior_surface::<3>(input)?.len())
+}
+
+fn surface_areas<const D: usize>(input: &[String]) -> Result<(usize, usize), Day18Error> {
+    Ok((
+        surface_area::<D>(input)?,
+        exterior_surface::<D>(input)?.len(),
+    ))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct DropletStats<const D: usize = 3> {
+    cubes: usize,
+    boundaries: Boundaries<D>,
+    surface_area: usize,
+    exterior_surface_area: usize,
+}
+
+impl<const D: usize> DropletStats<D> {
+    // Share of the bounding box filled with lava, if its volume fits a `u64`.
+    fn density(&self) -> Option<f64> {
+        let volume = self.boundaries.volume()?;
+
+        Some(self.cubes as f64 / volume as f64)
+    }
+
+    // Exposed faces per cube, which is 2 * D for a lone cube and shrinks as
+    // the droplet gets more compact.
+    fn surface_to_volume(&self, surface_area: usize) -> f64 {
+        surface_area as f64 / self.cubes as f64
+    }
+}
+
+impl<const D: usize> fmt::Display for DropletStats<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const AXES: [&str; 4] = ["x", "y", "z", "w"];
+
+        let join = |values: [u64; D]| {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        writeln!(f, "Cubes: {}", self.cubes)?;
+        writeln!(
+            f,
+            "Bounding box: {} to {}",
+            join(self.boundaries.min),
+            join(self.boundaries.max)
+        )?;
+
+        let extents: Vec<_> = self
+            .boundaries
+            .dimensions()
+            .iter()
+            .enumerate()
+            .map(|(axis, length)| format!("{}={}", AXES.get(axis).unwrap_or(&"?"), length))
+            .collect();
+        writeln!(f, "Extents: {}", extents.join(", "))?;
+
+        match (self.density(), self.boundaries.volume()) {
+            (Some(density), Some(volume)) => writeln!(
+                f,
+                "Fill density: {:.2}% ({} of {} cells)",
+                density * 100.0,
+                self.cubes,
+                volume
+            )?,
+            _ => writeln!(f, "Fill density: bounding box too large")?,
+        }
+
+        writeln!(
+            f,
+            "Surface to volume: {:.3} ({} faces), {:.3} exterior ({} faces)",
+            self.surface_to_volume(self.surface_area),
+            self.surface_area,
+            self.surface_to_volume(self.exterior_surface_area),
+            self.exterior_surface_area
+        )
+    }
+}
+
+fn droplet_stats<const D: usize>(input: &[String]) -> Result<DropletStats<D>, Day18Error> {
+    let cubes = parse_cubes::<D>(input)?;
+    let boundaries = compute_boundaries(&cubes)?;
+
+    Ok(DropletStats {
+        cubes: cubes.len(),
+        boundaries,
+        surface_area: surface_area::<D>(input)?,
+        exterior_surface_area: exterior_surface::<D>(input)?.len(),
+    })
+}
+
+fn stats_report(input: &[String], dimensions: usize) -> Result<String, Day18Error> {
+    Ok(match dimensions {
+        2 => droplet_stats::<2>(input)?.to_string(),
+        3 => droplet_stats::<3>(input)?.to_string(),
+        4 => droplet_stats::<4>(input)?.to_string(),
+        _ => return Err(Day18Error::InvalidDimensions(dimensions)),
+    })
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Pocket {
+    volume: usize,
+    surface_area: usize,
+}
+
+fn find_pockets<const D: usize>(
+    cubes: &HashSet<Cube<D>>,
+    boundaries: Boundaries<D>,
+    exterior: &Exterior<D>,
+) -> Vec<Pocket> {
+    let mut pockets = vec![];
+    let mut visited = HashSet::new();
+
+    for start in boundaries.cubes() {
+        if cubes.contains(&start) || exterior.contains(&start) || !visited.insert(start) {
+            continue;
+        }
+
+        // Anything that is neither lava nor exterior is enclosed, so the
+        // fill can never leave the bounding box.
+        let mut pocket = Pocket {
+            volume: 0,
+            surface_area: 0,
+        };
+        let mut to_visit = vec![start];
+
+        while let Some(cube) = to_visit.pop() {
+            pocket.volume += 1;
+
+            for neighbour in cube.neighbours() {
+                if cubes.contains(&neighbour) {
+                    pocket.surface_area += 1;
+                } else if visited.insert(neighbour) {
+                    to_visit.push(neighbour);
+                }
+            }
+        }
+
+        pockets.push(pocket);
+    }
+
+    pockets
+}
+
+fn interior_pockets(input: &[String]) -> Result<Vec<Pocket>, Day18Error> {
+    let cubes = parse_cubes::<3>(input)?;
+    let boundaries = compute_boundaries(&cubes)?;
+
+    let exterior = find_exterior(&cubes, boundaries);
+
+    Ok(find_pockets(&cubes, boundaries, &exterior))
+}
+
+fn render_slices(input: &[String]) -> Result<String, Day18Error> {
+    let cubes = parse_cubes::<3>(input)?;
+    let boundaries = compute_boundaries(&cubes)?;
+
+    let exterior = find_exterior(&cubes, boundaries);
+
+    let [min_x, min_y, min_z] = boundaries.min;
+    let [max_x, max_y, max_z] = boundaries.max;
+
+    let mut output = String::new();
+
+    for z in min_z..=max_z {
+        if z > min_z {
+            output.push('\n');
+        }
+
+        output.push_str(&format!("z = {z}\n"));
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let cube = Cube([x, y, z]);
+
+                output.push(if cubes.contains(&cube) {
+                    '#'
+                } else if exterior.contains(&cube) {
+                    '.'
+                } else {
+                    'o'
+                });
+            }
+            output.push('\n');
+        }
+    }
+
+    Ok(output)
+}
+
+fn to_obj(faces: &[Face]) -> String {
+    let mut vertex_indices = HashMap::new();
+    let mut vertices = String::new();
+    let mut polygons = String::new();
+
+    for face in faces {
+        polygons.push('f');
+
+        for corner in face.corners() {
+            let next_index = vertex_indices.len() + 1;
+            let index = *vertex_indices.entry(corner).or_insert_with(|| {
+                let [x, y, z] = corner;
+                vertices.push_str(&format!("v {x} {y} {z}\n"));
+
+                next_index
+            });
+
+            polygons.push_str(&format!(" {index}"));
+        }
+
+        polygons.push('\n');
+    }
+
+    vertices + &polygons
 }
 
 #[cfg(test)]
@@ -263,4 +756,193 @@ mod tests {
 
         assert_eq!(part2(&input).unwrap(), 58);
     }
+
+    #[test]
+    fn test_find_exterior_dense() {
+        let input = to_lines(EXAMPLE);
+        let cubes = parse_cubes(&input).unwrap();
+        let boundaries = compute_boundaries(&cubes).unwrap();
+
+        let sparse = find_exterior_sparse(&cubes, boundaries);
+        let dense = find_exterior_dense(&cubes, boundaries);
+
+        assert_eq!(
+            dense.cells.iter().filter(|&&cell| cell).count(),
+            sparse.len()
+        );
+        assert!(sparse.iter().all(|cube| dense.contains(cube)));
+
+        // The air pocket inside the example droplet is not part of the exterior.
+        assert!(!dense.contains(&Cube([2, 2, 5])));
+        assert!(matches!(
+            find_exterior(&cubes, boundaries),
+            Exterior::Dense(_)
+        ));
+    }
+
+    #[test]
+    fn test_render_slices() {
+        let input = to_lines(EXAMPLE);
+
+        let slices = render_slices(&input).unwrap();
+
+        assert!(slices.starts_with("z = 1\n...\n.#.\n...\n\nz = 2\n"));
+        assert!(slices.contains("z = 5\n.#.\n#o#\n.#.\n"));
+        assert_eq!(slices.matches('o').count(), 1);
+    }
+
+    #[test]
+    fn test_to_obj() {
+        let faces = exterior_surface(&to_lines("1,1,1\n2,1,1\n")).unwrap();
+
+        assert_eq!(faces.len(), 10);
+        assert_eq!(
+            faces[0].corners(),
+            [[1, 1, 2], [1, 2, 2], [1, 2, 1], [1, 1, 1]]
+        );
+
+        let obj = to_obj(&faces);
+
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("v ")).count(),
+            12
+        );
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("f ")).count(),
+            10
+        );
+        assert!(obj.starts_with("v 1 1 2\nv 1 2 2\nv 1 2 1\nv 1 1 1\n"));
+    }
+
+    #[test]
+    fn test_interior_pockets() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(
+            interior_pockets(&input).unwrap(),
+            vec![Pocket {
+                volume: 1,
+                surface_area: 6
+            }]
+        );
+
+        // A hollow 4x3x3 box with a 2-cell cavity, next to the example droplet.
+        let mut hollow_box = vec![];
+        for z in 10..=12 {
+            for y in 1..=3 {
+                for x in 1..=4 {
+                    if (y, z) != (2, 11) || x == 1 || x == 4 {
+                        hollow_box.push(format!("{x},{y},{z}"));
+                    }
+                }
+            }
+        }
+        let input = [input, hollow_box].concat();
+
+        let pockets = interior_pockets(&input).unwrap();
+
+        assert_eq!(
+            pockets,
+            vec![
+                Pocket {
+                    volume: 1,
+                    surface_area: 6
+                },
+                Pocket {
+                    volume: 2,
+                    surface_area: 10
+                }
+            ]
+        );
+        assert_eq!(
+            part1(&input).unwrap() - part2(&input).unwrap(),
+            pockets
+                .iter()
+                .map(|pocket| pocket.surface_area)
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_droplet_stats() {
+        let input = to_lines(EXAMPLE);
+
+        let stats = droplet_stats::<3>(&input).unwrap();
+        assert_eq!(
+            stats,
+            DropletStats {
+                cubes: 13,
+                boundaries: Boundaries {
+                    min: [1, 1, 1],
+                    max: [3, 3, 6]
+                },
+                surface_area: 64,
+                exterior_surface_area: 58,
+            }
+        );
+        assert_eq!(stats.density(), Some(13.0 / 54.0));
+
+        assert_eq!(
+            stats_report(&input, 3).unwrap(),
+            "\
+Cubes: 13
+Bounding box: 1,1,1 to 3,3,6
+Extents: x=3, y=3, z=6
+Fill density: 24.07% (13 of 54 cells)
+Surface to volume: 4.923 (64 faces), 4.462 exterior (58 faces)
+"
+        );
+
+        let lone = to_lines("1,1,1,1\n");
+        assert!(stats_report(&lone, 4)
+            .unwrap()
+            .ends_with("Surface to volume: 8.000 (8 faces), 8.000 exterior (8 faces)\n"));
+
+        let far_apart = DropletStats {
+            cubes: 2,
+            boundaries: Boundaries {
+                min: [1, 1],
+                max: [u64::MAX, 2],
+            },
+            surface_area: 8,
+            exterior_surface_area: 8,
+        };
+        assert_eq!(far_apart.density(), None);
+        assert!(far_apart
+            .to_string()
+            .contains("Fill density: bounding box too large\n"));
+
+        assert!(matches!(
+            droplet_stats::<3>(&[]),
+            Err(Day18Error::EmptyInput)
+        ));
+        assert!(matches!(
+            stats_report(&input, 5),
+            Err(Day18Error::InvalidDimensions(5))
+        ));
+    }
+
+    #[test]
+    fn test_generate_droplet() {
+        let droplet = generate_droplet(5);
+
+        assert_eq!(droplet.len(), 515);
+        assert_eq!(part1(&droplet).unwrap(), part2(&droplet).unwrap());
+    }
+
+    #[test]
+    fn test_other_dimensions() {
+        // A ring of 8 squares around a hole.
+        let ring = to_lines("1,1\n2,1\n3,1\n1,2\n3,2\n1,3\n2,3\n3,3\n");
+
+        assert_eq!(surface_areas::<2>(&ring).unwrap(), (16, 12));
+        assert!(matches!(
+            surface_areas::<3>(&ring),
+            Err(Day18Error::InvalidCube)
+        ));
+
+        let hypercubes = to_lines("1,1,1,1\n1,1,1,2\n");
+
+        assert_eq!(surface_areas::<4>(&hypercubes).unwrap(), (14, 14));
+    }
 }
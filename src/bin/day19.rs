@@ -1,13 +1,23 @@
-use std::{io, num::ParseIntError, str::FromStr};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt, io,
+    num::ParseIntError,
+    str::FromStr,
+    time::Instant,
+};
 
-use aoc::read_lines;
-use regex::Regex;
+use aoc::{cli::Args, progress::Progress, puzzles::part_label, read_lines};
+
+#[cfg(feature = "webhook")]
+use aoc::webhook;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day19Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
     ParseBlueprintError,
+    InvalidEngine(String),
 }
 
 impl From<io::Error> for Day19Error {
@@ -25,10 +35,63 @@ impl From<ParseIntError> for Day19Error {
 const INPUT_PATH: &str = "inputs/day19.txt";
 
 fn main() -> Result<(), Day19Error> {
+    let args = Args::from_env();
+
+    // Parses generated lines, so it doesn't need an input.
+    if let Some(lines) = args.parse("--parse-benchmark")? {
+        parse_benchmark(lines)?;
+        return Ok(());
+    }
+
     let input = read_lines(INPUT_PATH)?;
+    let engine = args.parse("--engine")?.unwrap_or_default();
+
+    let quiet = args.flag("--quiet");
+
+    // Degenerate blueprints still get searched, but their answers are suspect.
+    for blueprint in parse_blueprints(&input)? {
+        for warning in blueprint.lint(24) {
+            eprintln!("Warning: blueprint {}: {}", blueprint.id, warning);
+        }
+    }
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    #[cfg(feature = "webhook")]
+    let start = Instant::now();
+    let answer = part1(&input, engine, quiet)?;
+    println!("{}: {:?}", part_label(19, 1), answer);
+    #[cfg(feature = "webhook")]
+    webhook::notify_completion(19, 1, &answer, start.elapsed());
+
+    #[cfg(feature = "webhook")]
+    let start = Instant::now();
+    let answer = part2(&input, engine, quiet)?;
+    println!("{}: {:?}", part_label(19, 2), answer);
+    #[cfg(feature = "webhook")]
+    webhook::notify_completion(19, 2, &answer, start.elapsed());
+
+    if args.flag("--plan") {
+        for blueprint in parse_blueprints(&input)? {
+            let (geodes, builds) = optimal_build_order(24, &blueprint);
+
+            println!("== Blueprint {}: {} geodes ==", blueprint.id, geodes);
+            println!("{}", describe_build_order(24, &blueprint, &builds));
+        }
+    }
+
+    if args.flag("--stats") {
+        for blueprint in parse_blueprints(&input)? {
+            let (geodes, stats) = max_geodes_with_stats(24, &blueprint);
+            println!(
+                "Blueprint {}: {} geodes, {} nodes, {} pruned by bound, {} pruned by dominance, {} cache hits",
+                blueprint.id,
+                geodes,
+                stats.nodes,
+                stats.pruned_by_bound,
+                stats.pruned_by_dominance,
+                stats.cache_hits
+            );
+        }
+    }
 
     Ok(())
 }
@@ -48,31 +111,174 @@ impl FromStr for Blueprint {
     type Err = Day19Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let blueprint_regex =
-            Regex::new(r"^Blueprint ([0-9]+): Each ore robot costs ([0-9]+) ore\. Each clay robot costs ([0-9]+) ore\. Each obsidian robot costs ([0-9]+) ore and ([0-9]+) clay\. Each geode robot costs ([0-9]+) ore and ([0-9]+) obsidian\.$")
-                .unwrap();
-
-        if let Some(captures) = blueprint_regex.captures(s) {
-            Ok(Self {
-                id: captures[1].parse()?,
-                ore_robot_ore_cost: captures[2].parse()?,
-                clay_robot_ore_cost: captures[3].parse()?,
-                obsidian_robot_ore_cost: captures[4].parse()?,
-                obsidian_robot_clay_cost: captures[5].parse()?,
-                geode_robot_ore_cost: captures[6].parse()?,
-                geode_robot_obsidian_cost: captures[7].parse()?,
-            })
-        } else {
-            Err(Self::Err::ParseBlueprintError)
+        const SEPARATORS: [&str; 7] = [
+            ": Each ore robot costs ",
+            " ore. Each clay robot costs ",
+            " ore. Each obsidian robot costs ",
+            " ore and ",
+            " clay. Each geode robot costs ",
+            " ore and ",
+            " obsidian.",
+        ];
+
+        let mut rest = s
+            .strip_prefix("Blueprint ")
+            .ok_or(Self::Err::ParseBlueprintError)?;
+        let mut numbers = [0; 7];
+
+        for (number, separator) in numbers.iter_mut().zip(SEPARATORS) {
+            let (value, after) = rest
+                .split_once(separator)
+                .ok_or(Self::Err::ParseBlueprintError)?;
+
+            *number = value.parse()?;
+            rest = after;
+        }
+
+        if !rest.is_empty() {
+            return Err(Self::Err::ParseBlueprintError);
+        }
+
+        let [id, ore_robot_ore_cost, clay_robot_ore_cost, obsidian_robot_ore_cost, obsidian_robot_clay_cost, geode_robot_ore_cost, geode_robot_obsidian_cost] =
+            numbers;
+
+        Ok(Self {
+            id,
+            ore_robot_ore_cost,
+            clay_robot_ore_cost,
+            obsidian_robot_ore_cost,
+            obsidian_robot_clay_cost,
+            geode_robot_ore_cost,
+            geode_robot_obsidian_cost,
+        })
+    }
+}
+
+impl Blueprint {
+    fn max_ore_cost(&self) -> u64 {
+        self.ore_robot_ore_cost
+            .max(self.clay_robot_ore_cost)
+            .max(self.obsidian_robot_ore_cost)
+            .max(self.geode_robot_ore_cost)
+    }
+
+    fn costs(&self, robot_type: &RobotType) -> [u64; 2] {
+        use RobotType::*;
+
+        match robot_type {
+            Ore => [self.ore_robot_ore_cost, 1],
+            Clay => [self.clay_robot_ore_cost, 1],
+            Obsidian => [self.obsidian_robot_ore_cost, self.obsidian_robot_clay_cost],
+            Geode => [self.geode_robot_ore_cost, self.geode_robot_obsidian_cost],
+        }
+    }
+
+    // Earliest minute an obsidian robot could be built, assuming ore is never
+    // short and a clay robot is built every minute once the first is
+    // affordable. `None` if that is already past the time limit.
+    fn earliest_obsidian_robot(&self, time_limit: u64) -> Option<u64> {
+        let mut minute = self.clay_robot_ore_cost.saturating_add(1);
+        let (mut clay_robots, mut clay) = (0, 0u64);
+
+        while clay < self.obsidian_robot_clay_cost {
+            if minute >= time_limit {
+                return None;
+            }
+
+            clay = clay.saturating_add(clay_robots);
+            clay_robots += 1;
+            minute += 1;
+        }
+
+        Some(minute)
+    }
+
+    fn lint(&self, time_limit: u64) -> Vec<BlueprintWarning> {
+        use RobotType::*;
+
+        let mut warnings: Vec<_> = [Ore, Clay, Obsidian, Geode]
+            .into_iter()
+            .filter(|robot_type| self.costs(robot_type).contains(&0))
+            .map(BlueprintWarning::ZeroCost)
+            .collect();
+
+        let [geode_ore, geode_obsidian] = self.costs(&Geode);
+        if geode_ore.saturating_add(geode_obsidian) < self.ore_robot_ore_cost {
+            warnings.push(BlueprintWarning::GeodeCheaperThanOre);
+        }
+
+        // Obsidian collection starts the minute after its robot is built.
+        if self
+            .earliest_obsidian_robot(time_limit)
+            .is_none_or(|minute| minute >= time_limit)
+        {
+            warnings.push(BlueprintWarning::UnreachableObsidian(time_limit));
+        }
+
+        warnings
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum BlueprintWarning {
+    // The search never builds robots whose output costs nothing, so a zero
+    // clay or obsidian cost means no geodes at all.
+    ZeroCost(RobotType),
+    GeodeCheaperThanOre,
+    UnreachableObsidian(u64),
+}
+
+impl fmt::Display for BlueprintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ZeroCost(robot_type) => write!(f, "{} has a zero cost", robot_type.description()),
+            Self::GeodeCheaperThanOre => write!(
+                f,
+                "a geode-cracking robot costs fewer resources than an ore-collecting robot"
+            ),
+            Self::UnreachableObsidian(time_limit) => write!(
+                f,
+                "no obsidian can be collected within {} minutes",
+                time_limit
+            ),
         }
     }
 }
 
-fn parse_blueprints(input: &Vec<String>) -> Result<Vec<Blueprint>, Day19Error> {
+fn generate_blueprint_lines(lines: usize) -> Vec<String> {
+    (0..lines)
+        .map(|i| {
+            format!(
+                "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+                i + 1,
+                2 + i % 3,
+                2 + i % 4,
+                2 + i % 2,
+                5 + i % 15,
+                2 + i % 3,
+                5 + i % 17
+            )
+        })
+        .collect()
+}
+
+fn parse_benchmark(lines: usize) -> Result<(), Day19Error> {
+    let input = generate_blueprint_lines(lines);
+
+    let start = Instant::now();
+    for line in &input {
+        line.parse::<Blueprint>()?;
+    }
+    println!("Parsed {} blueprints in {:?}", lines, start.elapsed());
+
+    Ok(())
+}
+
+fn parse_blueprints(input: &[String]) -> Result<Vec<Blueprint>, Day19Error> {
     input.iter().map(|line| line.parse()).collect()
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum RobotType {
     Ore,
     Clay,
@@ -80,6 +286,36 @@ enum RobotType {
     Geode,
 }
 
+impl RobotType {
+    fn description(&self) -> &'static str {
+        use RobotType::*;
+
+        match self {
+            Ore => "an ore-collecting robot",
+            Clay => "a clay-collecting robot",
+            Obsidian => "an obsidian-collecting robot",
+            Geode => "a geode-cracking robot",
+        }
+    }
+
+    fn cost(&self, blueprint: &Blueprint) -> String {
+        use RobotType::*;
+
+        match self {
+            Ore => format!("{} ore", blueprint.ore_robot_ore_cost),
+            Clay => format!("{} ore", blueprint.clay_robot_ore_cost),
+            Obsidian => format!(
+                "{} ore and {} clay",
+                blueprint.obsidian_robot_ore_cost, blueprint.obsidian_robot_clay_cost
+            ),
+            Geode => format!(
+                "{} ore and {} obsidian",
+                blueprint.geode_robot_ore_cost, blueprint.geode_robot_obsidian_cost
+            ),
+        }
+    }
+}
+
 enum Action {
     DoNothing,
     MakeRobot(RobotType),
@@ -150,16 +386,56 @@ impl State {
         self
     }
 
+    fn robots(&self) -> [u64; 4] {
+        [
+            self.ore_robots,
+            self.clay_robots,
+            self.obsidian_robots,
+            self.geode_robots,
+        ]
+    }
+
+    fn resources(&self) -> [u64; 4] {
+        [self.ore, self.clay, self.obsidian, self.geode]
+    }
+
+    // Packs the whole state into one number, 16 bits per field, for cheap hashing.
+    fn key(&self) -> u128 {
+        [
+            self.ore_robots,
+            self.clay_robots,
+            self.obsidian_robots,
+            self.geode_robots,
+            self.ore,
+            self.clay,
+            self.obsidian,
+            self.geode,
+        ]
+        .into_iter()
+        .fold(0, |key, field| {
+            (key << 16) | field.min(u16::MAX as u64) as u128
+        })
+    }
+
+    // Robots can only spend so much of each resource in the time left, so any
+    // more than that is indistinguishable and can be thrown away.
+    fn clamp(mut self, blueprint: &Blueprint, time_left: u64) -> Self {
+        self.ore = self.ore.min(blueprint.max_ore_cost() * time_left);
+        self.clay = self
+            .clay
+            .min(blueprint.obsidian_robot_clay_cost * time_left);
+        self.obsidian = self
+            .obsidian
+            .min(blueprint.geode_robot_obsidian_cost * time_left);
+
+        self
+    }
+
     fn should_build_more(&self, blueprint: &Blueprint, robot_type: &RobotType) -> bool {
         use RobotType::*;
 
         match robot_type {
-            Ore => {
-                self.ore_robots < blueprint.ore_robot_ore_cost
-                    || self.ore_robots < blueprint.clay_robot_ore_cost
-                    || self.ore_robots < blueprint.obsidian_robot_ore_cost
-                    || self.ore_robots < blueprint.geode_robot_ore_cost
-            }
+            Ore => self.ore_robots < blueprint.max_ore_cost(),
             Clay => self.clay_robots < blueprint.obsidian_robot_clay_cost,
             Obsidian => self.obsidian_robots < blueprint.geode_robot_obsidian_cost,
             Geode => true,
@@ -219,27 +495,124 @@ impl State {
 }
 
 fn div_ceil(a: u64, b: u64) -> u64 {
-    (a + b - 1) / b
+    a.div_ceil(b)
+}
+
+// Nodes explored between progress reports.
+const PROGRESS_INTERVAL: u64 = 1 << 20;
+
+// Remembering states near the time limit costs more than re-exploring them.
+const MEMO_MIN_TIME_LEFT: u64 = 10;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct SearchStats {
+    nodes: u64,
+    pruned_by_bound: u64,
+    pruned_by_dominance: u64,
+    cache_hits: u64,
 }
 
-fn max_geodes(time_limit: u64, blueprint: &Blueprint) -> u64 {
-    let mut result = 0;
+struct Search<'a> {
+    time_limit: u64,
+    blueprint: &'a Blueprint,
+    best: u64,
+    // Earliest time each combination of robots and resources was seen.
+    seen: HashMap<u128, u64>,
+    // Most geodes found from each (time left, clamped state).
+    cache: HashMap<(u64, u128), u64>,
+    stats: SearchStats,
+    // Robots started on the current path and on the path to `best`, by minute.
+    builds: Vec<(u64, RobotType)>,
+    best_builds: Vec<(u64, RobotType)>,
+    progress: Option<&'a Progress>,
+}
 
-    fn aux(
-        time_limit: u64,
-        blueprint: &Blueprint,
-        time_spent: u64,
-        state: State,
-        result: &mut u64,
-    ) {
+impl<'a> Search<'a> {
+    fn new(time_limit: u64, blueprint: &'a Blueprint) -> Self {
+        Self {
+            time_limit,
+            blueprint,
+            best: 0,
+            seen: HashMap::new(),
+            cache: HashMap::new(),
+            stats: SearchStats::default(),
+            builds: vec![],
+            best_builds: vec![],
+            progress: None,
+        }
+    }
+
+    fn with_progress(mut self, progress: &'a Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn is_dominated(&mut self, time_spent: u64, state: &State) -> bool {
+        // The same robots and resources reached earlier can do anything this
+        // state can, with time to spare.
+        match self.seen.entry(state.key()) {
+            Entry::Occupied(mut entry) => {
+                if *entry.get() <= time_spent {
+                    return true;
+                }
+
+                entry.insert(time_spent);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(time_spent);
+            }
+        }
+
+        false
+    }
+
+    // Most geodes found by the end from this state. Branches that cannot
+    // beat the best so far are cut, so this may undercount for those.
+    fn explore(&mut self, time_spent: u64, state: State) -> u64 {
         use Action::*;
         use RobotType::*;
 
-        assert!(time_spent <= time_limit);
+        assert!(time_spent <= self.time_limit);
 
-        let mut stuck = true;
+        self.stats.nodes += 1;
+
+        if let Some(progress) = self.progress {
+            if self.stats.nodes.is_multiple_of(PROGRESS_INTERVAL) {
+                progress.report(&format!(
+                    "Blueprint {}: {} nodes, best {} geodes",
+                    self.blueprint.id, self.stats.nodes, self.best
+                ));
+            }
+        }
+
+        let time_left = self.time_limit - time_spent;
+        let state = state.clamp(self.blueprint, time_left);
+
+        let key = (time_left, state.key());
+        let remember = time_left >= MEMO_MIN_TIME_LEFT;
+
+        if remember {
+            if let Some(&geodes) = self.cache.get(&key) {
+                self.stats.cache_hits += 1;
+                return geodes;
+            }
+
+            if self.is_dominated(time_spent, &state) {
+                self.stats.pruned_by_dominance += 1;
+                return 0;
+            }
+        }
+
+        // The robots we have keep collecting until the limit.
+        let mut geodes = state.geode + time_left * state.geode_robots;
+
+        // Cached and dominated states never beat the best, so every new best
+        // is found here and the current path leads to it.
+        if geodes > self.best {
+            self.best = geodes;
+            self.best_builds = self.builds.clone();
+        }
 
-        let time_left = time_limit - time_spent;
         if time_left > 0 {
             // g = state.geodes, r = state.geode_robots, n = time_left
             // maximum geodes if we could buy a new geode robot every step:
@@ -249,63 +622,360 @@ fn max_geodes(time_limit: u64, blueprint: &Blueprint) -> u64 {
             let geode_upper_bound =
                 state.geode + time_left * state.geode_robots + (time_left * (time_left - 1)) / 2;
 
-            if geode_upper_bound > *result {
+            if geode_upper_bound > self.best {
                 for robot_type in [Ore, Clay, Obsidian, Geode] {
-                    if state.should_build_more(blueprint, &robot_type) {
-                        if let Some(time_to_wait) = state.time_to_wait(blueprint, &robot_type) {
-                            if time_spent + time_to_wait + 1 <= time_limit {
+                    if state.should_build_more(self.blueprint, &robot_type) {
+                        if let Some(time_to_wait) = state.time_to_wait(self.blueprint, &robot_type)
+                        {
+                            if time_spent + time_to_wait < self.time_limit {
                                 let mut state = state;
                                 for _ in 0..time_to_wait {
-                                    state = state.tick(blueprint, &DoNothing);
+                                    state = state.tick(self.blueprint, &DoNothing);
                                 }
-                                state = state.tick(blueprint, &MakeRobot(robot_type));
-
-                                aux(
-                                    time_limit,
-                                    blueprint,
-                                    time_spent + time_to_wait + 1,
-                                    state,
-                                    result,
-                                );
-                                stuck = false;
+                                state = state.tick(self.blueprint, &MakeRobot(robot_type));
+
+                                let minute = time_spent + time_to_wait + 1;
+
+                                self.builds.push((minute, robot_type));
+                                geodes = geodes.max(self.explore(minute, state));
+                                self.builds.pop();
                             }
                         }
                     }
                 }
+            } else {
+                self.stats.pruned_by_bound += 1;
             }
         }
 
-        if stuck {
-            let mut state = state;
-            // if no options, advance time until the limit and measure the result
-            for _ in time_spent..time_limit {
-                state = state.tick(blueprint, &Action::DoNothing);
+        if remember {
+            self.cache.insert(key, geodes);
+        }
+
+        geodes
+    }
+}
+
+fn max_geodes_with_stats(time_limit: u64, blueprint: &Blueprint) -> (u64, SearchStats) {
+    let mut search = Search::new(time_limit, blueprint);
+    search.explore(0, State::new());
+
+    (search.best, search.stats)
+}
+
+fn optimal_build_order(time_limit: u64, blueprint: &Blueprint) -> (u64, Vec<(u64, RobotType)>) {
+    let mut search = Search::new(time_limit, blueprint);
+    search.explore(0, State::new());
+
+    (search.best, search.best_builds)
+}
+
+fn plural(count: u64, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{count} {singular}")
+    } else {
+        format!("{count} {plural}")
+    }
+}
+
+fn describe_build_order(
+    time_limit: u64,
+    blueprint: &Blueprint,
+    builds: &[(u64, RobotType)],
+) -> String {
+    let mut output = String::new();
+    let mut state = State::new();
+
+    for minute in 1..=time_limit {
+        if minute > 1 {
+            output.push('\n');
+        }
+        output.push_str(&format!("== Minute {minute} ==\n"));
+
+        let build = builds
+            .iter()
+            .find(|(build_minute, _)| *build_minute == minute)
+            .map(|&(_, robot_type)| robot_type);
+
+        if let Some(robot_type) = build {
+            output.push_str(&format!(
+                "Spend {} to start building {}.\n",
+                robot_type.cost(blueprint),
+                robot_type.description()
+            ));
+        }
+
+        let robots = state;
+        state = state.tick(
+            blueprint,
+            &match build {
+                Some(robot_type) => Action::MakeRobot(robot_type),
+                None => Action::DoNothing,
+            },
+        );
+
+        let collected = [
+            (
+                robots.ore_robots,
+                "ore-collecting",
+                "collect",
+                "ore",
+                state.ore,
+            ),
+            (
+                robots.clay_robots,
+                "clay-collecting",
+                "collect",
+                "clay",
+                state.clay,
+            ),
+            (
+                robots.obsidian_robots,
+                "obsidian-collecting",
+                "collect",
+                "obsidian",
+                state.obsidian,
+            ),
+        ];
+
+        for (count, kind, verb, resource, total) in collected {
+            if count > 0 {
+                output.push_str(&format!(
+                    "{} {}{} {count} {resource}; you now have {total} {resource}.\n",
+                    plural(count, &format!("{kind} robot"), &format!("{kind} robots")),
+                    verb,
+                    if count == 1 { "s" } else { "" },
+                ));
             }
-            *result = (*result).max(state.geode);
+        }
+
+        if robots.geode_robots > 0 {
+            output.push_str(&format!(
+                "{} {} {}; you now have {}.\n",
+                plural(
+                    robots.geode_robots,
+                    "geode-cracking robot",
+                    "geode-cracking robots"
+                ),
+                if robots.geode_robots == 1 {
+                    "cracks"
+                } else {
+                    "crack"
+                },
+                plural(robots.geode_robots, "geode", "geodes"),
+                plural(state.geode, "open geode", "open geodes"),
+            ));
+        }
+
+        if let Some(robot_type) = build {
+            let count = state.robots()[robot_type as usize];
+            let description = robot_type.description();
+            let (_, name) = description.split_once(' ').unwrap();
+
+            output.push_str(&format!(
+                "The new {name} is ready; you now have {count} of them.\n"
+            ));
+        }
+    }
+
+    output
+}
+
+fn can_afford(state: &State, blueprint: &Blueprint, robot_type: &RobotType) -> bool {
+    state.time_to_wait(blueprint, robot_type) == Some(0)
+}
+
+fn keep_non_dominated(states: Vec<State>) -> Vec<State> {
+    // Only states with the same robots are compared, which keeps the groups small.
+    let mut groups: HashMap<[u64; 4], Vec<State>> = HashMap::new();
+    for state in states {
+        groups.entry(state.robots()).or_default().push(state);
+    }
+
+    let dominates = |a: &State, b: &State| {
+        a.resources()
+            .iter()
+            .zip(b.resources())
+            .all(|(a, b)| *a >= b)
+    };
+
+    let mut frontier = vec![];
+
+    for mut group in groups.into_values() {
+        group.sort_by_key(|state| std::cmp::Reverse(state.resources().iter().sum::<u64>()));
+
+        let mut kept: Vec<State> = vec![];
+        for state in group {
+            if !kept.iter().any(|other| dominates(other, &state)) {
+                kept.push(state);
+            }
+        }
+
+        frontier.extend(kept);
+    }
+
+    frontier
+}
+
+fn frontier_max_geodes(time_limit: u64, blueprint: &Blueprint, progress: &Progress) -> u64 {
+    use Action::*;
+    use RobotType::*;
+
+    let expand = |state: &State| {
+        let mut next = vec![state.tick(blueprint, &DoNothing)];
+
+        for robot_type in [Ore, Clay, Obsidian, Geode] {
+            if state.should_build_more(blueprint, &robot_type)
+                && can_afford(state, blueprint, &robot_type)
+            {
+                next.push(state.tick(blueprint, &MakeRobot(robot_type)));
+            }
+        }
+
+        next
+    };
+
+    let mut frontier = vec![State::new()];
+
+    for minute in 1..=time_limit {
+        let time_left = time_limit - minute;
+
+        #[cfg(not(feature = "parallel"))]
+        let next: Vec<_> = frontier.iter().flat_map(expand).collect();
+
+        #[cfg(feature = "parallel")]
+        let next: Vec<_> = {
+            use rayon::prelude::*;
+
+            frontier.par_iter().flat_map_iter(expand).collect()
+        };
+
+        let next: Vec<_> = next
+            .into_iter()
+            .map(|state| state.clamp(blueprint, time_left))
+            .collect();
+
+        // Same bounds as the search: what we are sure to get by idling, and
+        // what we would get with a new geode robot every minute.
+        let guaranteed = next
+            .iter()
+            .map(|state| state.geode + time_left * state.geode_robots)
+            .max()
+            .unwrap_or(0);
+
+        let next = next
+            .into_iter()
+            .filter(|state| {
+                let upper_bound = state.geode
+                    + time_left * state.geode_robots
+                    + (time_left * time_left.saturating_sub(1)) / 2;
+
+                upper_bound >= guaranteed
+            })
+            .collect();
+
+        frontier = keep_non_dominated(next);
+
+        let best = frontier.iter().map(|state| state.geode).max().unwrap_or(0);
+        progress.report(&format!(
+            "Blueprint {}: minute {}, {} states, best {} geodes",
+            blueprint.id,
+            minute,
+            frontier.len(),
+            best
+        ));
+    }
+
+    frontier.iter().map(|state| state.geode).max().unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Engine {
+    #[default]
+    Search,
+    Frontier,
+}
+
+impl FromStr for Engine {
+    type Err = Day19Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "search" => Ok(Self::Search),
+            "frontier" => Ok(Self::Frontier),
+            _ => Err(Self::Err::InvalidEngine(s.to_owned())),
         }
     }
+}
+
+fn evaluate_blueprint(
+    time_limit: u64,
+    blueprint: &Blueprint,
+    engine: Engine,
+    progress: &Progress,
+) -> u64 {
+    progress.report(&format!("Blueprint {}: searching", blueprint.id));
+
+    let geodes = match engine {
+        Engine::Search => {
+            let mut search = Search::new(time_limit, blueprint).with_progress(progress);
+            search.explore(0, State::new());
+
+            search.best
+        }
+        Engine::Frontier => frontier_max_geodes(time_limit, blueprint, progress),
+    };
+
+    progress.finish_one();
+    progress.report(&format!("Blueprint {}: {} geodes", blueprint.id, geodes));
+
+    geodes
+}
+
+fn evaluate_blueprints(
+    time_limit: u64,
+    blueprints: &[Blueprint],
+    engine: Engine,
+    quiet: bool,
+) -> Vec<u64> {
+    let progress = Progress::new(blueprints.len(), quiet);
+
+    #[cfg(not(feature = "parallel"))]
+    let geodes = blueprints
+        .iter()
+        .map(|blueprint| evaluate_blueprint(time_limit, blueprint, engine, &progress))
+        .collect();
+
+    #[cfg(feature = "parallel")]
+    let geodes = {
+        use rayon::prelude::*;
 
-    aux(time_limit, blueprint, 0, State::new(), &mut result);
+        blueprints
+            .par_iter()
+            .map(|blueprint| evaluate_blueprint(time_limit, blueprint, engine, &progress))
+            .collect()
+    };
 
-    result
+    geodes
 }
 
-fn part1(input: &Vec<String>) -> Result<u64, Day19Error> {
+fn part1(input: &[String], engine: Engine, quiet: bool) -> Result<u64, Day19Error> {
     let blueprints = parse_blueprints(input)?;
+    let geodes = evaluate_blueprints(24, &blueprints, engine, quiet);
 
     Ok(blueprints
-        .into_iter()
-        .map(|blueprint| blueprint.id * max_geodes(24, &blueprint))
+        .iter()
+        .zip(geodes)
+        .map(|(blueprint, geodes)| blueprint.id * geodes)
         .sum())
 }
 
-fn part2(input: &Vec<String>) -> Result<u64, Day19Error> {
+fn part2(input: &[String], engine: Engine, quiet: bool) -> Result<u64, Day19Error> {
     let blueprints = parse_blueprints(input)?;
+    let blueprints = &blueprints[..blueprints.len().min(3)];
 
-    Ok(blueprints
+    Ok(evaluate_blueprints(32, blueprints, engine, quiet)
         .into_iter()
-        .take(3)
-        .map(|blueprint| max_geodes(32, &blueprint))
         .product())
 }
 
@@ -336,17 +1006,157 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         assert_eq!(blueprint, expected);
     }
 
+    #[test]
+    fn test_parse_invalid_blueprint() {
+        assert!(matches!(
+            "Blueprint 1: Each ore robot costs 4 ore.".parse::<Blueprint>(),
+            Err(Day19Error::ParseBlueprintError)
+        ));
+        assert!(matches!(
+            "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian. Extra".parse::<Blueprint>(),
+            Err(Day19Error::ParseBlueprintError)
+        ));
+        assert!(matches!(
+            "Blueprint x: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.".parse::<Blueprint>(),
+            Err(Day19Error::ParseIntError(_))
+        ));
+    }
+
+    #[test]
+    fn test_lint() {
+        let blueprints = parse_blueprints(&to_lines(EXAMPLE)).unwrap();
+        assert!(blueprints
+            .iter()
+            .all(|blueprint| blueprint.lint(24).is_empty()));
+
+        assert_eq!(blueprints[0].earliest_obsidian_robot(24), Some(9));
+        assert_eq!(
+            blueprints[0].lint(9),
+            vec![BlueprintWarning::UnreachableObsidian(9)]
+        );
+        assert!(blueprints[0].lint(10).is_empty());
+
+        let degenerate = Blueprint {
+            id: 3,
+            ore_robot_ore_cost: 4,
+            clay_robot_ore_cost: 2,
+            obsidian_robot_ore_cost: 3,
+            obsidian_robot_clay_cost: 14,
+            geode_robot_ore_cost: 2,
+            geode_robot_obsidian_cost: 0,
+        };
+        assert_eq!(
+            degenerate.lint(24),
+            vec![
+                BlueprintWarning::ZeroCost(RobotType::Geode),
+                BlueprintWarning::GeodeCheaperThanOre
+            ]
+        );
+        assert_eq!(
+            degenerate.lint(24)[0].to_string(),
+            "a geode-cracking robot has a zero cost"
+        );
+
+        let expensive_clay = Blueprint {
+            obsidian_robot_clay_cost: u64::MAX,
+            ..degenerate
+        };
+        assert!(expensive_clay
+            .lint(32)
+            .contains(&BlueprintWarning::UnreachableObsidian(32)));
+    }
+
+    #[test]
+    fn test_generate_blueprint_lines() {
+        let blueprints = parse_blueprints(&generate_blueprint_lines(10)).unwrap();
+
+        assert_eq!(blueprints.len(), 10);
+        assert_eq!(blueprints[9].id, 10);
+        assert!(blueprints
+            .iter()
+            .all(|blueprint| blueprint.lint(24).is_empty()));
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part1(&input).unwrap(), 33);
+        assert_eq!(part1(&input, Engine::Search, true).unwrap(), 33);
+        assert_eq!(part1(&input, Engine::Frontier, true).unwrap(), 33);
     }
 
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part2(&input).unwrap(), 3472);
+        assert_eq!(part2(&input, Engine::Search, true).unwrap(), 3472);
+        assert_eq!(part2(&input, Engine::Frontier, true).unwrap(), 3472);
+    }
+
+    #[test]
+    fn test_search_stats() {
+        let blueprint: Blueprint = to_lines(EXAMPLE)[1].parse().unwrap();
+
+        let (geodes, stats) = max_geodes_with_stats(32, &blueprint);
+
+        assert_eq!(geodes, 62);
+        assert!(stats.pruned_by_bound > 0);
+        assert!(stats.pruned_by_dominance > 0);
+        assert!(stats.cache_hits > 0);
+        assert!(stats.pruned_by_bound + stats.pruned_by_dominance + stats.cache_hits < stats.nodes);
+    }
+
+    #[test]
+    fn test_optimal_build_order() {
+        let blueprint: Blueprint = to_lines(EXAMPLE)[0].parse().unwrap();
+
+        let (geodes, builds) = optimal_build_order(24, &blueprint);
+        let description = describe_build_order(24, &blueprint, &builds);
+
+        assert_eq!(geodes, 9);
+        assert_eq!(
+            builds
+                .iter()
+                .filter(|(_, robot)| *robot == RobotType::Geode)
+                .count(),
+            2
+        );
+        assert!(description.starts_with(
+            "\
+== Minute 1 ==
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+
+== Minute 2 ==
+1 ore-collecting robot collects 1 ore; you now have 2 ore.
+
+== Minute 3 ==
+Spend 2 ore to start building a clay-collecting robot.
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+The new clay-collecting robot is ready; you now have 1 of them.
+"
+        ));
+        assert!(description.ends_with("you now have 9 open geodes.\n"));
+    }
+
+    #[test]
+    fn test_engines_agree() {
+        let progress = Progress::new(6, true);
+
+        for blueprint in parse_blueprints(&to_lines(EXAMPLE)).unwrap() {
+            for time_limit in [10, 18, 24] {
+                assert_eq!(
+                    evaluate_blueprint(time_limit, &blueprint, Engine::Frontier, &progress),
+                    evaluate_blueprint(time_limit, &blueprint, Engine::Search, &progress)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_engine() {
+        assert!(matches!(
+            "beam".parse::<Engine>(),
+            Err(Day19Error::InvalidEngine(_))
+        ));
     }
 }
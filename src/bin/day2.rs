@@ -1,6 +1,6 @@
 use std::{io, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{puzzles::part_label, read_lines};
 
 #[derive(Debug)]
 enum Day2Error {
@@ -21,8 +21,8 @@ const INPUT_PATH: &str = "inputs/day2.txt";
 fn main() -> Result<(), Day2Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(2, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(2, 2), part2(&input)?);
 
     Ok(())
 }
@@ -1,12 +1,21 @@
-use std::{collections::VecDeque, io, num::ParseIntError};
+use std::{collections::VecDeque, fmt, io, num::ParseIntError, str::FromStr, time::Instant};
 
-use aoc::read_lines;
+use aoc::{
+    cli::Args,
+    mixing::{grove_coordinates, mix, Mixer, GROVE_OFFSETS},
+    puzzles::part_label,
+    read_lines,
+    rng::XorShift,
+    timing::time_repeated,
+};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day20Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
     ZeroNotFound,
+    InvalidAlgorithm(String),
 }
 
 impl From<io::Error> for Day20Error {
@@ -24,39 +33,110 @@ impl From<ParseIntError> for Day20Error {
 const INPUT_PATH: &str = "inputs/day20.txt";
 
 fn main() -> Result<(), Day20Error> {
+    let args = Args::from_env();
+
+    // Benchmarks run on a generated file, so they don't need an input.
+    if let Some(n) = args.parse("--benchmark")? {
+        benchmark(n);
+        return Ok(());
+    }
+
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input, 811589153, 10)?);
+    let mut algorithms = args
+        .values("--algorithm")
+        .into_iter()
+        .map(str::parse)
+        .collect::<Result<Vec<Algorithm>, _>>()?;
+    if algorithms.is_empty() {
+        algorithms.push(Algorithm::default());
+    }
+
+    if let Some(runs) = args.parse("--repeat")? {
+        for algorithm in algorithms {
+            if let Some(timings) = time_repeated(runs, || part1(&input, algorithm)) {
+                println!("Part 1 ({algorithm}): {timings}");
+            }
+            if let Some(timings) = time_repeated(runs, || part2(&input, 811589153, 10, algorithm)) {
+                println!("Part 2 ({algorithm}): {timings}");
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let [algorithm] = algorithms[..] {
+        println!("{}: {:?}", part_label(20, 1), part1(&input, algorithm)?);
+        println!(
+            "{}: {:?}",
+            part_label(20, 2),
+            part2(&input, 811589153, 10, algorithm)?
+        );
+    } else {
+        for algorithm in algorithms {
+            let start = Instant::now();
+            let result = part1(&input, algorithm)?;
+            println!("Part 1 ({algorithm}): {result:?} in {:?}", start.elapsed());
+
+            let start = Instant::now();
+            let result = part2(&input, 811589153, 10, algorithm)?;
+            println!("Part 2 ({algorithm}): {result:?} in {:?}", start.elapsed());
+        }
+    }
+
+    if args.flag("--inspect") {
+        print!("{}", inspect_rounds(&input, 811589153, 10)?);
+    }
 
     Ok(())
 }
 
-fn parse_encrypted_file(input: &Vec<String>) -> Result<Vec<i64>, ParseIntError> {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Algorithm {
+    Naive,
+    #[default]
+    Tree,
+}
+
+impl FromStr for Algorithm {
+    type Err = Day20Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "naive" => Ok(Self::Naive),
+            "tree" => Ok(Self::Tree),
+            _ => Err(Self::Err::InvalidAlgorithm(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Naive => write!(f, "naive"),
+            Self::Tree => write!(f, "tree"),
+        }
+    }
+}
+
+fn parse_encrypted_file(input: &[String]) -> Result<Vec<i64>, ParseIntError> {
     input.iter().map(|line| line.parse()).collect()
 }
 
 fn move_around(i: &mut usize, index: usize, new_index: usize, n: usize) {
     if *i == index {
         *i = new_index;
-    } else {
-        if index < new_index {
-            if index < *i && *i <= new_index {
-                *i = (*i + n - 1).rem_euclid(n);
-            }
-        } else {
-            if new_index <= *i && *i < index {
-                *i = (*i + 1).rem_euclid(n);
-            }
+    } else if index < new_index {
+        if index < *i && *i <= new_index {
+            *i = (*i + n - 1).rem_euclid(n);
         }
+    } else if new_index <= *i && *i < index {
+        *i = (*i + 1).rem_euclid(n);
     }
 }
 
-fn resulting_indices_to_file(
-    encrypted_file: &Vec<i64>,
-    resulting_indices: &Vec<usize>,
-) -> Vec<i64> {
-    let mut result = encrypted_file.clone();
+fn resulting_indices_to_file(encrypted_file: &[i64], resulting_indices: &[usize]) -> Vec<i64> {
+    let mut result = encrypted_file.to_vec();
     for (index, new_index) in resulting_indices.iter().enumerate() {
         result[*new_index] = encrypted_file[index];
     }
@@ -64,16 +144,15 @@ fn resulting_indices_to_file(
     result
 }
 
-fn mix(encrypted_file: Vec<i64>, decryption_key: i64, mixing_rounds: usize) -> Vec<i64> {
+fn mix_naive(encrypted_file: &[i64], decryption_key: i64, mixing_rounds: usize) -> Vec<i64> {
     let encrypted_file: Vec<_> = encrypted_file
-        .into_iter()
+        .iter()
         .map(|value| value * decryption_key)
         .collect();
     let n = encrypted_file.len();
 
     let mut next_indices_and_original_indices_and_values: VecDeque<_> =
-        std::iter::repeat(encrypted_file.iter().enumerate().enumerate())
-            .take(mixing_rounds)
+        std::iter::repeat_n(encrypted_file.iter().enumerate().enumerate(), mixing_rounds)
             .flatten()
             .collect();
 
@@ -99,35 +178,100 @@ fn mix(encrypted_file: Vec<i64>, decryption_key: i64, mixing_rounds: usize) -> V
     resulting_indices_to_file(&encrypted_file, &resulting_indices)
 }
 
-fn grove_coordinates(mixed_encrypted_file: Vec<i64>) -> Result<i64, Day20Error> {
-    let zero_index = mixed_encrypted_file
+fn mix_with(
+    algorithm: Algorithm,
+    encrypted_file: &[i64],
+    decryption_key: i64,
+    mixing_rounds: usize,
+) -> Vec<i64> {
+    match algorithm {
+        Algorithm::Naive => mix_naive(encrypted_file, decryption_key, mixing_rounds),
+        Algorithm::Tree => mix(encrypted_file, decryption_key, mixing_rounds),
+    }
+}
+
+fn generate_encrypted_file(n: usize) -> Vec<i64> {
+    // Same spread as the real inputs: values in -10000..=10000, one zero.
+    let mut rng = XorShift::new(0x9e3779b97f4a7c15);
+
+    (0..n)
+        .map(|i| {
+            if i == n / 2 {
+                return 0;
+            }
+
+            rng.below(20001) as i64 - 10000
+        })
+        .collect()
+}
+
+fn benchmark(n: usize) {
+    let encrypted_file = generate_encrypted_file(n);
+
+    let mut results = vec![];
+
+    for algorithm in [Algorithm::Naive, Algorithm::Tree] {
+        let start = Instant::now();
+        results.push(mix_with(algorithm, &encrypted_file, 811589153, 10));
+        println!("{algorithm} mixing: {:?}", start.elapsed());
+    }
+
+    assert_eq!(results[0], results[1]);
+}
+
+fn format_arrangement(values: &[i64]) -> String {
+    values
         .iter()
-        .position(|x| *x == 0)
-        .ok_or(Day20Error::ZeroNotFound)?;
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    Ok(
-        mixed_encrypted_file[(zero_index + 1000) % mixed_encrypted_file.len()]
-            + mixed_encrypted_file[(zero_index + 2000) % mixed_encrypted_file.len()]
-            + mixed_encrypted_file[(zero_index + 3000) % mixed_encrypted_file.len()],
-    )
+fn inspect_rounds(
+    input: &[String],
+    decryption_key: i64,
+    mixing_rounds: usize,
+) -> Result<String, Day20Error> {
+    let encrypted_file = parse_encrypted_file(input)?;
+    let mut mixer = Mixer::new(&encrypted_file, decryption_key);
+
+    let mut output = format!(
+        "Initial arrangement:\n{}\n",
+        format_arrangement(&mixer.values())
+    );
+
+    for _ in 0..mixing_rounds {
+        mixer.mix_round();
+
+        let rounds = mixer.rounds();
+        output += &format!(
+            "\nAfter {} round{} of mixing:\n{}\n",
+            rounds,
+            if rounds == 1 { "" } else { "s" },
+            format_arrangement(&mixer.values())
+        );
+    }
+
+    Ok(output)
 }
 
-fn part1(input: &Vec<String>) -> Result<i64, Day20Error> {
+fn part1(input: &[String], algorithm: Algorithm) -> Result<i64, Day20Error> {
     let encrypted_file = parse_encrypted_file(input)?;
-    let mixed_encrypted_file = mix(encrypted_file, 1, 1);
+    let mixed_encrypted_file = mix_with(algorithm, &encrypted_file, 1, 1);
 
-    grove_coordinates(mixed_encrypted_file)
+    grove_coordinates(&mixed_encrypted_file, &GROVE_OFFSETS).ok_or(Day20Error::ZeroNotFound)
 }
 
 fn part2(
-    input: &Vec<String>,
+    input: &[String],
     decryption_key: i64,
     mixing_rounds: usize,
+    algorithm: Algorithm,
 ) -> Result<i64, Day20Error> {
     let encrypted_file = parse_encrypted_file(input)?;
-    let mixed_encrypted_file = mix(encrypted_file, decryption_key, mixing_rounds);
+    let mixed_encrypted_file = mix_with(algorithm, &encrypted_file, decryption_key, mixing_rounds);
 
-    grove_coordinates(mixed_encrypted_file)
+    grove_coordinates(&mixed_encrypted_file, &GROVE_OFFSETS).ok_or(Day20Error::ZeroNotFound)
 }
 
 #[cfg(test)]
@@ -147,54 +291,61 @@ mod tests {
 ";
 
     #[test]
-    fn test_mix_1_1() {
-        let encrypted_file = vec![1, 2, -3, 3, -2, 0, 4];
-        let result = mix(encrypted_file, 1, 1);
-
-        let expected_result = vec![-2, 1, 2, -3, 4, 0, 3];
-
-        assert_eq!(result, expected_result);
-    }
+    fn test_inspect_rounds() {
+        let input = to_lines(EXAMPLE);
 
-    #[test]
-    fn test_mix_811589153_10() {
-        let encrypted_file = vec![1, 2, -3, 3, -2, 0, 4];
-        let result = mix(encrypted_file, 811589153, 10);
+        assert_eq!(
+            inspect_rounds(&input, 811589153, 2).unwrap(),
+            "\
+Initial arrangement:
+811589153, 1623178306, -2434767459, 2434767459, -1623178306, 0, 3246356612
 
-        let expected_result = vec![
-            0,
-            -2434767459,
-            1623178306,
-            3246356612,
-            -1623178306,
-            2434767459,
-            811589153,
-        ];
+After 1 round of mixing:
+0, -2434767459, 3246356612, -1623178306, 2434767459, 1623178306, 811589153
 
-        assert_eq!(result, expected_result);
+After 2 rounds of mixing:
+0, 2434767459, 1623178306, 3246356612, -2434767459, -1623178306, 811589153
+"
+        );
     }
 
     #[test]
-    fn test_grove_coordinates() {
-        let mixed_encrypted_file = vec![1, 2, -3, 4, 0, 3, -2];
-        let result = grove_coordinates(mixed_encrypted_file).unwrap();
-
-        let expected_result = 3;
+    fn test_part1() {
+        let input = to_lines(EXAMPLE);
 
-        assert_eq!(result, expected_result);
+        assert_eq!(part1(&input, Algorithm::Naive).unwrap(), 3);
+        assert_eq!(part1(&input, Algorithm::Tree).unwrap(), 3);
     }
 
     #[test]
-    fn test_part1() {
+    fn test_part2() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part1(&input).unwrap(), 3);
+        assert_eq!(
+            part2(&input, 811589153, 10, Algorithm::Naive).unwrap(),
+            1623178306
+        );
+        assert_eq!(
+            part2(&input, 811589153, 10, Algorithm::Tree).unwrap(),
+            1623178306
+        );
     }
 
     #[test]
-    fn test_part2() {
-        let input = to_lines(EXAMPLE);
+    fn test_mix_matches_naive() {
+        for n in [2, 3, 7, 50, 200] {
+            let encrypted_file = generate_encrypted_file(n);
+
+            for (key, rounds) in [(1, 1), (-7, 2), (811589153, 3)] {
+                assert_eq!(
+                    mix(&encrypted_file, key, rounds),
+                    mix_naive(&encrypted_file, key, rounds)
+                );
+            }
 
-        assert_eq!(part2(&input, 811589153, 10).unwrap(), 1623178306);
+            // Shrinking every value to -2..=2 leaves many duplicates.
+            let duplicates: Vec<_> = encrypted_file.iter().map(|value| value % 3).collect();
+            assert_eq!(mix(&duplicates, 1, 2), mix_naive(&duplicates, 1, 2));
+        }
     }
 }
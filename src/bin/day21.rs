@@ -1,13 +1,14 @@
 use std::{
-    collections::{HashMap, HashSet},
-    io,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, io,
     num::ParseIntError,
     str::FromStr,
 };
 
-use aoc::read_lines;
+use aoc::{cli::Args, puzzles::part_label, read_lines, render::Dot};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day21Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
@@ -16,8 +17,14 @@ enum Day21Error {
     InvalidMonkeyLine,
     MonkeyNotFound,
     UnexpectedRootJob,
-    MoreThanOneHuman,
+    NonLinearEquation,
     SolveEquationError,
+    DivisionByZero,
+    NonIntegerAnswer(Rational),
+    NonIntegerOperand(Rational),
+    AmbiguousInversion(Operation),
+    Overflow,
+    InvalidOverride(String),
 }
 
 impl From<io::Error> for Day21Error {
@@ -37,18 +44,181 @@ const INPUT_PATH: &str = "inputs/day21.txt";
 fn main() -> Result<(), Day21Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    let args = Args::from_env();
+
+    println!("{}: {:?}", part_label(21, 1), part1(&input)?);
+
+    if args.flag("--show-equation") {
+        println!("Equation: {}", show_equation(&input)?);
+    }
+
+    println!("{}: {:?}", part_label(21, 2), part2(&input)?);
+
+    if let Some(path) = args.value("--dot") {
+        to_dot(&parse_monkeys(&input)?).save(path)?;
+    }
+
+    let overrides = args
+        .values("--set")
+        .into_iter()
+        .map(parse_override)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !overrides.is_empty() {
+        println!("Root with overrides: {}", what_if(&input, &overrides)?);
+    }
 
     Ok(())
 }
 
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// Yelled numbers are parsed as i64 but kept as i128 fractions, with every
+// operation checked so that overflow is an error rather than a wrong answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    fn new(numerator: i128, denominator: i128) -> Result<Self, Day21Error> {
+        if denominator == 0 {
+            return Err(Day21Error::DivisionByZero);
+        }
+
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+
+        Ok(Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        })
+    }
+
+    fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    fn checked_add(self, other: Self) -> Result<Self, Day21Error> {
+        let numerator = checked(self.numerator.checked_mul(other.denominator))?
+            .checked_add(checked(other.numerator.checked_mul(self.denominator))?);
+
+        Self::new(
+            checked(numerator)?,
+            checked(self.denominator.checked_mul(other.denominator))?,
+        )
+    }
+
+    fn checked_sub(self, other: Self) -> Result<Self, Day21Error> {
+        self.checked_add(Self {
+            numerator: checked(other.numerator.checked_neg())?,
+            ..other
+        })
+    }
+
+    fn checked_mul(self, other: Self) -> Result<Self, Day21Error> {
+        Self::new(
+            checked(self.numerator.checked_mul(other.numerator))?,
+            checked(self.denominator.checked_mul(other.denominator))?,
+        )
+    }
+
+    fn checked_div(self, other: Self) -> Result<Self, Day21Error> {
+        Self::new(
+            checked(self.numerator.checked_mul(other.denominator))?,
+            checked(self.denominator.checked_mul(other.numerator))?,
+        )
+    }
+
+    fn as_integer(self) -> Option<i128> {
+        (self.denominator == 1).then_some(self.numerator)
+    }
+
+    fn to_integer(self) -> Result<i128, Day21Error> {
+        self.as_integer().ok_or(Day21Error::NonIntegerAnswer(self))
+    }
+
+    fn checked_pow(self, exponent: i128) -> Result<Self, Day21Error> {
+        let base = if exponent < 0 {
+            Self::from(1).checked_div(self)?
+        } else {
+            self
+        };
+        let exponent = u32::try_from(exponent.unsigned_abs()).map_err(|_| Day21Error::Overflow)?;
+
+        Ok(Self {
+            numerator: checked(base.numerator.checked_pow(exponent))?,
+            denominator: checked(base.denominator.checked_pow(exponent))?,
+        })
+    }
+
+    // The exact k-th root, if there is one.
+    fn root(self, k: u32) -> Option<Self> {
+        fn integer_root(n: i128, k: u32) -> Option<i128> {
+            let target = n.unsigned_abs();
+
+            // Binary search for the largest root with root ^ k <= |n|.
+            let bound = 1u128.checked_shl(128 / k).unwrap_or(u128::MAX);
+            let (mut low, mut high) = (0, target.min(bound) + 1);
+            while high - low > 1 {
+                let middle = low + (high - low) / 2;
+                match middle.checked_pow(k) {
+                    Some(power) if power <= target => low = middle,
+                    _ => high = middle,
+                }
+            }
+
+            (low.pow(k) == target).then(|| low as i128 * n.signum())
+        }
+
+        if k.is_multiple_of(2) && self.numerator < 0 {
+            return None;
+        }
+
+        Some(Self {
+            numerator: integer_root(self.numerator, k)?,
+            denominator: integer_root(self.denominator, k)?,
+        })
+    }
+}
+
+fn checked(n: Option<i128>) -> Result<i128, Day21Error> {
+    n.ok_or(Day21Error::Overflow)
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self {
+            numerator: n.into(),
+            denominator: 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Operation {
     Addition,
     Subtraction,
     Multiplication,
     Division,
+    Modulo,
+    Exponentiation,
 }
 
 impl FromStr for Operation {
@@ -60,48 +230,130 @@ impl FromStr for Operation {
             "-" => Ok(Self::Subtraction),
             "*" => Ok(Self::Multiplication),
             "/" => Ok(Self::Division),
+            "%" => Ok(Self::Modulo),
+            "^" => Ok(Self::Exponentiation),
             _ => Err(Self::Err::InvalidOperation),
         }
     }
 }
 
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Operation::*;
+
+        match self {
+            Addition => write!(f, "+"),
+            Subtraction => write!(f, "-"),
+            Multiplication => write!(f, "*"),
+            Division => write!(f, "/"),
+            Modulo => write!(f, "%"),
+            Exponentiation => write!(f, "^"),
+        }
+    }
+}
+
 impl Operation {
-    fn compute(&self, left: &i64, right: &i64) -> i64 {
+    fn compute(&self, left: Rational, right: Rational) -> Result<Rational, Day21Error> {
         use Operation::*;
 
         match self {
-            Addition => left + right,
-            Subtraction => left - right,
-            Multiplication => left * right,
-            Division => left / right,
+            Addition => left.checked_add(right),
+            Subtraction => left.checked_sub(right),
+            Multiplication => left.checked_mul(right),
+            Division => left.checked_div(right),
+            Modulo => {
+                let left = integer_operand(left)?;
+                let right = integer_operand(right)?;
+
+                if right == 0 {
+                    return Err(Day21Error::DivisionByZero);
+                }
+
+                Rational::new(left.rem_euclid(right), 1)
+            }
+            Exponentiation => left.checked_pow(integer_operand(right)?),
         }
     }
 
-    fn solve_left(self, left: i64, target: i64) -> i64 {
+    fn solve_left(self, left: Rational, target: Rational) -> Result<Rational, Day21Error> {
         // left (self) x == target
         // <=> x == self.solve_left(left, target)
         use Operation::*;
 
         match self {
-            Addition => target - left,       // l + x == t ==> x = t - l
-            Subtraction => left - target,    // l - x == t ==> x = l - t
-            Multiplication => target / left, // l * x == t ==> x = t / l
-            Division => left / target,       // l / x == t ==> x = l / t
+            Addition => target.checked_sub(left), // l + x == t ==> x = t - l
+            Subtraction => left.checked_sub(target), // l - x == t ==> x = l - t
+            Multiplication => Self::divide(target, left), // l * x == t ==> x = t / l
+            Division => Self::divide(left, target), // l / x == t ==> x = l / t
+            Modulo => Err(Day21Error::AmbiguousInversion(self)),
+            Exponentiation => Self::logarithm(left, target), // l ^ x == t ==> x = log_l(t)
         }
     }
 
-    fn solve_right(self, right: i64, target: i64) -> i64 {
+    fn solve_right(self, right: Rational, target: Rational) -> Result<Rational, Day21Error> {
         // x (self) right == target
         // <=> x == self.solve_right(right, target)
         use Operation::*;
 
         match self {
-            Addition => target - right,       // x + r = t ==> x = t - r
-            Subtraction => target + right,    // x - r = t ==> x = t + r
-            Multiplication => target / right, // x * r = t ==> x = t / r
-            Division => target * right,       // x / r = t ==> x = t * r
+            Addition => target.checked_sub(right), // x + r = t ==> x = t - r
+            Subtraction => target.checked_add(right), // x - r = t ==> x = t + r
+            Multiplication => Self::divide(target, right), // x * r = t ==> x = t / r
+            Division => target.checked_mul(right), // x / r = t ==> x = t * r
+            Modulo => Err(Day21Error::AmbiguousInversion(self)),
+            Exponentiation => Self::nth_root(right, target), // x ^ r == t ==> x = t ^ (1 / r)
         }
     }
+
+    fn logarithm(base: Rational, target: Rational) -> Result<Rational, Day21Error> {
+        // 0, 1 and -1 reach the same powers for many different exponents.
+        if base.numerator.abs() == base.denominator || base.is_zero() {
+            return Err(Day21Error::AmbiguousInversion(Self::Exponentiation));
+        }
+
+        // |base ^ k| grows with k (or with -k), so only the exponents that
+        // don't overflow can possibly match.
+        for exponent in (0..).flat_map(|k: i128| [k, -k]) {
+            match base.checked_pow(exponent) {
+                Ok(power) if power == target => return Rational::new(exponent, 1),
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+
+        Err(Day21Error::SolveEquationError)
+    }
+
+    fn nth_root(exponent: Rational, target: Rational) -> Result<Rational, Day21Error> {
+        let exponent = integer_operand(exponent)?;
+
+        // x ^ 2 == 4 has two solutions, and x ^ 0 == 1 has infinitely many.
+        if exponent == 0 || (exponent % 2 == 0 && !target.is_zero()) {
+            return Err(Day21Error::AmbiguousInversion(Self::Exponentiation));
+        }
+
+        let target = if exponent < 0 {
+            Rational::from(1).checked_div(target)?
+        } else {
+            target
+        };
+        let k = u32::try_from(exponent.unsigned_abs()).map_err(|_| Day21Error::Overflow)?;
+
+        target.root(k).ok_or(Day21Error::SolveEquationError)
+    }
+
+    fn divide(dividend: Rational, divisor: Rational) -> Result<Rational, Day21Error> {
+        // Dividing by zero here means any (or no) x solves the equation.
+        if divisor.is_zero() {
+            return Err(Day21Error::SolveEquationError);
+        }
+
+        dividend.checked_div(divisor)
+    }
+}
+
+fn integer_operand(n: Rational) -> Result<i128, Day21Error> {
+    n.as_integer().ok_or(Day21Error::NonIntegerOperand(n))
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -111,16 +363,22 @@ enum MonkeyJob {
 }
 
 impl MonkeyJob {
-    fn try_compute(&self, yelled_numbers: &HashMap<String, i64>) -> Option<i64> {
+    fn try_compute(
+        &self,
+        yelled_numbers: &HashMap<String, Rational>,
+    ) -> Result<Option<Rational>, Day21Error> {
         use MonkeyJob::*;
 
         match self {
-            YellNumber(number) => Some(*number),
+            YellNumber(number) => Ok(Some(Rational::from(*number))),
             YellOperation(op, left, right) => {
-                let left = yelled_numbers.get(left)?;
-                let right = yelled_numbers.get(right)?;
+                let (Some(left), Some(right)) =
+                    (yelled_numbers.get(left), yelled_numbers.get(right))
+                else {
+                    return Ok(None);
+                };
 
-                Some(op.compute(left, right))
+                op.compute(*left, *right).map(Some)
             }
         }
     }
@@ -143,8 +401,8 @@ impl FromStr for MonkeyJob {
     }
 }
 
-fn parse_monkeys(input: &Vec<String>) -> Result<HashMap<String, MonkeyJob>, Day21Error> {
-    fn parse_line(line: &String) -> Result<(String, MonkeyJob), Day21Error> {
+fn parse_monkeys(input: &[String]) -> Result<BTreeMap<String, MonkeyJob>, Day21Error> {
+    fn parse_line(line: &str) -> Result<(String, MonkeyJob), Day21Error> {
         let v: Vec<_> = line.split(": ").collect();
         match v.as_slice() {
             [name, job] => Ok((name.to_string(), job.parse()?)),
@@ -152,13 +410,40 @@ fn parse_monkeys(input: &Vec<String>) -> Result<HashMap<String, MonkeyJob>, Day2
         }
     }
 
-    input.iter().map(parse_line).collect()
+    input.iter().map(String::as_str).map(parse_line).collect()
+}
+
+fn to_dot(monkeys: &BTreeMap<String, MonkeyJob>) -> Dot {
+    let mut dot = Dot::new();
+
+    for (name, job) in monkeys {
+        let fill = match name.as_str() {
+            "root" => Some("gold"),
+            "humn" => Some("lightblue"),
+            _ => None,
+        };
+
+        match job {
+            MonkeyJob::YellNumber(number) => {
+                dot.node(name, &format!("{name}: {number}"), fill);
+            }
+            MonkeyJob::YellOperation(op, left, right) => {
+                dot.node(name, &format!("{name}: {left} {op} {right}"), fill);
+                dot.edge(name, left);
+                dot.edge(name, right);
+            }
+        }
+    }
+
+    dot
 }
 
-fn compute_root_yelled_number(monkeys: HashMap<String, MonkeyJob>) -> Result<i64, Day21Error> {
-    let mut yelled_numbers: HashMap<String, i64> = HashMap::new();
+fn yelled_numbers(
+    monkeys: &BTreeMap<String, MonkeyJob>,
+) -> Result<HashMap<String, Rational>, Day21Error> {
+    let mut yelled_numbers: HashMap<String, Rational> = HashMap::new();
 
-    let mut waiting_monkeys: HashSet<_> = monkeys.keys().collect();
+    let mut waiting_monkeys: BTreeSet<_> = monkeys.keys().collect();
 
     while !waiting_monkeys.is_empty() {
         let mut next_waiting_monkeys = waiting_monkeys.clone();
@@ -166,7 +451,7 @@ fn compute_root_yelled_number(monkeys: HashMap<String, MonkeyJob>) -> Result<i64
         for &monkey_name in &waiting_monkeys {
             let monkey_job = monkeys.get(monkey_name).ok_or(Day21Error::MonkeyNotFound)?;
 
-            if let Some(yelled_number) = monkey_job.try_compute(&yelled_numbers) {
+            if let Some(yelled_number) = monkey_job.try_compute(&yelled_numbers)? {
                 next_waiting_monkeys.remove(monkey_name);
                 yelled_numbers.insert(monkey_name.clone(), yelled_number);
             }
@@ -175,120 +460,320 @@ fn compute_root_yelled_number(monkeys: HashMap<String, MonkeyJob>) -> Result<i64
         waiting_monkeys = next_waiting_monkeys;
     }
 
-    yelled_numbers
+    Ok(yelled_numbers)
+}
+
+fn compute_root_yelled_number(monkeys: BTreeMap<String, MonkeyJob>) -> Result<i128, Day21Error> {
+    yelled_numbers(&monkeys)?
         .get("root")
-        .copied()
-        .ok_or(Day21Error::MonkeyNotFound)
+        .ok_or(Day21Error::MonkeyNotFound)?
+        .to_integer()
+}
+
+fn parse_override(s: &str) -> Result<(String, i64), Day21Error> {
+    let (name, number) = s
+        .split_once('=')
+        .ok_or_else(|| Day21Error::InvalidOverride(s.to_owned()))?;
+
+    Ok((name.to_owned(), number.parse()?))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct WhatIf {
+    root: Rational,
+    // Both sides of root's equality, unless root itself was overridden.
+    equality: Option<(Rational, Rational)>,
 }
 
-fn part1(input: &Vec<String>) -> Result<i64, Day21Error> {
+impl fmt::Display for WhatIf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.root)?;
+
+        match self.equality {
+            Some((left, right)) if left == right => {
+                write!(f, " (equality holds: {left} == {right})")
+            }
+            Some((left, right)) => write!(f, " (equality fails: {left} != {right})"),
+            None => Ok(()),
+        }
+    }
+}
+
+// Evaluates every monkey after replacing the jobs of some with fixed numbers.
+fn what_if(input: &[String], overrides: &[(String, i64)]) -> Result<WhatIf, Day21Error> {
+    let mut monkeys = parse_monkeys(input)?;
+
+    for (name, number) in overrides {
+        let job = monkeys.get_mut(name).ok_or(Day21Error::MonkeyNotFound)?;
+        *job = MonkeyJob::YellNumber(*number);
+    }
+
+    let yelled_numbers = yelled_numbers(&monkeys)?;
+    let yelled = |name: &str| {
+        yelled_numbers
+            .get(name)
+            .copied()
+            .ok_or(Day21Error::MonkeyNotFound)
+    };
+
+    let equality = match monkeys.get("root") {
+        Some(MonkeyJob::YellOperation(_, left, right)) => Some((yelled(left)?, yelled(right)?)),
+        _ => None,
+    };
+
+    Ok(WhatIf {
+        root: yelled("root")?,
+        equality,
+    })
+}
+
+fn part1(input: &[String]) -> Result<i128, Day21Error> {
     let monkeys = parse_monkeys(input)?;
 
     compute_root_yelled_number(monkeys)
 }
 
-#[derive(Debug)]
+type ExpressionId = usize;
+
+#[derive(Debug, Clone, Copy)]
 enum Expression {
     Human,
-    Number(i64),
-    Operation(Operation, Box<Expression>, Box<Expression>),
+    Number(Rational),
+    Operation(Operation, ExpressionId, ExpressionId),
+}
+
+// Both sides of root's equation, with every expression stored in one arena
+// and referring to its operands by index.
+#[derive(Debug)]
+struct Equation {
+    expressions: Vec<Expression>,
+    left: ExpressionId,
+    right: ExpressionId,
 }
 
-impl Expression {
-    fn reduce(self) -> Self {
+impl Equation {
+    fn from_monkeys(monkeys: BTreeMap<String, MonkeyJob>) -> Result<Self, Day21Error> {
+        let root = monkeys.get("root").ok_or(Day21Error::MonkeyNotFound)?;
+
+        if let MonkeyJob::YellOperation(_, left, right) = root {
+            let mut expressions = vec![];
+            let left = Self::push_monkey(&mut expressions, &monkeys, left)?;
+            let right = Self::push_monkey(&mut expressions, &monkeys, right)?;
+
+            Ok(Self {
+                expressions,
+                left,
+                right,
+            })
+        } else {
+            Err(Day21Error::UnexpectedRootJob)
+        }
+    }
+
+    fn push_monkey(
+        expressions: &mut Vec<Expression>,
+        monkeys: &BTreeMap<String, MonkeyJob>,
+        monkey_name: &str,
+    ) -> Result<ExpressionId, Day21Error> {
         use Expression::*;
+        use MonkeyJob::*;
 
-        match self {
-            Operation(op, mut left, mut right) => {
-                *left = (*left).reduce();
-                *right = (*right).reduce();
-
-                if let Number(left) = *left {
-                    if let Number(right) = *right {
-                        return Number(op.compute(&left, &right));
-                    }
-                }
+        let expression = if monkey_name == "humn" {
+            Human
+        } else {
+            match monkeys.get(monkey_name) {
+                Some(YellNumber(number)) => Number(Rational::from(*number)),
+                Some(YellOperation(op, left, right)) => Operation(
+                    *op,
+                    Self::push_monkey(expressions, monkeys, left)?,
+                    Self::push_monkey(expressions, monkeys, right)?,
+                ),
+                None => return Err(Day21Error::MonkeyNotFound),
+            }
+        };
+
+        expressions.push(expression);
+
+        Ok(expressions.len() - 1)
+    }
+
+    fn reduce(&mut self) -> Result<(), Day21Error> {
+        self.reduce_expression(self.left)?;
+        self.reduce_expression(self.right)
+    }
+
+    // Folds constant subexpressions in place.
+    fn reduce_expression(&mut self, id: ExpressionId) -> Result<(), Day21Error> {
+        use Expression::*;
 
-                Operation(op, left, right)
+        if let Operation(op, left, right) = self.expressions[id] {
+            self.reduce_expression(left)?;
+            self.reduce_expression(right)?;
+
+            if let (Number(left), Number(right)) = (self.expressions[left], self.expressions[right])
+            {
+                self.expressions[id] = Number(op.compute(left, right)?);
             }
-            other => other,
         }
+
+        Ok(())
     }
-}
 
-fn from_monkey_name(
-    monkeys: &HashMap<String, MonkeyJob>,
-    monkey_name: &String,
-) -> Result<Expression, Day21Error> {
-    use Expression::*;
-    use MonkeyJob::*;
+    fn linear(&self, id: ExpressionId) -> Result<Linear, Day21Error> {
+        use Expression::*;
 
-    if monkey_name == "humn" {
-        Ok(Human)
-    } else {
-        match monkeys.get(monkey_name) {
-            Some(YellNumber(number)) => Ok(Number(*number)),
-            Some(YellOperation(op, left, right)) => Ok(Operation(
-                *op,
-                Box::new(from_monkey_name(monkeys, left)?),
-                Box::new(from_monkey_name(monkeys, right)?),
-            )),
-            None => Err(Day21Error::MonkeyNotFound),
+        match self.expressions[id] {
+            Human => Ok(Linear {
+                slope: Rational::from(1),
+                constant: Rational::from(0),
+            }),
+            Number(n) => Ok(Linear::constant(n)),
+            Operation(op, left, right) => self.linear(left)?.combine(op, self.linear(right)?),
         }
     }
-}
 
-fn from_monkeys(
-    monkeys: HashMap<String, MonkeyJob>,
-) -> Result<(Expression, Expression), Day21Error> {
-    let root = monkeys.get("root").ok_or(Day21Error::MonkeyNotFound)?;
+    fn solve_for(&self, id: ExpressionId, target: Rational) -> Result<Rational, Day21Error> {
+        use Expression::*;
 
-    if let MonkeyJob::YellOperation(_, left, right) = root {
-        Ok((
-            from_monkey_name(&monkeys, left)?,
-            from_monkey_name(&monkeys, right)?,
-        ))
-    } else {
-        Err(Day21Error::UnexpectedRootJob)
+        match self.expressions[id] {
+            Human => Ok(target),
+            Number(_) => Err(Day21Error::SolveEquationError),
+            Operation(op, left, right) => match (self.expressions[left], self.expressions[right]) {
+                (Number(n), _) => self.solve_for(right, op.solve_left(n, target)?),
+                (_, Number(n)) => self.solve_for(left, op.solve_right(n, target)?),
+                _ => solve_linear(self.linear(id)?, Linear::constant(target)),
+            },
+        }
+    }
+
+    fn solve(&mut self) -> Result<i128, Day21Error> {
+        use Expression::*;
+
+        self.reduce()?;
+
+        let solution = match (self.expressions[self.left], self.expressions[self.right]) {
+            (Number(target), _) => self.solve_for(self.right, target)?,
+            (_, Number(target)) => self.solve_for(self.left, target)?,
+            _ => solve_linear(self.linear(self.left)?, self.linear(self.right)?)?,
+        };
+
+        solution.to_integer()
+    }
+
+    fn display(&self, id: ExpressionId) -> ExpressionDisplay<'_> {
+        ExpressionDisplay {
+            expressions: &self.expressions,
+            id,
+        }
+    }
+}
+
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} = {}",
+            self.display(self.left),
+            self.display(self.right)
+        )
     }
 }
 
-fn solve_equation((left, right): (Expression, Expression)) -> Result<i64, Day21Error> {
-    fn solve_aux(expression: Expression, target: i64) -> Result<i64, Day21Error> {
+struct ExpressionDisplay<'a> {
+    expressions: &'a [Expression],
+    id: ExpressionId,
+}
+
+impl fmt::Display for ExpressionDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Expression::*;
 
-        match expression {
-            Human => Ok(target),
-            Number(_) => Err(Day21Error::SolveEquationError),
-            Operation(op, left, right) => {
-                if let Number(n) = *left {
-                    solve_aux(*right, op.solve_left(n, target))
-                } else if let Number(n) = *right {
-                    solve_aux(*left, op.solve_right(n, target))
-                } else {
-                    Err(Day21Error::MoreThanOneHuman)
-                }
+        let operand = |id| Self {
+            expressions: self.expressions,
+            id,
+        };
+
+        match self.expressions[self.id] {
+            Human => write!(f, "x"),
+            Number(n) => write!(f, "{n}"),
+            Operation(op, left, right) => write!(f, "({} {op} {})", operand(left), operand(right)),
+        }
+    }
+}
+
+// slope * humn + constant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Linear {
+    slope: Rational,
+    constant: Rational,
+}
+
+impl Linear {
+    fn constant(constant: Rational) -> Self {
+        Self {
+            slope: Rational::from(0),
+            constant,
+        }
+    }
+
+    fn combine(self, op: Operation, other: Self) -> Result<Self, Day21Error> {
+        use Operation::*;
+
+        match op {
+            Addition => Ok(Self {
+                slope: self.slope.checked_add(other.slope)?,
+                constant: self.constant.checked_add(other.constant)?,
+            }),
+            Subtraction => Ok(Self {
+                slope: self.slope.checked_sub(other.slope)?,
+                constant: self.constant.checked_sub(other.constant)?,
+            }),
+            Multiplication if self.slope.is_zero() => other.scale(self.constant),
+            Multiplication if other.slope.is_zero() => self.scale(other.constant),
+            Division if other.slope.is_zero() => {
+                self.scale(Rational::from(1).checked_div(other.constant)?)
+            }
+            _ if self.slope.is_zero() && other.slope.is_zero() => {
+                Ok(Self::constant(op.compute(self.constant, other.constant)?))
             }
+            _ => Err(Day21Error::NonLinearEquation),
         }
     }
 
-    let left = left.reduce();
-    let right = right.reduce();
+    fn scale(self, factor: Rational) -> Result<Self, Day21Error> {
+        Ok(Self {
+            slope: self.slope.checked_mul(factor)?,
+            constant: self.constant.checked_mul(factor)?,
+        })
+    }
+}
 
-    if let Expression::Number(target) = left {
-        solve_aux(right, target)
-    } else if let Expression::Number(target) = right {
-        solve_aux(left, target)
-    } else {
-        Err(Day21Error::MoreThanOneHuman)
+fn solve_linear(left: Linear, right: Linear) -> Result<Rational, Day21Error> {
+    // (a - c) * humn + (b - d) == 0
+    let difference = left.combine(Operation::Subtraction, right)?;
+
+    if difference.slope.is_zero() {
+        return Err(Day21Error::SolveEquationError);
     }
+
+    Rational::from(0)
+        .checked_sub(difference.constant)?
+        .checked_div(difference.slope)
+}
+
+fn show_equation(input: &[String]) -> Result<String, Day21Error> {
+    let monkeys = parse_monkeys(input)?;
+    let mut equation = Equation::from_monkeys(monkeys)?;
+
+    equation.reduce()?;
+
+    Ok(equation.to_string())
 }
 
-fn part2(input: &Vec<String>) -> Result<i64, Day21Error> {
+fn part2(input: &[String]) -> Result<i128, Day21Error> {
     let monkeys = parse_monkeys(input)?;
-    let equation = from_monkeys(monkeys)?;
 
-    solve_equation(equation)
+    Equation::from_monkeys(monkeys)?.solve()
 }
 
 #[cfg(test)]
@@ -322,7 +807,7 @@ hmdt: 32
         let input = to_lines(EXAMPLE);
 
         let result = parse_monkeys(&input).unwrap();
-        let mut expected_result = HashMap::new();
+        let mut expected_result = BTreeMap::new();
         expected_result.insert(
             String::from("root"),
             YellOperation(Addition, String::from("pppw"), String::from("sjmn")),
@@ -363,6 +848,132 @@ hmdt: 32
         assert_eq!(result, expected_result);
     }
 
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(2, 4).unwrap();
+
+        assert_eq!(half, Rational::new(-1, -2).unwrap());
+        assert_eq!(half.checked_add(half).unwrap(), Rational::from(1));
+        assert_eq!(
+            Rational::from(1)
+                .checked_sub(half.checked_mul(Rational::from(3)).unwrap())
+                .unwrap(),
+            Rational::new(-1, 2).unwrap()
+        );
+        assert!(matches!(
+            Rational::from(1).checked_div(Rational::from(0)),
+            Err(Day21Error::DivisionByZero)
+        ));
+        assert!(half.to_integer().is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        // 2^62 * 4 overflows i64 but not i128, while 2^62 ^ 3 overflows both.
+        let input = to_lines("root: a * b\na: 4611686018427387904\nb: 4\n");
+        assert_eq!(part1(&input).unwrap(), 1 << 64);
+
+        let input = to_lines("root: a ^ b\na: 4611686018427387904\nb: 3\n");
+        assert!(matches!(part1(&input), Err(Day21Error::Overflow)));
+    }
+
+    #[test]
+    fn test_part2_exact_division() {
+        // (humn + 1) / 2 * 4 == 6 needs (humn + 1) / 2 == 3 / 2, which
+        // integer division would truncate to 1 and give humn == 1.
+        let input = to_lines(
+            "\
+root: lhs + six
+lhs: half * four
+half: succ / two
+succ: humn + one
+one: 1
+two: 2
+four: 4
+six: 6
+humn: 0
+",
+        );
+
+        assert_eq!(part2(&input).unwrap(), 2);
+
+        let input =
+            to_lines("root: double + three\ndouble: humn * two\ntwo: 2\nthree: 3\nhumn: 0\n");
+
+        assert!(matches!(
+            part2(&input),
+            Err(Day21Error::NonIntegerAnswer(_))
+        ));
+    }
+
+    #[test]
+    fn test_part2_humn_on_both_sides() {
+        // 2 * humn + 3 == humn + 10, with humn also used twice in one subtree.
+        let input = to_lines(
+            "\
+root: lhs + rhs
+lhs: twice + three
+twice: humn + humn
+rhs: humn + ten
+three: 3
+ten: 10
+humn: 0
+",
+        );
+
+        assert_eq!(part2(&input).unwrap(), 7);
+
+        let input = to_lines("root: square + four\nsquare: humn * humn\nfour: 4\nhumn: 0\n");
+
+        assert!(matches!(part2(&input), Err(Day21Error::NonLinearEquation)));
+    }
+
+    #[test]
+    fn test_what_if() {
+        let input = to_lines(EXAMPLE);
+        let overrides = |s: &str| {
+            s.split_whitespace()
+                .map(parse_override)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        let unchanged = what_if(&input, &[]).unwrap();
+        assert_eq!(unchanged.to_string(), "152 (equality fails: 2 != 150)");
+
+        let solved = what_if(&input, &overrides("humn=301")).unwrap();
+        assert_eq!(solved.to_string(), "300 (equality holds: 150 == 150)");
+
+        let fractional = what_if(&input, &overrides("humn=6 dbpl=7")).unwrap();
+        assert_eq!(fractional.to_string(), "425/2 (equality fails: 5/2 != 210)");
+
+        let root = what_if(&input, &overrides("root=7")).unwrap();
+        assert_eq!(
+            root,
+            WhatIf {
+                root: Rational::from(7),
+                equality: None
+            }
+        );
+
+        assert!(matches!(
+            what_if(&input, &overrides("nope=1")),
+            Err(Day21Error::MonkeyNotFound)
+        ));
+        assert!(matches!(
+            what_if(&input, &overrides("lfqf=0")),
+            Err(Day21Error::DivisionByZero)
+        ));
+        assert!(matches!(
+            parse_override("humn"),
+            Err(Day21Error::InvalidOverride(_))
+        ));
+        assert!(matches!(
+            parse_override("humn=x"),
+            Err(Day21Error::ParseIntError(_))
+        ));
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
@@ -370,6 +981,59 @@ hmdt: 32
         assert_eq!(part1(&input).unwrap(), 152);
     }
 
+    #[test]
+    fn test_modulo_and_exponentiation() {
+        let input = to_lines("root: a % b\na: b ^ c\nb: 3\nc: 4\n");
+        assert_eq!(part1(&input).unwrap(), 0);
+
+        let input = to_lines("root: a + b\na: humn ^ c\nb: 125\nc: 3\nhumn: 0\n");
+        assert_eq!(part2(&input).unwrap(), 5);
+
+        let input = to_lines("root: a + b\na: humn ^ c\nb: 7\nc: 1\nhumn: 0\n");
+        assert_eq!(part2(&input).unwrap(), 7);
+
+        let input = to_lines("root: a + b\na: c ^ humn\nb: 4\nc: 2\nhumn: 0\n");
+        assert_eq!(part2(&input).unwrap(), 2);
+
+        let input = to_lines("root: a + b\na: c ^ humn\nb: d / e\nc: 2\nd: 1\ne: 8\nhumn: 0\n");
+        assert_eq!(part2(&input).unwrap(), -3);
+
+        let input = to_lines("root: a + b\na: humn ^ c\nb: 4\nc: 2\nhumn: 0\n");
+        assert!(matches!(
+            part2(&input),
+            Err(Day21Error::AmbiguousInversion(Operation::Exponentiation))
+        ));
+
+        let input = to_lines("root: a + b\na: humn % c\nb: 1\nc: 2\nhumn: 0\n");
+        assert!(matches!(
+            part2(&input),
+            Err(Day21Error::AmbiguousInversion(Operation::Modulo))
+        ));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let input = to_lines(EXAMPLE);
+        let dot = to_dot(&parse_monkeys(&input).unwrap()).to_text();
+
+        assert!(
+            dot.contains(r#""root" [label="root: pppw + sjmn", style=filled, fillcolor="gold"];"#)
+        );
+        assert!(dot.contains(r#""humn" [label="humn: 5", style=filled, fillcolor="lightblue"];"#));
+        assert!(dot.contains(r#""ptdq" -> "humn";"#));
+        assert_eq!(dot.matches("->").count(), 14);
+    }
+
+    #[test]
+    fn test_show_equation() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(
+            show_equation(&input).unwrap(),
+            "((4 + (2 * (x - 3))) / 4) = 150"
+        );
+    }
+
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
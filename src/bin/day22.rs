@@ -0,0 +1,308 @@
+use std::{io, num::ParseIntError};
+
+use aoc::{
+    cli::Args,
+    cube::{CubeError, CubeNet, Direction},
+    puzzles::part_label,
+    read_lines,
+};
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum Day22Error {
+    IoError(io::Error),
+    ParseIntError(ParseIntError),
+    InvalidTile(char),
+    InvalidInstruction(char),
+    MissingPath,
+    NoOpenTile,
+    CubeError(CubeError),
+}
+
+impl From<io::Error> for Day22Error {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+impl From<ParseIntError> for Day22Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
+impl From<CubeError> for Day22Error {
+    fn from(e: CubeError) -> Self {
+        Self::CubeError(e)
+    }
+}
+
+const INPUT_PATH: &str = "inputs/day22.txt";
+
+fn main() -> Result<(), Day22Error> {
+    let args = Args::from_env();
+    let input = read_lines(INPUT_PATH)?;
+
+    println!("{}: {:?}", part_label(22, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(22, 2), part2(&input)?);
+
+    if args.flag("--faces") {
+        let (board, _) = parse_notes(&input)?;
+        print!("{}", board.cube()?);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Void,
+    Open,
+    Wall,
+}
+
+impl TryFrom<char> for Tile {
+    type Error = Day22Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            ' ' => Ok(Self::Void),
+            '.' => Ok(Self::Open),
+            '#' => Ok(Self::Wall),
+            _ => Err(Self::Error::InvalidTile(value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Forward(usize),
+    TurnLeft,
+    TurnRight,
+}
+
+type Position = (usize, usize, Direction);
+
+struct Board {
+    tiles: Vec<Vec<Tile>>,
+}
+
+impl Board {
+    fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    fn width(&self) -> usize {
+        self.tiles.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    fn get(&self, row: usize, col: usize) -> Tile {
+        self.tiles
+            .get(row)
+            .and_then(|tiles| tiles.get(col))
+            .copied()
+            .unwrap_or(Tile::Void)
+    }
+
+    fn start(&self) -> Result<Position, Day22Error> {
+        let col = self.tiles[0]
+            .iter()
+            .position(|&tile| tile == Tile::Open)
+            .ok_or(Day22Error::NoOpenTile)?;
+
+        Ok((0, col, Direction::Right))
+    }
+
+    // Walks back the other way to the far side of the board.
+    fn wrap_flat(&self, (mut row, mut col, facing): Position) -> Position {
+        let (dr, dc) = facing.opposite().delta();
+
+        loop {
+            let (next_row, next_col) = (row.wrapping_add_signed(dr), col.wrapping_add_signed(dc));
+            if self.get(next_row, next_col) == Tile::Void {
+                return (row, col, facing);
+            }
+            (row, col) = (next_row, next_col);
+        }
+    }
+
+    fn cube(&self) -> Result<CubeNet, CubeError> {
+        CubeNet::fold(self.height(), self.width(), |row, col| {
+            self.get(row, col) != Tile::Void
+        })
+    }
+
+    // Follows the path, calling `wrap` to find where stepping off the board leads.
+    fn follow(
+        &self,
+        path: &[Instruction],
+        wrap: impl Fn(Position) -> Position,
+    ) -> Result<Position, Day22Error> {
+        let mut position = self.start()?;
+
+        for instruction in path {
+            let (row, col, facing) = position;
+
+            match instruction {
+                Instruction::TurnLeft => position = (row, col, facing.turn_left()),
+                Instruction::TurnRight => position = (row, col, facing.turn_right()),
+                Instruction::Forward(steps) => {
+                    for _ in 0..*steps {
+                        let (row, col, facing) = position;
+                        let (dr, dc) = facing.delta();
+
+                        let mut next = (
+                            row.wrapping_add_signed(dr),
+                            col.wrapping_add_signed(dc),
+                            facing,
+                        );
+                        if self.get(next.0, next.1) == Tile::Void {
+                            next = wrap(position);
+                        }
+
+                        if self.get(next.0, next.1) == Tile::Wall {
+                            break;
+                        }
+                        position = next;
+                    }
+                }
+            }
+        }
+
+        Ok(position)
+    }
+}
+
+fn parse_path(line: &str) -> Result<Vec<Instruction>, Day22Error> {
+    let mut path = vec![];
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+
+        if digits > 0 {
+            path.push(Instruction::Forward(rest[..digits].parse()?));
+            rest = &rest[digits..];
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        path.push(match c {
+            'L' => Instruction::TurnLeft,
+            'R' => Instruction::TurnRight,
+            _ => return Err(Day22Error::InvalidInstruction(c)),
+        });
+        rest = &rest[c.len_utf8()..];
+    }
+
+    Ok(path)
+}
+
+fn parse_notes(input: &[String]) -> Result<(Board, Vec<Instruction>), Day22Error> {
+    let (map, path) = match input.iter().position(String::is_empty) {
+        Some(blank) => (&input[..blank], &input[blank + 1..]),
+        None => return Err(Day22Error::MissingPath),
+    };
+
+    let tiles = map
+        .iter()
+        .map(|line| line.chars().map(Tile::try_from).collect())
+        .collect::<Result<_, _>>()?;
+    let path = parse_path(path.first().ok_or(Day22Error::MissingPath)?)?;
+
+    Ok((Board { tiles }, path))
+}
+
+fn password((row, col, facing): Position) -> usize {
+    1000 * (row + 1) + 4 * (col + 1) + facing.value()
+}
+
+fn part1(input: &[String]) -> Result<usize, Day22Error> {
+    let (board, path) = parse_notes(input)?;
+
+    Ok(password(
+        board.follow(&path, |position| board.wrap_flat(position))?,
+    ))
+}
+
+fn part2(input: &[String]) -> Result<usize, Day22Error> {
+    let (board, path) = parse_notes(input)?;
+    let cube = board.cube()?;
+
+    Ok(password(board.follow(&path, |(row, col, facing)| {
+        cube.wrap(row, col, facing)
+    })?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc::to_lines;
+
+    const EXAMPLE: &str = "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+";
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("10R5L").unwrap(),
+            vec![
+                Instruction::Forward(10),
+                Instruction::TurnRight,
+                Instruction::Forward(5),
+                Instruction::TurnLeft
+            ]
+        );
+        assert!(matches!(
+            parse_path("10X"),
+            Err(Day22Error::InvalidInstruction('X'))
+        ));
+    }
+
+    #[test]
+    fn test_faces() {
+        let (board, _) = parse_notes(&to_lines(EXAMPLE)).unwrap();
+        let cube = board.cube().unwrap();
+
+        assert_eq!(cube.size(), 4);
+        assert!(cube.to_string().starts_with(
+            "\
+Face 1 (rows 1-4, columns 9-12):
+  right -> face 6 facing left
+  down -> face 4 facing down
+  left -> face 3 facing down
+  up -> face 2 facing down
+"
+        ));
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part1(&input).unwrap(), 6032);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part2(&input).unwrap(), 5031);
+    }
+}
@@ -0,0 +1,533 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    num::ParseIntError,
+    str::FromStr,
+};
+
+use aoc::{
+    animation::Animation,
+    cli::Args,
+    puzzles::part_label,
+    read_lines,
+    render::{InvalidPacking, Packing},
+};
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum Day23Error {
+    IoError(io::Error),
+    ParseIntError(ParseIntError),
+    InvalidPacking(InvalidPacking),
+    InvalidTile(char),
+    InvalidCompass(String),
+    InvalidRule(String),
+    NoRules,
+    NoSteadyState(usize),
+}
+
+impl From<io::Error> for Day23Error {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+impl From<ParseIntError> for Day23Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
+impl From<InvalidPacking> for Day23Error {
+    fn from(e: InvalidPacking) -> Self {
+        Self::InvalidPacking(e)
+    }
+}
+
+const INPUT_PATH: &str = "inputs/day23.txt";
+
+const ROUNDS: usize = 10;
+const STOCK_ORDER: &str = "NSWE";
+// Custom rules can keep elves moving forever, so part 2 gives up eventually.
+const ROUND_LIMIT: usize = 100_000;
+
+fn main() -> Result<(), Day23Error> {
+    let input = read_lines(INPUT_PATH)?;
+
+    let args = Args::from_env();
+
+    let rounds = args.parse("--rounds")?.unwrap_or(ROUNDS);
+    let rules = parse_rules(
+        &args.values("--rule"),
+        args.value("--order").unwrap_or(STOCK_ORDER),
+    )?;
+
+    println!(
+        "{}: {:?}",
+        part_label(23, 1),
+        part1(&input, &rules, rounds)?
+    );
+    // Custom rules may never settle, which shouldn't stop the rendering below.
+    match part2(&input, &rules) {
+        Ok(rounds) => println!("{}: {:?}", part_label(23, 2), rounds),
+        Err(e) => eprintln!("{}: {:?}", part_label(23, 2), e),
+    }
+
+    let packing = args.parse("--packing")?;
+
+    if args.flag("--render") {
+        let mut grove = Grove::new(parse_elves(&input)?, rules.clone());
+        for _ in 0..rounds {
+            grove.round();
+        }
+
+        print!("{}", render(&grove.elves, grove.bounds(), packing));
+    }
+
+    if let Some(path) = args.value("--animate") {
+        animate(&input, &rules, rounds, packing)?.save(path)?;
+    }
+
+    Ok(())
+}
+
+// The eight neighbouring positions, clockwise from north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compass {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+const COMPASS: [Compass; 8] = [
+    Compass::N,
+    Compass::NE,
+    Compass::E,
+    Compass::SE,
+    Compass::S,
+    Compass::SW,
+    Compass::W,
+    Compass::NW,
+];
+
+impl Compass {
+    fn rotate(self, eighths: usize) -> Self {
+        COMPASS[(self as usize + eighths) % 8]
+    }
+
+    // Row and column steps.
+    fn delta(self) -> (i64, i64) {
+        match self {
+            Self::N => (-1, 0),
+            Self::NE => (-1, 1),
+            Self::E => (0, 1),
+            Self::SE => (1, 1),
+            Self::S => (1, 0),
+            Self::SW => (1, -1),
+            Self::W => (0, -1),
+            Self::NW => (-1, -1),
+        }
+    }
+}
+
+impl FromStr for Compass {
+    type Err = Day23Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "N" => Ok(Self::N),
+            "NE" => Ok(Self::NE),
+            "E" => Ok(Self::E),
+            "SE" => Ok(Self::SE),
+            "S" => Ok(Self::S),
+            "SW" => Ok(Self::SW),
+            "W" => Ok(Self::W),
+            "NW" => Ok(Self::NW),
+            _ => Err(Self::Err::InvalidCompass(s.to_owned())),
+        }
+    }
+}
+
+// An elf steps towards `step` if none of the `checks` positions hold an elf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    step: Compass,
+    checks: Vec<Compass>,
+}
+
+impl Rule {
+    // Checks the step and the positions either side of it, like the puzzle's rules.
+    fn stock(step: Compass) -> Self {
+        Self {
+            step,
+            checks: vec![step.rotate(7), step, step.rotate(1)],
+        }
+    }
+}
+
+// Either `N` for a stock rule, or `NE:N,NE,E` to choose the checks.
+impl FromStr for Rule {
+    type Err = Day23Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None => Ok(Self::stock(s.parse()?)),
+            Some((step, checks)) => {
+                let rule = Self {
+                    step: step.parse()?,
+                    checks: checks
+                        .split(',')
+                        .filter(|check| !check.is_empty())
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?,
+                };
+
+                // Otherwise an elf could step onto another one that stays put.
+                if !rule.checks.contains(&rule.step) {
+                    return Err(Self::Err::InvalidRule(s.to_owned()));
+                }
+
+                Ok(rule)
+            }
+        }
+    }
+}
+
+fn parse_rules(rules: &[&str], order: &str) -> Result<Vec<Rule>, Day23Error> {
+    let rules: Vec<Rule> = if rules.is_empty() {
+        order
+            .chars()
+            .map(|c| c.to_string().parse())
+            .collect::<Result<_, _>>()?
+    } else {
+        rules
+            .iter()
+            .map(|rule| rule.parse())
+            .collect::<Result<_, _>>()?
+    };
+
+    if rules.is_empty() {
+        return Err(Day23Error::NoRules);
+    }
+
+    Ok(rules)
+}
+
+type Position = (i64, i64);
+
+fn parse_elves(input: &[String]) -> Result<HashSet<Position>, Day23Error> {
+    let mut elves = HashSet::new();
+
+    for (row, line) in input.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            match c {
+                '#' => {
+                    elves.insert((row as i64, col as i64));
+                }
+                '.' => {}
+                _ => return Err(Day23Error::InvalidTile(c)),
+            }
+        }
+    }
+
+    Ok(elves)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bounds {
+    top: i64,
+    left: i64,
+    bottom: i64,
+    right: i64,
+}
+
+impl Bounds {
+    fn of<'a>(positions: impl IntoIterator<Item = &'a Position>) -> Self {
+        positions.into_iter().fold(
+            Self {
+                top: i64::MAX,
+                left: i64::MAX,
+                bottom: i64::MIN,
+                right: i64::MIN,
+            },
+            |bounds, &(row, col)| Self {
+                top: bounds.top.min(row),
+                left: bounds.left.min(col),
+                bottom: bounds.bottom.max(row),
+                right: bounds.right.max(col),
+            },
+        )
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            top: self.top.min(other.top),
+            left: self.left.min(other.left),
+            bottom: self.bottom.max(other.bottom),
+            right: self.right.max(other.right),
+        }
+    }
+
+    fn height(&self) -> usize {
+        (self.bottom - self.top + 1).max(0) as usize
+    }
+
+    fn width(&self) -> usize {
+        (self.right - self.left + 1).max(0) as usize
+    }
+}
+
+struct Grove {
+    elves: HashSet<Position>,
+    rules: Vec<Rule>,
+    first_rule: usize,
+}
+
+impl Grove {
+    fn new(elves: HashSet<Position>, rules: Vec<Rule>) -> Self {
+        Self {
+            elves,
+            rules,
+            first_rule: 0,
+        }
+    }
+
+    fn is_occupied(&self, (row, col): Position, compass: Compass) -> bool {
+        let (dr, dc) = compass.delta();
+
+        self.elves.contains(&(row + dr, col + dc))
+    }
+
+    fn propose(&self, elf: Position) -> Option<Position> {
+        if !COMPASS
+            .iter()
+            .any(|&compass| self.is_occupied(elf, compass))
+        {
+            return None;
+        }
+
+        (0..self.rules.len())
+            .map(|i| &self.rules[(self.first_rule + i) % self.rules.len()])
+            .find(|rule| {
+                rule.checks
+                    .iter()
+                    .all(|&check| !self.is_occupied(elf, check))
+            })
+            .map(|rule| {
+                let (dr, dc) = rule.step.delta();
+                (elf.0 + dr, elf.1 + dc)
+            })
+    }
+
+    // Plays one round, returning whether any elf moved.
+    fn round(&mut self) -> bool {
+        let mut proposals: HashMap<Position, Vec<Position>> = HashMap::new();
+        for &elf in &self.elves {
+            if let Some(target) = self.propose(elf) {
+                proposals.entry(target).or_default().push(elf);
+            }
+        }
+
+        let mut moved = false;
+        for (target, elves) in proposals {
+            if let [elf] = elves[..] {
+                self.elves.remove(&elf);
+                self.elves.insert(target);
+                moved = true;
+            }
+        }
+
+        self.first_rule = (self.first_rule + 1) % self.rules.len();
+
+        moved
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::of(&self.elves)
+    }
+
+    fn empty_ground(&self) -> usize {
+        let bounds = self.bounds();
+
+        bounds.height() * bounds.width() - self.elves.len()
+    }
+}
+
+fn render(elves: &HashSet<Position>, bounds: Bounds, packing: Option<Packing>) -> String {
+    let is_set =
+        |x: usize, y: usize| elves.contains(&(bounds.top + y as i64, bounds.left + x as i64));
+
+    match packing {
+        Some(packing) => packing.pack(bounds.width(), bounds.height(), is_set),
+        None => {
+            let mut text = String::new();
+            for y in 0..bounds.height() {
+                for x in 0..bounds.width() {
+                    text.push(if is_set(x, y) { '#' } else { '.' });
+                }
+                text.push('\n');
+            }
+            text
+        }
+    }
+}
+
+// Every frame shows the area covered over all the rounds, so the view stays still.
+fn animate(
+    input: &[String],
+    rules: &[Rule],
+    rounds: usize,
+    packing: Option<Packing>,
+) -> Result<Animation, Day23Error> {
+    let mut grove = Grove::new(parse_elves(input)?, rules.to_vec());
+
+    let mut states = vec![grove.elves.clone()];
+    for _ in 0..rounds {
+        if !grove.round() {
+            break;
+        }
+        states.push(grove.elves.clone());
+    }
+
+    let bounds = states.iter().map(Bounds::of).reduce(Bounds::union).unwrap();
+
+    let mut animation = Animation::new();
+    for elves in &states {
+        animation.push_frame(render(elves, bounds, packing));
+    }
+
+    Ok(animation)
+}
+
+fn part1(input: &[String], rules: &[Rule], rounds: usize) -> Result<usize, Day23Error> {
+    let mut grove = Grove::new(parse_elves(input)?, rules.to_vec());
+
+    for _ in 0..rounds {
+        grove.round();
+    }
+
+    Ok(grove.empty_ground())
+}
+
+fn part2(input: &[String], rules: &[Rule]) -> Result<usize, Day23Error> {
+    let mut grove = Grove::new(parse_elves(input)?, rules.to_vec());
+
+    (1..=ROUND_LIMIT)
+        .find(|_| !grove.round())
+        .ok_or(Day23Error::NoSteadyState(ROUND_LIMIT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc::to_lines;
+
+    const EXAMPLE: &str = "\
+....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..
+";
+
+    const SMALL_EXAMPLE: &str = "\
+.....
+..##.
+..#..
+.....
+..##.
+.....
+";
+
+    fn stock_rules() -> Vec<Rule> {
+        parse_rules(&[], STOCK_ORDER).unwrap()
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(
+            parse_rules(&["NE:N,NE", "S"], STOCK_ORDER).unwrap(),
+            vec![
+                Rule {
+                    step: Compass::NE,
+                    checks: vec![Compass::N, Compass::NE],
+                },
+                Rule {
+                    step: Compass::S,
+                    checks: vec![Compass::SE, Compass::S, Compass::SW],
+                },
+            ]
+        );
+        assert_eq!(parse_rules(&[], "WE").unwrap()[0], Rule::stock(Compass::W));
+        assert!(matches!(
+            parse_rules(&["X"], STOCK_ORDER),
+            Err(Day23Error::InvalidCompass(_))
+        ));
+        assert!(matches!(parse_rules(&[], ""), Err(Day23Error::NoRules)));
+        for rule in ["N:E", "NE:N,E", "S:"] {
+            assert!(matches!(
+                parse_rules(&[rule], STOCK_ORDER),
+                Err(Day23Error::InvalidRule(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_small_example() {
+        let mut grove = Grove::new(
+            parse_elves(&to_lines(SMALL_EXAMPLE)).unwrap(),
+            stock_rules(),
+        );
+
+        for _ in 0..3 {
+            assert!(grove.round());
+        }
+        assert!(!grove.round());
+
+        assert_eq!(
+            render(&grove.elves, grove.bounds(), None),
+            "\
+..#..
+....#
+#....
+....#
+.....
+..#..
+"
+        );
+    }
+
+    #[test]
+    fn test_animate() {
+        let input = to_lines(SMALL_EXAMPLE);
+        let animation = animate(&input, &stock_rules(), 10, Some(Packing::HalfBlock)).unwrap();
+
+        assert_eq!(animation.frames().len(), 4);
+        assert!(animation
+            .frames()
+            .iter()
+            .all(|frame| frame.lines().count() == 3));
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part1(&input, &stock_rules(), ROUNDS).unwrap(), 110);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part2(&input, &stock_rules()).unwrap(), 20);
+    }
+}
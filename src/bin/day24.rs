@@ -0,0 +1,592 @@
+use std::{cmp::Ordering, collections::BinaryHeap, io, num::ParseIntError};
+
+use aoc::{animation::Animation, cli::Args, puzzles::part_label, read_lines, render::Svg};
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum Day24Error {
+    IoError(io::Error),
+    ParseIntError(ParseIntError),
+    InvalidTile(char),
+    InvalidValley,
+    InvalidPart(usize),
+    NoPath,
+}
+
+impl From<io::Error> for Day24Error {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+impl From<ParseIntError> for Day24Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
+const WALL_COLOUR: &str = "#4a4e69";
+const GROUND_COLOUR: &str = "#f2e9e4";
+const BLIZZARD_COLOUR: &str = "#5fa8d3";
+const PATH_COLOUR: &str = "#f4a261";
+const EXPEDITION_COLOUR: &str = "#e63946";
+
+const INPUT_PATH: &str = "inputs/day24.txt";
+
+fn main() -> Result<(), Day24Error> {
+    let input = read_lines(INPUT_PATH)?;
+
+    let args = Args::from_env();
+
+    println!("{}: {:?}", part_label(24, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(24, 2), part2(&input)?);
+
+    let minute = args.parse("--minute")?;
+    let render_path = args.value("--render");
+    let animate_path = args.value("--animate");
+
+    if minute.is_some() || render_path.is_some() || animate_path.is_some() {
+        let valley = parse_valley(&input)?;
+        let path = expedition(&valley, args.parse("--part")?.unwrap_or(1))?;
+
+        if let Some(minute) = minute {
+            print!("{}", render_frame(&valley, &path, minute));
+        }
+        if let Some(render_path) = render_path {
+            render_svg(&valley, &path, minute.unwrap_or(path.len() - 1)).save(render_path)?;
+        }
+        if let Some(animate_path) = animate_path {
+            animate(&valley, &path).save(animate_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Heading {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Heading {
+    fn symbol(self) -> char {
+        match self {
+            Heading::Up => '^',
+            Heading::Down => 'v',
+            Heading::Left => '<',
+            Heading::Right => '>',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Blizzard {
+    // Position inside the walls.
+    row: usize,
+    col: usize,
+    heading: Heading,
+}
+
+impl Blizzard {
+    // Position inside the walls after `minute` minutes.
+    fn at(&self, minute: usize, height: usize, width: usize) -> (usize, usize) {
+        match self.heading {
+            Heading::Up => ((self.row + height - minute % height) % height, self.col),
+            Heading::Down => ((self.row + minute) % height, self.col),
+            Heading::Left => (self.row, (self.col + width - minute % width) % width),
+            Heading::Right => (self.row, (self.col + minute) % width),
+        }
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+// Positions count the walls, so the entrance is in row 0 and the exit in the
+// last row.
+type Position = (usize, usize);
+
+// Waiting in place, then the four directions, as row and column steps.
+const MOVES: [(isize, isize); 5] = [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)];
+
+struct Valley {
+    // Size of the area inside the walls.
+    height: usize,
+    width: usize,
+    entrance: Position,
+    exit: Position,
+    // The blizzards are back where they started after this many minutes.
+    period: usize,
+    // Which cells inside the walls hold a blizzard, for each minute of the period.
+    occupied: Vec<Vec<bool>>,
+    blizzards: Vec<Blizzard>,
+}
+
+impl Valley {
+    fn new(
+        height: usize,
+        width: usize,
+        entrance: usize,
+        exit: usize,
+        blizzards: &[Blizzard],
+    ) -> Self {
+        let period = lcm(height, width);
+
+        let occupied = (0..period)
+            .map(|minute| {
+                let mut occupied = vec![false; height * width];
+                for blizzard in blizzards {
+                    let (row, col) = blizzard.at(minute, height, width);
+                    occupied[row * width + col] = true;
+                }
+                occupied
+            })
+            .collect();
+
+        Self {
+            height,
+            width,
+            entrance: (0, entrance + 1),
+            exit: (height + 1, exit + 1),
+            period,
+            occupied,
+            blizzards: blizzards.to_vec(),
+        }
+    }
+
+    fn is_open(&self, (row, col): Position, minute: usize) -> bool {
+        if (row, col) == self.entrance || (row, col) == self.exit {
+            return true;
+        }
+        if !(1..=self.height).contains(&row) || !(1..=self.width).contains(&col) {
+            return false;
+        }
+
+        !self.occupied[minute % self.period][(row - 1) * self.width + col - 1]
+    }
+
+    // Each reachable position along with the index of its move in `MOVES`.
+    fn moves(&self, (row, col): Position) -> impl Iterator<Item = (u8, Position)> {
+        MOVES
+            .into_iter()
+            .enumerate()
+            .filter_map(move |(step, (dr, dc))| {
+                Some((
+                    step as u8,
+                    (row.checked_add_signed(dr)?, col.checked_add_signed(dc)?),
+                ))
+            })
+    }
+
+    fn index_of(&self, (row, col): Position, minute: usize) -> usize {
+        ((minute % self.period) * (self.height + 2) + row) * (self.width + 2) + col
+    }
+
+    // A* over (position, minute modulo the period), returning the minute at
+    // which the expedition first reaches `to`.
+    fn crossing(&self, from: Position, to: Position, start: usize) -> Result<usize, Day24Error> {
+        Ok(start + self.route(from, to, start)?.len() - 1)
+    }
+
+    // The expedition's position at each minute from `start` until it first
+    // reaches `to`.
+    fn route(
+        &self,
+        from: Position,
+        to: Position,
+        start: usize,
+    ) -> Result<Vec<Position>, Day24Error> {
+        let distance = |(row, col): Position| row.abs_diff(to.0) + col.abs_diff(to.1);
+
+        // The move that first reached each state, or `None` if unvisited.
+        let mut reached_by = vec![None; self.period * (self.height + 2) * (self.width + 2)];
+        let mut queue = BinaryHeap::from([State {
+            position: from,
+            minute: start,
+            estimate: start + distance(from),
+            step: 0,
+        }]);
+
+        while let Some(State {
+            position,
+            minute,
+            step,
+            ..
+        }) = queue.pop()
+        {
+            let index = self.index_of(position, minute);
+            if reached_by[index].is_some() {
+                continue;
+            }
+            reached_by[index] = Some(step);
+
+            if position == to {
+                let mut route = vec![position];
+                let (mut position, mut minute) = (position, minute);
+
+                while minute > start {
+                    let step =
+                        reached_by[self.index_of(position, minute)].expect("state was visited");
+                    let (dr, dc) = MOVES[step as usize];
+                    position = (
+                        position.0.wrapping_add_signed(-dr),
+                        position.1.wrapping_add_signed(-dc),
+                    );
+                    minute -= 1;
+                    route.push(position);
+                }
+
+                route.reverse();
+                return Ok(route);
+            }
+
+            for (step, next) in self.moves(position) {
+                if self.is_open(next, minute + 1)
+                    && reached_by[self.index_of(next, minute + 1)].is_none()
+                {
+                    queue.push(State {
+                        position: next,
+                        minute: minute + 1,
+                        estimate: minute + 1 + distance(next),
+                        step,
+                    });
+                }
+            }
+        }
+
+        Err(Day24Error::NoPath)
+    }
+
+    // The valley at `minute`, walls included, showing blizzards the way the
+    // puzzle does: a count where several share a cell.
+    fn tiles(&self, minute: usize) -> Vec<Vec<char>> {
+        let mut tiles = vec![vec!['#'; self.width + 2]; self.height + 2];
+        for (row, col) in [self.entrance, self.exit] {
+            tiles[row][col] = '.';
+        }
+
+        for row in &mut tiles[1..=self.height] {
+            row[1..=self.width].fill('.');
+        }
+
+        let mut counts = vec![0; self.height * self.width];
+        for blizzard in &self.blizzards {
+            let (row, col) = blizzard.at(minute, self.height, self.width);
+            let count = &mut counts[row * self.width + col];
+            *count += 1;
+
+            tiles[row + 1][col + 1] = match *count {
+                1 => blizzard.heading.symbol(),
+                count => char::from_digit(count, 10).unwrap_or('*'),
+            };
+        }
+
+        tiles
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct State {
+    position: Position,
+    minute: usize,
+    estimate: usize,
+    // Index in `MOVES` of the move that led here.
+    step: u8,
+}
+
+// Lowest estimate first, so `BinaryHeap` pops the most promising state.
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.minute.cmp(&other.minute))
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn gap(line: &str) -> Result<usize, Day24Error> {
+    let mut gaps = line.match_indices('.');
+
+    match (gaps.next(), gaps.next()) {
+        (Some((index, _)), None) if index > 0 => Ok(index - 1),
+        _ => Err(Day24Error::InvalidValley),
+    }
+}
+
+fn parse_valley(input: &[String]) -> Result<Valley, Day24Error> {
+    let (first, rest) = input.split_first().ok_or(Day24Error::InvalidValley)?;
+    let (last, inside) = rest.split_last().ok_or(Day24Error::InvalidValley)?;
+
+    let width = first
+        .len()
+        .checked_sub(2)
+        .ok_or(Day24Error::InvalidValley)?;
+    let height = inside.len();
+    if width == 0 || height == 0 {
+        return Err(Day24Error::InvalidValley);
+    }
+
+    let mut blizzards = vec![];
+    for (row, line) in inside.iter().enumerate() {
+        let cells = line
+            .strip_prefix('#')
+            .and_then(|line| line.strip_suffix('#'))
+            .ok_or(Day24Error::InvalidValley)?;
+        if cells.len() != width {
+            return Err(Day24Error::InvalidValley);
+        }
+
+        for (col, c) in cells.chars().enumerate() {
+            let heading = match c {
+                '.' => continue,
+                '^' => Heading::Up,
+                'v' => Heading::Down,
+                '<' => Heading::Left,
+                '>' => Heading::Right,
+                _ => return Err(Day24Error::InvalidTile(c)),
+            };
+            blizzards.push(Blizzard { row, col, heading });
+        }
+    }
+
+    Ok(Valley::new(
+        height,
+        width,
+        gap(first)?,
+        gap(last)?,
+        &blizzards,
+    ))
+}
+
+// Positions at every minute of the part's trip, starting at the entrance.
+fn expedition(valley: &Valley, part: usize) -> Result<Vec<Position>, Day24Error> {
+    let (entrance, exit) = (valley.entrance, valley.exit);
+    let legs = match part {
+        1 => vec![(entrance, exit)],
+        2 => vec![(entrance, exit), (exit, entrance), (entrance, exit)],
+        part => return Err(Day24Error::InvalidPart(part)),
+    };
+
+    let mut path = vec![entrance];
+    for (from, to) in legs {
+        let route = valley.route(from, to, path.len() - 1)?;
+        path.extend(route.into_iter().skip(1));
+    }
+
+    Ok(path)
+}
+
+// The expedition is `E` and the cells it has already been through are `o`,
+// unless a blizzard is passing over them.
+fn render_frame(valley: &Valley, path: &[Position], minute: usize) -> String {
+    let mut tiles = valley.tiles(minute);
+
+    for &(row, col) in path.iter().take(minute) {
+        if tiles[row][col] == '.' {
+            tiles[row][col] = 'o';
+        }
+    }
+    if let Some(&(row, col)) = path.get(minute) {
+        tiles[row][col] = 'E';
+    }
+
+    tiles
+        .into_iter()
+        .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+        .collect()
+}
+
+// Unlike the text frames, the whole path is overlaid.
+fn render_svg(valley: &Valley, path: &[Position], minute: usize) -> Svg {
+    let tiles = valley.tiles(minute);
+    let width = valley.width as i64 + 2;
+    let height = valley.height as i64 + 2;
+
+    let mut svg = Svg::new(0, 0, width, height);
+    svg.rect(0, 0, width, height, GROUND_COLOUR);
+
+    for &(row, col) in path {
+        svg.rect(col as i64, row as i64, 1, 1, PATH_COLOUR);
+    }
+
+    for (row, line) in tiles.iter().enumerate() {
+        for (col, tile) in line.iter().enumerate() {
+            let colour = match tile {
+                '.' => continue,
+                '#' => WALL_COLOUR,
+                _ => BLIZZARD_COLOUR,
+            };
+            svg.rect(col as i64, row as i64, 1, 1, colour);
+        }
+    }
+
+    if let Some(&(row, col)) = path.get(minute) {
+        svg.rect(col as i64, row as i64, 1, 1, EXPEDITION_COLOUR);
+    }
+
+    svg
+}
+
+fn animate(valley: &Valley, path: &[Position]) -> Animation {
+    let mut animation = Animation::new();
+    for minute in 0..path.len() {
+        animation.push_frame(render_frame(valley, path, minute));
+    }
+
+    animation
+}
+
+fn part1(input: &[String]) -> Result<usize, Day24Error> {
+    let valley = parse_valley(input)?;
+
+    valley.crossing(valley.entrance, valley.exit, 0)
+}
+
+fn part2(input: &[String]) -> Result<usize, Day24Error> {
+    let valley = parse_valley(input)?;
+
+    let there = valley.crossing(valley.entrance, valley.exit, 0)?;
+    let back = valley.crossing(valley.exit, valley.entrance, there)?;
+    valley.crossing(valley.entrance, valley.exit, back)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc::to_lines;
+
+    const EXAMPLE: &str = "\
+#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+";
+
+    #[test]
+    fn test_blizzard_cache() {
+        let valley = parse_valley(&to_lines(EXAMPLE)).unwrap();
+
+        assert_eq!((valley.height, valley.width), (4, 6));
+        assert_eq!(valley.period, 12);
+        assert_eq!((valley.entrance, valley.exit), ((0, 1), (5, 6)));
+
+        assert!(!valley.is_open((1, 1), 0));
+        assert!(valley.is_open((1, 3), 0));
+        // The first row's `>>` has moved one step right after a minute.
+        assert!(valley.is_open((1, 1), 1));
+        assert!(!valley.is_open((1, 3), 1));
+        assert!(!valley.is_open((0, 2), 0));
+        assert_eq!(valley.is_open((1, 3), 13), valley.is_open((1, 3), 1));
+    }
+
+    #[test]
+    fn test_expedition() {
+        let valley = parse_valley(&to_lines(EXAMPLE)).unwrap();
+
+        for (part, minutes) in [(1, 18), (2, 54)] {
+            let path = expedition(&valley, part).unwrap();
+
+            assert_eq!(path.len(), minutes + 1);
+            assert_eq!(path.first(), Some(&valley.entrance));
+            assert_eq!(path.last(), Some(&valley.exit));
+            for (minute, pair) in path.windows(2).enumerate() {
+                assert!(valley.moves(pair[0]).any(|(_, next)| next == pair[1]));
+                assert!(valley.is_open(pair[1], minute + 1));
+            }
+        }
+
+        assert!(matches!(
+            expedition(&valley, 3),
+            Err(Day24Error::InvalidPart(3))
+        ));
+    }
+
+    #[test]
+    fn test_render_frame() {
+        let valley = parse_valley(&to_lines(EXAMPLE)).unwrap();
+        let path = expedition(&valley, 1).unwrap();
+
+        assert_eq!(render_frame(&valley, &[], 0), EXAMPLE);
+        assert_eq!(
+            render_frame(&valley, &path, 0),
+            EXAMPLE.replacen('.', "E", 1)
+        );
+        // Minute 2 of the puzzle's walkthrough.
+        assert_eq!(
+            render_frame(&valley, &path, 2),
+            "\
+#o######
+#o2>2..#
+#E^22^<#
+#.>2.^>#
+#.>..<.#
+######.#
+"
+        );
+
+        let last = render_frame(&valley, &path, path.len() - 1);
+        assert_eq!(last.lines().last(), Some("######E#"));
+        assert!(!render_frame(&valley, &path, path.len()).contains('E'));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let valley = parse_valley(&to_lines(EXAMPLE)).unwrap();
+        let path = expedition(&valley, 1).unwrap();
+
+        let svg = render_svg(&valley, &path, 0).to_text();
+        assert!(svg.contains(r#"viewBox="0 0 8 6""#));
+        assert_eq!(svg.matches(WALL_COLOUR).count(), 22);
+        assert_eq!(svg.matches(BLIZZARD_COLOUR).count(), 19);
+        assert_eq!(svg.matches(EXPEDITION_COLOUR).count(), 1);
+        assert_eq!(svg.matches(PATH_COLOUR).count(), path.len());
+    }
+
+    #[test]
+    fn test_animate() {
+        let valley = parse_valley(&to_lines(EXAMPLE)).unwrap();
+        let path = expedition(&valley, 2).unwrap();
+
+        let animation = animate(&valley, &path);
+        assert_eq!(animation.frames().len(), 55);
+        assert!(animation
+            .frames()
+            .iter()
+            .all(|frame| frame.matches('E').count() == 1 && frame.lines().count() == 6));
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part1(&input).unwrap(), 18);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part2(&input).unwrap(), 54);
+    }
+}
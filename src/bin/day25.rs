@@ -0,0 +1,95 @@
+use std::io;
+
+use aoc::{
+    puzzles::part_label,
+    read_lines,
+    snafu::{Snafu, SnafuError},
+};
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum Day25Error {
+    IoError(io::Error),
+    SnafuError(SnafuError),
+}
+
+impl From<io::Error> for Day25Error {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+impl From<SnafuError> for Day25Error {
+    fn from(e: SnafuError) -> Self {
+        Self::SnafuError(e)
+    }
+}
+
+const INPUT_PATH: &str = "inputs/day25.txt";
+
+// There is no part 2: the last star comes with the other forty-nine.
+fn main() -> Result<(), Day25Error> {
+    let input = read_lines(INPUT_PATH)?;
+
+    println!("{}: {:?}", part_label(25, 1), part1(&input)?);
+
+    Ok(())
+}
+
+fn part1(input: &[String]) -> Result<String, Day25Error> {
+    let numbers = input
+        .iter()
+        .map(|line| line.parse())
+        .collect::<Result<Vec<Snafu>, _>>()?;
+
+    let sum = numbers
+        .into_iter()
+        .try_fold(Snafu::default(), |sum, number| {
+            sum.checked_add(number).ok_or(SnafuError::Overflow)
+        })?;
+
+    Ok(sum.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc::to_lines;
+
+    const EXAMPLE: &str = "\
+1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122
+";
+
+    #[test]
+    fn test_part1() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part1(&input).unwrap(), "2=-1=0");
+    }
+
+    #[test]
+    fn test_part1_overflow() {
+        // 5^27 is more than half of `i64::MAX`, so two of them overflow.
+        let line = format!("1{}", "0".repeat(27));
+        let input = to_lines(&[line.as_str(); 2].join("\n"));
+
+        assert!(matches!(
+            part1(&input),
+            Err(Day25Error::SnafuError(SnafuError::Overflow))
+        ));
+        assert!(part1(&input[..1]).is_ok());
+    }
+}
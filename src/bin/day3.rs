@@ -1,6 +1,6 @@
 use std::{collections::HashSet, io};
 
-use aoc::read_lines;
+use aoc::{puzzles::part_label, read_lines};
 
 #[derive(Debug)]
 enum Day3Error {
@@ -23,8 +23,8 @@ const INPUT_PATH: &str = "inputs/day3.txt";
 fn main() -> Result<(), Day3Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(3, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(3, 2), part2(&input)?);
 
     Ok(())
 }
@@ -1,6 +1,6 @@
 use std::{collections::HashSet, io, num::ParseIntError, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{puzzles::part_label, read_lines};
 
 #[derive(Debug)]
 enum Day4Error {
@@ -27,8 +27,8 @@ const INPUT_PATH: &str = "inputs/day4.txt";
 fn main() -> Result<(), Day4Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(4, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(4, 2), part2(&input)?);
 
     Ok(())
 }
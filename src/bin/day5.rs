@@ -1,6 +1,6 @@
 use std::{io, num::ParseIntError, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{puzzles::part_label, read_lines};
 
 #[derive(Debug)]
 enum Day5Error {
@@ -29,8 +29,8 @@ const INPUT_PATH: &str = "inputs/day5.txt";
 fn main() -> Result<(), Day5Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(5, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(5, 2), part2(&input)?);
 
     Ok(())
 }
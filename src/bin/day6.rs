@@ -1,15 +1,19 @@
 use std::{
     collections::{HashSet, VecDeque},
     io,
+    num::ParseIntError,
+    time::Instant,
 };
 
-use aoc::read_lines;
+use aoc::{cli::Args, puzzles::part_label, read_lines, rng::XorShift};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day6Error {
     IoError(io::Error),
     EmptyInput,
     NoMarker,
+    ParseIntError(ParseIntError),
 }
 
 impl From<io::Error> for Day6Error {
@@ -18,18 +22,41 @@ impl From<io::Error> for Day6Error {
     }
 }
 
+impl From<ParseIntError> for Day6Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
 const INPUT_PATH: &str = "inputs/day6.txt";
 
 fn main() -> Result<(), Day6Error> {
+    let args = Args::from_env();
+
+    // Benchmarks run on a generated datastream, so they don't need an input.
+    if let Some(megabytes) = args.parse("--benchmark")? {
+        return benchmark(megabytes);
+    }
+
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(6, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(6, 2), part2(&input)?);
 
     Ok(())
 }
 
 fn find_marker(input: &str, window_size: usize) -> Result<usize, Day6Error> {
+    // Positions count characters, which are bytes only for ASCII.
+    #[cfg(feature = "bit-parallel")]
+    if input.is_ascii() {
+        return find_marker_bit_parallel(input.as_bytes(), window_size);
+    }
+
+    find_marker_scalar(input, window_size)
+}
+
+fn find_marker_scalar(input: &str, window_size: usize) -> Result<usize, Day6Error> {
     let mut chars = input.chars().enumerate();
     let mut window: VecDeque<char> = VecDeque::new();
     for _ in 0..(window_size - 1) {
@@ -55,6 +82,71 @@ fn find_marker(input: &str, window_size: usize) -> Result<usize, Day6Error> {
     Err(Day6Error::NoMarker)
 }
 
+// Each byte value owns a bit of a 256-bit mask, flipped as the byte enters
+// and leaves the window. A bit is set while its byte appears an odd number of
+// times, so all `window_size` bits are set exactly when the bytes differ.
+#[cfg(feature = "bit-parallel")]
+fn find_marker_bit_parallel(input: &[u8], window_size: usize) -> Result<usize, Day6Error> {
+    // Flips the byte's bit, returning 1 if it is now set and 0 if not.
+    fn toggle(mask: &mut [u64; 4], byte: u8) -> usize {
+        let word = &mut mask[usize::from(byte >> 6)];
+        *word ^= 1 << (byte & 63);
+
+        ((*word >> (byte & 63)) & 1) as usize
+    }
+
+    let mut mask = [0u64; 4];
+    let mut set_bits = 0;
+
+    for (i, &byte) in input.iter().enumerate() {
+        // Branch-free: each flip adds 1 to the count if it set a bit, or
+        // takes 1 away if it cleared one.
+        if i >= window_size {
+            set_bits = set_bits + 2 * toggle(&mut mask, input[i - window_size]) - 1;
+        }
+        set_bits = set_bits + 2 * toggle(&mut mask, byte) - 1;
+
+        if set_bits == window_size {
+            return Ok(i + 1);
+        }
+    }
+
+    Err(Day6Error::NoMarker)
+}
+
+// Letters from the first half of the alphabet can't hold a start-of-message
+// marker, so the only one is the run of fourteen letters at the very end.
+fn generate_datastream(megabytes: usize) -> String {
+    let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+
+    let mut datastream: String = (0..megabytes << 20)
+        .map(|_| (b'a' + rng.below(13) as u8) as char)
+        .collect();
+    datastream.push_str("abcdefghijklmn");
+
+    datastream
+}
+
+fn benchmark(megabytes: usize) -> Result<(), Day6Error> {
+    let datastream = generate_datastream(megabytes);
+    println!("Generated datastream of {} bytes", datastream.len());
+
+    let start = Instant::now();
+    let scalar = find_marker_scalar(&datastream, 14)?;
+    println!("Scalar: {scalar} in {:?}", start.elapsed());
+
+    #[cfg(feature = "bit-parallel")]
+    {
+        let start = Instant::now();
+        let bit_parallel = find_marker_bit_parallel(datastream.as_bytes(), 14)?;
+        println!("Bit-parallel: {bit_parallel} in {:?}", start.elapsed());
+
+        assert_eq!(scalar, bit_parallel);
+    }
+
+    Ok(())
+}
+
 fn part1(input: &Vec<String>) -> Result<usize, Day6Error> {
     let input = input.first().ok_or(Day6Error::EmptyInput)?;
 
@@ -66,3 +158,48 @@ fn part2(input: &Vec<String>) -> Result<usize, Day6Error> {
 
     find_marker(input, 14)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLES: [(&str, usize, usize); 4] = [
+        ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+        ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+        ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+        ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+    ];
+
+    #[test]
+    fn test_find_marker() {
+        for (datastream, packet, message) in EXAMPLES {
+            assert_eq!(find_marker(datastream, 4).unwrap(), packet);
+            assert_eq!(find_marker(datastream, 14).unwrap(), message);
+        }
+        assert!(matches!(find_marker("abcabc", 4), Err(Day6Error::NoMarker)));
+    }
+
+    #[test]
+    #[cfg(feature = "bit-parallel")]
+    fn test_bit_parallel_matches_scalar() {
+        let datastream = generate_datastream(0);
+        assert_eq!(
+            find_marker_bit_parallel(datastream.as_bytes(), 14).unwrap(),
+            14
+        );
+
+        for (datastream, _, _) in EXAMPLES {
+            for window_size in 1..=16 {
+                assert_eq!(
+                    find_marker_bit_parallel(datastream.as_bytes(), window_size).ok(),
+                    find_marker_scalar(datastream, window_size).ok(),
+                    "{datastream} with a window of {window_size}"
+                );
+            }
+        }
+        // Bytes from every word of the mask.
+        let bytes = [0, 64, 128, 255, 0, 1];
+        assert_eq!(find_marker_bit_parallel(&bytes, 4).unwrap(), 4);
+        assert_eq!(find_marker_bit_parallel(&bytes, 5).unwrap(), 6);
+    }
+}
@@ -1,8 +1,9 @@
-use std::{collections::HashMap, io, num::ParseIntError, str::FromStr};
+use std::{collections::BTreeMap, fmt, io, num::ParseIntError, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{puzzles::part_label, read_lines};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum Day7Error {
     IoError(io::Error),
     ParseIntError(ParseIntError),
@@ -30,8 +31,8 @@ const INPUT_PATH: &str = "inputs/day7.txt";
 fn main() -> Result<(), Day7Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(7, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(7, 2), part2(&input)?);
 
     Ok(())
 }
@@ -39,22 +40,149 @@ fn main() -> Result<(), Day7Error> {
 fn parse_directory_entry(s: &str) -> Result<(String, File), Day7Error> {
     let v: Vec<_> = s.split(" ").collect();
     match v.as_slice() {
-        ["dir", name] => Ok((name.to_string(), File::Directory(HashMap::new()))),
+        ["dir", name] => Ok((name.to_string(), File::Directory(BTreeMap::new()))),
         [size, name] => Ok((name.to_string(), File::File(size.parse()?))),
         _ => Err(Day7Error::InvalidFileSystemItem(s.to_string())),
     }
 }
 
+type FileId = usize;
+
 #[derive(Debug)]
 enum File {
     File(usize),
-    Directory(HashMap<String, File>),
+    Directory(BTreeMap<String, FileId>),
+}
+
+// Every file lives in one arena, and directories refer to their entries by
+// index. Entries are always pushed after their parent directory.
+#[derive(Debug)]
+struct FileSystem {
+    files: Vec<File>,
+}
+
+const ROOT: FileId = 0;
+
+impl FileSystem {
+    fn new() -> Self {
+        Self {
+            files: vec![File::Directory(BTreeMap::new())],
+        }
+    }
+
+    fn find_item(&self, path: &[String]) -> Result<FileId, Day7Error> {
+        path.iter()
+            .try_fold(ROOT, |id, component| match &self.files[id] {
+                File::File(_) => Err(Day7Error::NotADirectory),
+                File::Directory(entries) => entries
+                    .get(component)
+                    .copied()
+                    .ok_or(Day7Error::ItemNotFound),
+            })
+    }
+
+    fn set_entries(&mut self, id: FileId, entries: Vec<(String, File)>) -> Result<(), Day7Error> {
+        if let File::File(_) = self.files[id] {
+            return Err(Day7Error::NotADirectory);
+        }
+
+        let mut entry_ids = BTreeMap::new();
+        for (name, file) in entries {
+            self.files.push(file);
+            entry_ids.insert(name, self.files.len() - 1);
+        }
+        self.files[id] = File::Directory(entry_ids);
+
+        Ok(())
+    }
+
+    // Entries come after their directory, so a reverse sweep sees every
+    // entry's size before the directory that contains it.
+    fn total_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.files.len()];
+
+        for id in (0..self.files.len()).rev() {
+            sizes[id] = match &self.files[id] {
+                File::File(size) => *size,
+                File::Directory(entries) => entries.values().map(|entry| sizes[*entry]).sum(),
+            };
+        }
+
+        sizes
+    }
+
+    // Directories reachable from the root; listing a directory twice leaves
+    // its old entries unreachable in the arena.
+    fn directories(&self) -> Vec<FileId> {
+        let mut directories = vec![];
+        let mut to_explore = vec![ROOT];
+
+        while let Some(id) = to_explore.pop() {
+            if let File::Directory(entries) = &self.files[id] {
+                directories.push(id);
+                to_explore.extend(entries.values());
+            }
+        }
+
+        directories
+    }
+}
+
+// An absolute directory path, kept normalised: no `.`, `..` or empty
+// components.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DirectoryPath {
+    components: Vec<String>,
+}
+
+impl DirectoryPath {
+    fn root() -> Self {
+        Self::default()
+    }
+
+    fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    // Resolves a `cd` target against this path. Absolute targets start from
+    // the root, and `..` at the root stays there, as in a shell.
+    fn join(&self, target: &str) -> Self {
+        let mut path = if target.starts_with('/') {
+            Self::root()
+        } else {
+            self.clone()
+        };
+
+        for component in target.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    path.components.pop();
+                }
+                name => path.components.push(name.to_string()),
+            }
+        }
+
+        path
+    }
+}
+
+impl fmt::Display for DirectoryPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.components.is_empty() {
+            return write!(f, "/");
+        }
+
+        for component in &self.components {
+            write!(f, "/{}", component)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 enum Command {
-    CdRoot,
-    CdParent,
     Cd(String),
     Ls,
 }
@@ -65,9 +193,7 @@ impl FromStr for Command {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let v: Vec<_> = s.split(" ").collect();
         match v.as_slice() {
-            ["$", "cd", "/"] => Ok(Self::CdRoot),
-            ["$", "cd", ".."] => Ok(Self::CdParent),
-            ["$", "cd", name] => Ok(Self::Cd(name.to_string())),
+            ["$", "cd", target] => Ok(Self::Cd(target.to_string())),
             ["$", "ls"] => Ok(Self::Ls),
             _ => Err(Self::Err::InvalidCommand(s.to_string())),
         }
@@ -93,7 +219,7 @@ impl TryFrom<Vec<String>> for CommandOutputPair {
     }
 }
 
-fn parse_command_output_pairs(input: &Vec<String>) -> Result<Vec<CommandOutputPair>, Day7Error> {
+fn parse_command_output_pairs(input: &[String]) -> Result<Vec<CommandOutputPair>, Day7Error> {
     let mut accumulator: Vec<String> = vec![];
     let mut command_output_pairs: Vec<CommandOutputPair> = vec![];
 
@@ -111,22 +237,8 @@ fn parse_command_output_pairs(input: &Vec<String>) -> Result<Vec<CommandOutputPa
 }
 
 struct State {
-    root: File,
-    path: Vec<String>,
-}
-
-fn find_item<'a>(root: &'a mut File, path: &[String]) -> Result<&'a mut File, Day7Error> {
-    match path {
-        [] => Ok(root),
-        [component, rest @ ..] => match root {
-            File::File(_) => Err(Day7Error::NotADirectory),
-            File::Directory(items) => {
-                let file = items.get_mut(component).ok_or(Day7Error::ItemNotFound)?;
-
-                find_item(file, rest)
-            }
-        },
-    }
+    file_system: FileSystem,
+    path: DirectoryPath,
 }
 
 fn reduce(
@@ -134,109 +246,178 @@ fn reduce(
     CommandOutputPair { command, output }: CommandOutputPair,
 ) -> Result<State, Day7Error> {
     match command {
-        Command::CdRoot => Ok(State {
-            root: state.root,
-            path: vec![],
-        }),
-        Command::CdParent => {
-            state.path.pop();
-            Ok(state)
-        }
-        Command::Cd(name) => {
-            state.path.push(name);
+        Command::Cd(target) => {
+            state.path = state.path.join(&target);
             Ok(state)
         }
         Command::Ls => {
             let entries = output
                 .iter()
                 .map(|line| parse_directory_entry(line))
-                .collect::<Result<HashMap<String, File>, Day7Error>>()?;
+                .collect::<Result<Vec<_>, Day7Error>>()?;
 
-            if let File::Directory(e) = find_item(&mut state.root, &state.path)? {
-                *e = entries;
-                Ok(state)
-            } else {
-                Err(Day7Error::NotADirectory)
-            }
+            let id = state.file_system.find_item(state.path.components())?;
+            state.file_system.set_entries(id, entries)?;
+
+            Ok(state)
         }
     }
 }
 
-fn infer_structure<'a>(command_output_pairs: Vec<CommandOutputPair>) -> Result<File, Day7Error> {
+fn infer_structure(command_output_pairs: Vec<CommandOutputPair>) -> Result<FileSystem, Day7Error> {
     let mut state = State {
-        root: File::Directory(HashMap::new()),
-        path: vec![],
+        file_system: FileSystem::new(),
+        path: DirectoryPath::root(),
     };
 
     for command_output_pair in command_output_pairs {
         state = reduce(state, command_output_pair)?;
     }
 
-    Ok(state.root)
+    Ok(state.file_system)
 }
 
-fn total_size(file: &File) -> usize {
-    match file {
-        File::File(size) => size.to_owned(),
-        File::Directory(entries) => entries.values().map(total_size).sum(),
-    }
-}
+fn directory_sizes(file_system: &FileSystem) -> impl Iterator<Item = usize> {
+    let sizes = file_system.total_sizes();
 
-#[derive(Debug)]
-struct Walk<'a> {
-    to_explore: Vec<(&'a String, &'a File)>,
+    file_system
+        .directories()
+        .into_iter()
+        .map(move |id| sizes[id])
 }
 
-impl<'a> Walk<'a> {
-    fn new(root: (&'a String, &'a File)) -> Walk<'a> {
-        Walk {
-            to_explore: vec![root],
-        }
-    }
-}
-
-impl<'a> Iterator for Walk<'a> {
-    type Item = (&'a String, &'a File);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.to_explore.pop().map(|(name, file)| {
-            if let File::Directory(entries) = file {
-                self.to_explore.extend(entries);
-            }
-
-            (name, file)
-        })
-    }
-}
-
-fn part1(input: &Vec<String>) -> Result<usize, Day7Error> {
+fn part1(input: &[String]) -> Result<usize, Day7Error> {
     let command_output_pairs = parse_command_output_pairs(input)?;
-    let root = infer_structure(command_output_pairs)?;
+    let file_system = infer_structure(command_output_pairs)?;
 
-    let total = Walk::new((&String::from("/"), &root))
-        .filter_map(|(_, file)| match file {
-            File::File(_) => None,
-            File::Directory(_) => Some(total_size(file)),
-        })
+    let total = directory_sizes(&file_system)
         .filter(|size| size <= &100000)
         .sum();
 
     Ok(total)
 }
 
-fn part2(input: &Vec<String>) -> Result<usize, Day7Error> {
+fn part2(input: &[String]) -> Result<usize, Day7Error> {
     let command_output_pairs = parse_command_output_pairs(input)?;
-    let root = infer_structure(command_output_pairs)?;
+    let file_system = infer_structure(command_output_pairs)?;
 
-    let unused_space = 70000000 - total_size(&root);
+    let unused_space = 70000000 - file_system.total_sizes()[ROOT];
     let required_space = 30000000 - unused_space;
 
-    Walk::new((&String::from("/"), &root))
-        .filter_map(|(_, file)| match file {
-            File::File(_) => None,
-            File::Directory(_) => Some(total_size(file)),
-        })
+    directory_sizes(&file_system)
         .filter(|size| size >= &required_space)
         .min()
         .ok_or(Day7Error::NoSolution)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aoc::to_lines;
+
+    const EXAMPLE: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+    #[test]
+    fn test_directory_path_join() {
+        let root = DirectoryPath::root();
+        let path = |target| root.join(target).to_string();
+
+        assert_eq!(path("/"), "/");
+        assert_eq!(path("a"), "/a");
+        assert_eq!(path("/a/e"), "/a/e");
+        assert_eq!(path("./a"), "/a");
+        assert_eq!(path("a/./e/"), "/a/e");
+        assert_eq!(path("a//e"), "/a/e");
+        assert_eq!(path("a/e/../d"), "/a/d");
+        assert_eq!(path(".."), "/");
+        assert_eq!(path("../../a"), "/a");
+        assert_eq!(path("a/../.."), "/");
+
+        let a_e = root.join("/a/e");
+        assert_eq!(a_e.components(), ["a", "e"]);
+        assert_eq!(a_e.join("..").to_string(), "/a");
+        assert_eq!(a_e.join("../../d").to_string(), "/d");
+        assert_eq!(a_e.join("/d").to_string(), "/d");
+        assert_eq!(a_e.join(".").join("./x"), root.join("a/e/x"));
+    }
+
+    #[test]
+    fn test_normalised_cd() {
+        let input = to_lines(
+            "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd ./a/
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd /a/e
+$ ls
+584 i
+$ cd ../../d/.
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+$ cd ../a/e/../..
+$ cd ..
+",
+        );
+
+        assert_eq!(part1(&input).unwrap(), 95437);
+        assert_eq!(part2(&input).unwrap(), 24933642);
+    }
+
+    #[test]
+    fn test_cd_into_missing_directory() {
+        let input = to_lines("$ cd /\n$ ls\ndir a\n$ cd /a/b\n$ ls\n1 x\n");
+
+        assert!(matches!(part1(&input), Err(Day7Error::ItemNotFound)));
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part1(&input).unwrap(), 95437);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = to_lines(EXAMPLE);
+
+        assert_eq!(part2(&input).unwrap(), 24933642);
+    }
+}
@@ -1,6 +1,6 @@
 use std::{io, num::ParseIntError, ops::Range};
 
-use aoc::read_lines;
+use aoc::{puzzles::part_label, read_lines};
 
 #[derive(Debug)]
 enum Day8Error {
@@ -28,8 +28,8 @@ const INPUT_PATH: &str = "inputs/day8.txt";
 fn main() -> Result<(), Day8Error> {
     let input = read_lines(INPUT_PATH)?;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    println!("{}: {:?}", part_label(8, 1), part1(&input)?);
+    println!("{}: {:?}", part_label(8, 2), part2(&input)?);
 
     Ok(())
 }
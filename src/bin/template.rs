@@ -47,13 +47,13 @@ ABCD
     fn test_part1() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part1(&input).unwrap(), todo!());
+        assert_eq!(part1(&input).unwrap(), todo!() as usize);
     }
 
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE);
 
-        assert_eq!(part2(&input).unwrap(), todo!());
+        assert_eq!(part2(&input).unwrap(), todo!() as usize);
     }
 }
@@ -0,0 +1,158 @@
+// Registers the solvers with the cargo-aoc runner. Each day's binary is
+// included as-is, and its answers are computed with the same defaults as
+// running the binary without flags.
+
+use std::fmt::{Debug, Display};
+
+fn answer<T: Display, E: Debug>(result: Result<T, E>) -> Result<String, String> {
+    result
+        .map(|answer| answer.to_string())
+        .map_err(|e| format!("{:?}", e))
+}
+
+macro_rules! solutions {
+    ($day:ident, $path:literal, |$input:ident| $part1:expr, $part2:expr) => {
+        #[allow(dead_code)]
+        pub mod $day {
+            include!($path);
+
+            #[aoc_runner_derive::aoc_generator($day)]
+            fn cargo_aoc_input(input: &str) -> Vec<String> {
+                aoc::to_lines(input)
+            }
+
+            #[aoc_runner_derive::aoc($day, part1)]
+            #[allow(clippy::ptr_arg)]
+            fn cargo_aoc_part1($input: &Vec<String>) -> Result<String, String> {
+                super::answer($part1)
+            }
+
+            #[aoc_runner_derive::aoc($day, part2)]
+            #[allow(clippy::ptr_arg)]
+            fn cargo_aoc_part2($input: &Vec<String>) -> Result<String, String> {
+                super::answer($part2)
+            }
+        }
+    };
+    ($day:ident, $path:literal, |$input:ident| $part1:expr) => {
+        #[allow(dead_code)]
+        pub mod $day {
+            include!($path);
+
+            #[aoc_runner_derive::aoc_generator($day)]
+            fn cargo_aoc_input(input: &str) -> Vec<String> {
+                aoc::to_lines(input)
+            }
+
+            #[aoc_runner_derive::aoc($day, part1)]
+            #[allow(clippy::ptr_arg)]
+            fn cargo_aoc_part1($input: &Vec<String>) -> Result<String, String> {
+                super::answer($part1)
+            }
+        }
+    };
+}
+
+solutions!(day1, "bin/day1.rs", |input| part1(input), part2(input));
+solutions!(day2, "bin/day2.rs", |input| part1(input), part2(input));
+solutions!(day3, "bin/day3.rs", |input| part1(input), part2(input));
+solutions!(day4, "bin/day4.rs", |input| part1(input), part2(input));
+solutions!(day5, "bin/day5.rs", |input| part1(input), part2(input));
+solutions!(day6, "bin/day6.rs", |input| part1(input), part2(input));
+solutions!(day7, "bin/day7.rs", |input| part1(input), part2(input));
+solutions!(day8, "bin/day8.rs", |input| part1(input), part2(input));
+solutions!(day9, "bin/day9.rs", |input| part1(input), part2(input));
+solutions!(
+    day10,
+    "bin/day10.rs",
+    |input| part1(input, Isa::Puzzle, Sampling::default()),
+    part2(input, Isa::Puzzle)
+);
+solutions!(day11, "bin/day11.rs", |input| part1(input), part2(input));
+solutions!(
+    day12,
+    "bin/day12.rs",
+    |input| part1(input, &MovementRules::default(), Algorithm::default()),
+    part2(input, &MovementRules::default(), Algorithm::default())
+);
+solutions!(day13, "bin/day13.rs", |input| part1(input), part2(input));
+solutions!(
+    day14,
+    "bin/day14.rs",
+    |input| part1_resuming(input, &[SOURCE]).map(|counts| counts.iter().sum::<usize>()),
+    part2_flood_fill(input, &[SOURCE])
+);
+solutions!(
+    day15,
+    "bin/day15.rs",
+    |input| part1(input, ROW),
+    part2(
+        input,
+        SEARCH_MIN,
+        SEARCH_MAX,
+        TUNING_MULTIPLIER,
+        Algorithm::default()
+    )
+    .map(|(_, frequency)| frequency)
+);
+solutions!(day16, "bin/day16.rs", |input| part1(input), part2(input));
+solutions!(
+    day17,
+    "bin/day17.rs",
+    |input| part1(input, &default_rock_shapes(), CHAMBER_WIDTH),
+    part2(input, &default_rock_shapes(), CHAMBER_WIDTH)
+);
+solutions!(day18, "bin/day18.rs", |input| part1(input), part2(input));
+solutions!(
+    day19,
+    "bin/day19.rs",
+    |input| part1(input, Engine::default(), true),
+    part2(input, Engine::default(), true)
+);
+solutions!(
+    day20,
+    "bin/day20.rs",
+    |input| part1(input, Algorithm::default()),
+    part2(input, 811589153, 10, Algorithm::default())
+);
+solutions!(day21, "bin/day21.rs", |input| part1(input), part2(input));
+solutions!(day22, "bin/day22.rs", |input| part1(input), part2(input));
+solutions!(
+    day23,
+    "bin/day23.rs",
+    |input| parse_rules(&[], STOCK_ORDER).and_then(|rules| part1(input, &rules, ROUNDS)),
+    parse_rules(&[], STOCK_ORDER).and_then(|rules| part2(input, &rules))
+);
+solutions!(day24, "bin/day24.rs", |input| part1(input), part2(input));
+solutions!(day25, "bin/day25.rs", |input| part1(input));
+
+#[cfg(test)]
+mod tests {
+    use aoc_runner::ArcStr;
+
+    use crate::{Day14Part1, Day14Part2, Day1Part1, Day1Part2, Factory};
+
+    const DAY1_EXAMPLE: &str =
+        "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+    const DAY14_EXAMPLE: &str = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n";
+
+    #[test]
+    fn test_runners() {
+        let answers = [
+            Factory::day1_part1(ArcStr::from(DAY1_EXAMPLE)),
+            Factory::day1_part2(ArcStr::from(DAY1_EXAMPLE)),
+            Factory::day14_part1(ArcStr::from(DAY14_EXAMPLE)),
+            Factory::day14_part2(ArcStr::from(DAY14_EXAMPLE)),
+        ]
+        .map(|runner| runner.unwrap().try_run().unwrap().to_string());
+
+        assert_eq!(answers, ["24000", "45000", "24", "93"]);
+    }
+
+    #[test]
+    fn test_runner_error() {
+        let runner = Factory::day1_part1(ArcStr::from("1000\nten\n")).unwrap();
+
+        assert!(runner.try_run().is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use std::{env, str::FromStr};
+
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn from_env() -> Self {
+        Self::new(env::args().skip(1))
+    }
+
+    pub fn new<I: IntoIterator<Item = String>>(args: I) -> Self {
+        Self {
+            args: args.into_iter().collect(),
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.args.iter().any(|arg| {
+            arg == name
+                || arg
+                    .strip_prefix(name)
+                    .is_some_and(|rest| rest.starts_with('='))
+        })
+    }
+
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        let mut values = vec![];
+
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            if arg == name {
+                if let Some(value) = args.next() {
+                    values.push(value.as_str());
+                }
+            } else if let Some(value) = arg
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                values.push(value);
+            }
+        }
+
+        values
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values(name).pop()
+    }
+
+    pub fn parse<T: FromStr>(&self, name: &str) -> Result<Option<T>, T::Err> {
+        self.value(name).map(str::parse).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Args {
+        Args::new(s.split_whitespace().map(|s| s.to_owned()))
+    }
+
+    #[test]
+    fn test_flag() {
+        let args = args("--verbose --row=10");
+
+        assert!(args.flag("--verbose"));
+        assert!(args.flag("--row"));
+        assert!(!args.flag("--quiet"));
+    }
+
+    #[test]
+    fn test_values() {
+        let args = args("--set a=1 --row 10 --set=b=2");
+
+        assert_eq!(args.values("--set"), vec!["a=1", "b=2"]);
+        assert_eq!(args.value("--row"), Some("10"));
+        assert_eq!(args.parse::<usize>("--row"), Ok(Some(10)));
+        assert_eq!(args.parse::<usize>("--min"), Ok(None));
+    }
+}
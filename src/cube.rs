@@ -0,0 +1,422 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CubeError {
+    InvalidArea(usize),
+    PartialFace(usize, usize),
+    NotConnected,
+    OverlappingFaces,
+}
+
+// In the order of the puzzle's facing values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+    Direction::Up,
+];
+
+impl Direction {
+    pub fn value(self) -> usize {
+        self as usize
+    }
+
+    pub fn turn_right(self) -> Self {
+        DIRECTIONS[(self.value() + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        DIRECTIONS[(self.value() + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        DIRECTIONS[(self.value() + 2) % 4]
+    }
+
+    // Row and column steps.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Self::Right => (0, 1),
+            Self::Down => (1, 0),
+            Self::Left => (0, -1),
+            Self::Up => (-1, 0),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Right => write!(f, "right"),
+            Self::Down => write!(f, "down"),
+            Self::Left => write!(f, "left"),
+            Self::Up => write!(f, "up"),
+        }
+    }
+}
+
+type Vector = [i32; 3];
+
+fn negate([x, y, z]: Vector) -> Vector {
+    [-x, -y, -z]
+}
+
+// Where a face sits on the cube: its outward normal, and where the net's
+// right and down directions point on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Orientation {
+    normal: Vector,
+    right: Vector,
+    down: Vector,
+}
+
+impl Orientation {
+    fn along(&self, direction: Direction) -> Vector {
+        match direction {
+            Direction::Right => self.right,
+            Direction::Down => self.down,
+            Direction::Left => negate(self.right),
+            Direction::Up => negate(self.down),
+        }
+    }
+
+    // Folds the neighbouring face of the net over the edge in `direction`.
+    fn fold(&self, direction: Direction) -> Self {
+        let Self {
+            normal,
+            right,
+            down,
+        } = *self;
+
+        match direction {
+            Direction::Right => Self {
+                normal: right,
+                right: negate(normal),
+                down,
+            },
+            Direction::Down => Self {
+                normal: down,
+                right,
+                down: negate(normal),
+            },
+            Direction::Left => Self {
+                normal: negate(right),
+                right: normal,
+                down,
+            },
+            Direction::Up => Self {
+                normal: negate(down),
+                right,
+                down: normal,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Face {
+    // Top-left cell of the face on the net.
+    pub row: usize,
+    pub col: usize,
+    orientation: Orientation,
+}
+
+// Leaving a face in some direction enters `face` facing `facing`. The
+// position along the edge runs the other way round when `reversed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub face: usize,
+    pub facing: Direction,
+    pub reversed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CubeNet {
+    size: usize,
+    faces: Vec<Face>,
+    edges: Vec<[Edge; 4]>,
+}
+
+impl CubeNet {
+    // Folds the cells for which `is_face` holds into a cube, working out
+    // which edges meet from the net alone.
+    pub fn fold(
+        height: usize,
+        width: usize,
+        is_face: impl Fn(usize, usize) -> bool,
+    ) -> Result<Self, CubeError> {
+        let area = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .filter(|&(row, col)| is_face(row, col))
+            .count();
+
+        let size = (1..=area).find(|size| 6 * size * size >= area).unwrap_or(0);
+        if size == 0 || 6 * size * size != area {
+            return Err(CubeError::InvalidArea(area));
+        }
+
+        let mut blocks = vec![];
+        for block_row in 0..height.div_ceil(size) {
+            for block_col in 0..width.div_ceil(size) {
+                let (row, col) = (block_row * size, block_col * size);
+                let present = (row..row + size)
+                    .flat_map(|row| (col..col + size).map(move |col| (row, col)))
+                    .filter(|&(row, col)| row < height && col < width && is_face(row, col))
+                    .count();
+
+                if present == size * size {
+                    blocks.push((block_row, block_col));
+                } else if present != 0 {
+                    return Err(CubeError::PartialFace(row, col));
+                }
+            }
+        }
+
+        let mut orientations = vec![None; blocks.len()];
+        orientations[0] = Some(Orientation {
+            normal: [0, 0, 1],
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+        });
+
+        let mut queue = VecDeque::from([0]);
+        while let Some(index) = queue.pop_front() {
+            let orientation = orientations[index].unwrap();
+            let (block_row, block_col) = blocks[index];
+
+            for direction in DIRECTIONS {
+                let (dr, dc) = direction.delta();
+                let neighbour = (
+                    block_row.wrapping_add_signed(dr),
+                    block_col.wrapping_add_signed(dc),
+                );
+
+                if let Some(next) = blocks.iter().position(|&block| block == neighbour) {
+                    if orientations[next].is_none() {
+                        orientations[next] = Some(orientation.fold(direction));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let faces = blocks
+            .iter()
+            .zip(orientations)
+            .map(|(&(block_row, block_col), orientation)| {
+                Some(Face {
+                    row: block_row * size,
+                    col: block_col * size,
+                    orientation: orientation?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(CubeError::NotConnected)?;
+
+        let edge = |face, direction| {
+            Self::edge(&faces, face, direction).ok_or(CubeError::OverlappingFaces)
+        };
+        let edges = faces
+            .iter()
+            .map(|face| {
+                Ok([
+                    edge(face, Direction::Right)?,
+                    edge(face, Direction::Down)?,
+                    edge(face, Direction::Left)?,
+                    edge(face, Direction::Up)?,
+                ])
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { size, faces, edges })
+    }
+
+    fn edge(faces: &[Face], from: &Face, direction: Direction) -> Option<Edge> {
+        let leaving = from.orientation.along(direction);
+        let entering = negate(from.orientation.normal);
+
+        let mut matching = faces
+            .iter()
+            .enumerate()
+            .filter(|(_, face)| face.orientation.normal == leaving);
+        let (index, face) = matching.next()?;
+        if matching.next().is_some() {
+            return None;
+        }
+
+        let facing = DIRECTIONS
+            .into_iter()
+            .find(|&facing| face.orientation.along(facing) == entering)?;
+
+        // Positions along an edge count in the direction to the right of
+        // travel, on both sides.
+        let reversed = from.orientation.along(direction.turn_right())
+            != face.orientation.along(facing.turn_right());
+
+        Some(Edge {
+            face: index,
+            facing,
+            reversed,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn faces(&self) -> &[Face] {
+        &self.faces
+    }
+
+    pub fn edge_from(&self, face: usize, direction: Direction) -> Edge {
+        self.edges[face][direction.value()]
+    }
+
+    pub fn face_at(&self, row: usize, col: usize) -> Option<usize> {
+        self.faces.iter().position(|face| {
+            (face.row..face.row + self.size).contains(&row)
+                && (face.col..face.col + self.size).contains(&col)
+        })
+    }
+
+    // Steps off the edge of the net from `row`, `col` in `facing`, returning
+    // the cell and facing on the other side of the cube's edge.
+    pub fn wrap(&self, row: usize, col: usize, facing: Direction) -> (usize, usize, Direction) {
+        let index = self
+            .face_at(row, col)
+            .expect("position should be on a face");
+        let face = self.faces[index];
+        let last = self.size - 1;
+
+        let (local_row, local_col) = (row - face.row, col - face.col);
+        let offset = match facing {
+            Direction::Right => local_row,
+            Direction::Down => last - local_col,
+            Direction::Left => last - local_row,
+            Direction::Up => local_col,
+        };
+
+        let edge = self.edge_from(index, facing);
+        let offset = if edge.reversed { last - offset } else { offset };
+
+        let (local_row, local_col) = match edge.facing {
+            Direction::Right => (offset, 0),
+            Direction::Down => (0, last - offset),
+            Direction::Left => (last - offset, last),
+            Direction::Up => (last, offset),
+        };
+
+        let face = self.faces[edge.face];
+        (face.row + local_row, face.col + local_col, edge.facing)
+    }
+}
+
+impl fmt::Display for CubeNet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, face) in self.faces.iter().enumerate() {
+            writeln!(
+                f,
+                "Face {} (rows {}-{}, columns {}-{}):",
+                index + 1,
+                face.row + 1,
+                face.row + self.size,
+                face.col + 1,
+                face.col + self.size
+            )?;
+
+            for direction in DIRECTIONS {
+                let edge = self.edge_from(index, direction);
+                writeln!(
+                    f,
+                    "  {} -> face {} facing {}{}",
+                    direction,
+                    edge.face + 1,
+                    edge.facing,
+                    if edge.reversed { ", reversed" } else { "" }
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(net: &[&str], size: usize) -> Result<CubeNet, CubeError> {
+        let width = net.iter().map(|line| line.len()).max().unwrap_or(0) * size;
+
+        CubeNet::fold(net.len() * size, width, |row, col| {
+            net[row / size].as_bytes().get(col / size) == Some(&b'#')
+        })
+    }
+
+    const NETS: [&[&str]; 4] = [
+        &["..#", "###", "..##"],
+        &[".##", ".#", "##", "#"],
+        &[".#", "###", ".#", ".#"],
+        &["##", ".###", "..#"],
+    ];
+
+    #[test]
+    fn test_example_edges() {
+        let cube = fold(NETS[0], 4).unwrap();
+
+        assert_eq!(cube.size(), 4);
+        assert_eq!(cube.face_at(5, 11), Some(3));
+        // Right off the middle row's last face comes down onto the bottom-right face.
+        assert_eq!(cube.wrap(5, 11, Direction::Right), (8, 14, Direction::Down));
+        // Down off the bottom-left face comes back up the left face.
+        assert_eq!(cube.wrap(11, 10, Direction::Down), (7, 1, Direction::Up));
+    }
+
+    #[test]
+    fn test_wrap_round_trip() {
+        for net in NETS {
+            let cube = fold(net, 3).unwrap();
+            let last = cube.size() - 1;
+
+            for face in cube.faces() {
+                for offset in 0..cube.size() {
+                    for (local_row, local_col, facing) in [
+                        (offset, last, Direction::Right),
+                        (last, offset, Direction::Down),
+                        (offset, 0, Direction::Left),
+                        (0, offset, Direction::Up),
+                    ] {
+                        let (row, col) = (face.row + local_row, face.col + local_col);
+                        let (next_row, next_col, next_facing) = cube.wrap(row, col, facing);
+
+                        assert_ne!(cube.face_at(next_row, next_col), cube.face_at(row, col));
+                        assert_eq!(
+                            cube.wrap(next_row, next_col, next_facing.opposite()),
+                            (row, col, facing.opposite())
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_nets() {
+        assert_eq!(fold(&["###", "###"], 2), Err(CubeError::OverlappingFaces));
+        assert_eq!(fold(&["###", "", "###"], 2), Err(CubeError::NotConnected));
+        assert_eq!(fold(&["#####"], 2), Err(CubeError::InvalidArea(20)));
+        assert_eq!(
+            CubeNet::fold(4, 7, |row, col| (row % 2..row % 2 + 6).contains(&col)),
+            Err(CubeError::PartialFace(0, 0))
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use alloc::collections::{btree_map::Entry, BTreeMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    // Number of whole cycles needed to reach `index`, and the earlier index with the same state
+    pub fn fold(&self, index: usize) -> (usize, usize) {
+        if index < self.start {
+            return (0, index);
+        }
+
+        let offset = index - self.start;
+        (offset / self.length, self.start + offset % self.length)
+    }
+}
+
+pub struct CycleDetector<K> {
+    seen: BTreeMap<K, usize>,
+}
+
+impl<K: Ord> CycleDetector<K> {
+    pub fn new() -> Self {
+        Self {
+            seen: BTreeMap::new(),
+        }
+    }
+
+    pub fn observe(&mut self, index: usize, key: K) -> Option<Cycle> {
+        match self.seen.entry(key) {
+            Entry::Occupied(entry) => Some(Cycle {
+                start: *entry.get(),
+                length: index - entry.get(),
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+                None
+            }
+        }
+    }
+}
+
+impl<K: Ord> Default for CycleDetector<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{vec, vec::Vec};
+
+    #[test]
+    fn test_observe() {
+        let mut detector = CycleDetector::new();
+        let sequence = [3, 1, 4, 1, 5];
+
+        let cycles: Vec<_> = sequence
+            .iter()
+            .enumerate()
+            .map(|(i, value)| detector.observe(i, *value))
+            .collect();
+
+        assert_eq!(
+            cycles,
+            vec![
+                None,
+                None,
+                None,
+                Some(Cycle {
+                    start: 1,
+                    length: 2
+                }),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        let cycle = Cycle {
+            start: 2,
+            length: 3,
+        };
+
+        assert_eq!(cycle.fold(1), (0, 1));
+        assert_eq!(cycle.fold(4), (0, 4));
+        assert_eq!(cycle.fold(5), (1, 2));
+        assert_eq!(cycle.fold(13), (3, 4));
+    }
+}
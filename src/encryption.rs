@@ -0,0 +1,112 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use sha2::Sha256;
+
+pub const KEY_VARIABLE: &str = "AOC_INPUT_KEY";
+pub const EXTENSION: &str = "enc";
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const ROUNDS: u32 = 100000;
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Aes256Gcm {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, ROUNDS, &mut key);
+
+    Aes256Gcm::new(&key.into())
+}
+
+// Layout: salt, then nonce, then the AES-256-GCM ciphertext.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Vec<u8> {
+    let mut salt = [0; SALT_LENGTH];
+    let mut nonce = [0; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher(passphrase, &salt)
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .expect("encrypting in memory cannot fail");
+
+    [&salt[..], &nonce, &ciphertext].concat()
+}
+
+pub fn decrypt(data: &[u8], passphrase: &str) -> io::Result<Vec<u8>> {
+    if data.len() < SALT_LENGTH + NONCE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "encrypted input is truncated",
+        ));
+    }
+
+    let (salt, rest) = data.split_at(SALT_LENGTH);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+
+    cipher(passphrase, salt)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "wrong key or corrupted encrypted input",
+            )
+        })
+}
+
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(EXTENSION);
+
+    path.into()
+}
+
+pub fn passphrase() -> io::Result<String> {
+    env::var(KEY_VARIABLE).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} must be set to read encrypted inputs", KEY_VARIABLE),
+        )
+    })
+}
+
+pub fn read_encrypted(path: &Path) -> io::Result<String> {
+    let plaintext = decrypt(&fs::read(encrypted_path(path))?, &passphrase()?)?;
+
+    String::from_utf8(plaintext).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn encrypt_file(path: &Path, passphrase: &str) -> io::Result<PathBuf> {
+    let encrypted = encrypted_path(path);
+    fs::write(&encrypted, encrypt(&fs::read(path)?, passphrase))?;
+
+    Ok(encrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let encrypted = encrypt(b"1000\n2000\n", "hunter2");
+
+        assert_ne!(&encrypted[SALT_LENGTH + NONCE_LENGTH..], b"1000\n2000\n");
+        assert_eq!(decrypt(&encrypted, "hunter2").unwrap(), b"1000\n2000\n");
+        assert!(decrypt(&encrypted, "hunter3").is_err());
+        assert!(decrypt(&encrypted[..10], "hunter2").is_err());
+    }
+
+    #[test]
+    fn test_encrypted_path() {
+        assert_eq!(
+            encrypted_path(Path::new("inputs/day1.txt")),
+            PathBuf::from("inputs/day1.txt.enc")
+        );
+    }
+}
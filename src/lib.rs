@@ -1,17 +1,94 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod animation;
+#[cfg(feature = "cargo-aoc")]
+pub mod cargo_aoc;
+#[cfg(feature = "std")]
+pub mod cli;
+pub mod cube;
+pub mod cycle;
+#[cfg(feature = "encrypted-inputs")]
+pub mod encryption;
+pub mod mixing;
+#[cfg(feature = "std")]
+pub mod progress;
+pub mod puzzles;
+pub mod render;
+pub mod rng;
+pub mod rope;
+pub mod sensor;
+pub mod snafu;
+#[cfg(feature = "std")]
+pub mod timing;
+pub mod tower;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+
+// The solvers included by `cargo_aoc` refer to this crate by name.
+#[cfg(feature = "cargo-aoc")]
+extern crate self as aoc;
+
+use alloc::{borrow::ToOwned, collections::BinaryHeap, string::String, vec::Vec};
+use core::cmp::Reverse;
+
+#[cfg(feature = "std")]
 use std::{
     fs::File,
     io::{self, BufRead, BufReader},
     path::Path,
 };
 
+#[cfg(feature = "std")]
 pub fn read_lines(path: &str) -> io::Result<Vec<String>> {
     let path = Path::new(path);
+
+    // Inputs can be committed encrypted, as `<path>.enc`, instead.
+    #[cfg(feature = "encrypted-inputs")]
+    if !path.exists() && encryption::encrypted_path(path).exists() {
+        return Ok(to_lines(&encryption::read_encrypted(path)?));
+    }
+
     let file = File::open(path)?;
     let reader = BufReader::new(file);
 
-    return reader.lines().collect();
+    reader.lines().collect()
 }
 
 pub fn to_lines(data: &str) -> Vec<String> {
     data.lines().map(|s| s.to_owned()).collect()
 }
+
+pub fn top_k<T: Ord>(items: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+
+    for item in items {
+        heap.push(Reverse(item));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(item)| item)
+        .collect()
+}
+
+#[cfg(feature = "cargo-aoc")]
+aoc_runner_derive::aoc_lib! { year = 2022 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn test_top_k() {
+        assert_eq!(top_k([3, 1, 4, 1, 5, 9, 2, 6], 3), vec![9, 6, 5]);
+        assert_eq!(top_k([3, 1], 3), vec![3, 1]);
+        assert_eq!(top_k([3, 1], 0), Vec::<i32>::new());
+    }
+}
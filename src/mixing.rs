@@ -0,0 +1,343 @@
+use alloc::{vec, vec::Vec};
+
+use crate::rng::XorShift;
+
+// Implicit treap: a balanced tree ordered by position in the sequence, where
+// each node knows its subtree size and parent so positions take O(log n).
+struct OrderStatisticTree {
+    nodes: Vec<TreeNode>,
+    root: Option<usize>,
+}
+
+struct TreeNode {
+    left: Option<usize>,
+    right: Option<usize>,
+    parent: Option<usize>,
+    size: usize,
+    priority: u64,
+}
+
+impl OrderStatisticTree {
+    fn new(n: usize) -> Self {
+        // Any well-mixed priorities will do, so use a fixed xorshift sequence.
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        let nodes = (0..n)
+            .map(|_| TreeNode {
+                left: None,
+                right: None,
+                parent: None,
+                size: 1,
+                priority: rng.next_u64(),
+            })
+            .collect();
+
+        let mut tree = Self { nodes, root: None };
+        for node in 0..n {
+            tree.root = tree.merge(tree.root, Some(node));
+        }
+
+        tree
+    }
+
+    fn size(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |node| self.nodes[node].size)
+    }
+
+    fn update(&mut self, node: usize) {
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+
+        self.nodes[node].size = 1 + self.size(left) + self.size(right);
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    fn merge(&mut self, left: Option<usize>, right: Option<usize>) -> Option<usize> {
+        let (Some(l), Some(r)) = (left, right) else {
+            let root = left.or(right)?;
+            self.nodes[root].parent = None;
+            return Some(root);
+        };
+
+        let root = if self.nodes[l].priority > self.nodes[r].priority {
+            self.nodes[l].right = self.merge(self.nodes[l].right, Some(r));
+            l
+        } else {
+            self.nodes[r].left = self.merge(Some(l), self.nodes[r].left);
+            r
+        };
+
+        self.update(root);
+        self.nodes[root].parent = None;
+
+        Some(root)
+    }
+
+    // Splits into the first `k` nodes and the rest.
+    fn split(&mut self, node: Option<usize>, k: usize) -> (Option<usize>, Option<usize>) {
+        let Some(node) = node else {
+            return (None, None);
+        };
+
+        let left_size = self.size(self.nodes[node].left);
+
+        let (left, right) = if k <= left_size {
+            let (left, rest) = self.split(self.nodes[node].left, k);
+            self.nodes[node].left = rest;
+            (left, Some(node))
+        } else {
+            let (rest, right) = self.split(self.nodes[node].right, k - left_size - 1);
+            self.nodes[node].right = rest;
+            (Some(node), right)
+        };
+
+        self.update(node);
+        for root in [left, right].into_iter().flatten() {
+            self.nodes[root].parent = None;
+        }
+
+        (left, right)
+    }
+
+    fn position(&self, node: usize) -> usize {
+        let mut position = self.size(self.nodes[node].left);
+        let mut current = node;
+
+        while let Some(parent) = self.nodes[current].parent {
+            if self.nodes[parent].right == Some(current) {
+                position += self.size(self.nodes[parent].left) + 1;
+            }
+            current = parent;
+        }
+
+        position
+    }
+
+    fn move_to(&mut self, node: usize, new_position: usize) {
+        let position = self.position(node);
+
+        let (before, rest) = self.split(self.root, position);
+        let (_, after) = self.split(rest, 1);
+        let root = self.merge(before, after);
+
+        let (before, after) = self.split(root, new_position);
+        let root = self.merge(before, Some(node));
+        self.root = self.merge(root, after);
+    }
+
+    fn in_order(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![];
+        let mut current = self.root;
+
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = self.nodes[node].left;
+            }
+
+            let node = stack.pop().unwrap();
+            order.push(node);
+            current = self.nodes[node].right;
+        }
+
+        order
+    }
+}
+
+pub const GROVE_OFFSETS: [usize; 3] = [1000, 2000, 3000];
+
+pub struct Mixer {
+    values: Vec<i64>,
+    tree: OrderStatisticTree,
+    rounds: usize,
+}
+
+impl Mixer {
+    pub fn new(values: &[i64], key: i64) -> Self {
+        Self {
+            values: values.iter().map(|value| value * key).collect(),
+            tree: OrderStatisticTree::new(values.len()),
+            rounds: 0,
+        }
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    pub fn mix_round(&mut self) {
+        self.rounds += 1;
+
+        let n = self.values.len();
+        if n < 2 {
+            return;
+        }
+
+        for (node, value) in self.values.iter().enumerate() {
+            let position = self.tree.position(node);
+            let new_position = ((position as i64) + value).rem_euclid(n as i64 - 1) as usize;
+
+            self.tree.move_to(node, new_position);
+        }
+    }
+
+    // Returns the original index of each value, in mixed order.
+    pub fn permutation(&self) -> Vec<usize> {
+        self.tree.in_order()
+    }
+
+    pub fn values(&self) -> Vec<i64> {
+        self.permutation()
+            .into_iter()
+            .map(|index| self.values[index])
+            .collect()
+    }
+}
+
+pub fn mix_permutation(values: &[i64], key: i64, rounds: usize) -> Vec<usize> {
+    let mut mixer = Mixer::new(values, key);
+
+    for _ in 0..rounds {
+        mixer.mix_round();
+    }
+
+    mixer.permutation()
+}
+
+pub fn mix(values: &[i64], key: i64, rounds: usize) -> Vec<i64> {
+    mix_permutation(values, key, rounds)
+        .into_iter()
+        .map(|index| values[index] * key)
+        .collect()
+}
+
+pub fn grove_coordinates(mixed: &[i64], offsets: &[usize]) -> Option<i64> {
+    let zero_index = mixed.iter().position(|x| *x == 0)?;
+
+    Some(
+        offsets
+            .iter()
+            .map(|offset| mixed[(zero_index + offset) % mixed.len()])
+            .sum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [i64; 7] = [1, 2, -3, 3, -2, 0, 4];
+
+    fn reference_mix(values: &[i64], key: i64, rounds: usize) -> Vec<i64> {
+        let n = values.len();
+        let mut mixed: Vec<_> = values.iter().map(|value| value * key).enumerate().collect();
+
+        for _ in 0..rounds {
+            for index in 0..n {
+                let position = mixed.iter().position(|(i, _)| *i == index).unwrap();
+                let (i, value) = mixed.remove(position);
+
+                let new_position = (position as i64 + value).rem_euclid(n as i64 - 1) as usize;
+                mixed.insert(new_position, (i, value));
+            }
+        }
+
+        mixed.into_iter().map(|(_, value)| value).collect()
+    }
+
+    fn random_values(rng: &mut XorShift, n: usize, spread: i64) -> Vec<i64> {
+        (0..n)
+            .map(|_| rng.below(2 * spread as u64 + 1) as i64 - spread)
+            .collect()
+    }
+
+    #[test]
+    fn test_mix_1_1() {
+        assert_eq!(mix(&EXAMPLE, 1, 1), vec![-2, 1, 2, -3, 4, 0, 3]);
+    }
+
+    #[test]
+    fn test_mix_811589153_10() {
+        let expected_result = vec![
+            0,
+            -2434767459,
+            1623178306,
+            3246356612,
+            -1623178306,
+            2434767459,
+            811589153,
+        ];
+
+        assert_eq!(mix(&EXAMPLE, 811589153, 10), expected_result);
+    }
+
+    #[test]
+    fn test_mix_permutation() {
+        assert_eq!(mix_permutation(&EXAMPLE, 1, 1), vec![4, 0, 1, 2, 6, 5, 3]);
+        assert_eq!(mix_permutation(&[5], 1, 3), vec![0]);
+    }
+
+    #[test]
+    fn test_mixer_rounds() {
+        let mut mixer = Mixer::new(&EXAMPLE, 811589153);
+
+        mixer.mix_round();
+        assert_eq!(
+            mixer.values(),
+            vec![
+                0,
+                -2434767459,
+                3246356612,
+                -1623178306,
+                2434767459,
+                1623178306,
+                811589153
+            ]
+        );
+
+        mixer.mix_round();
+        assert_eq!(
+            mixer.values(),
+            vec![
+                0,
+                2434767459,
+                1623178306,
+                3246356612,
+                -2434767459,
+                -1623178306,
+                811589153
+            ]
+        );
+        assert_eq!(mixer.rounds(), 2);
+    }
+
+    #[test]
+    fn test_mix_matches_reference() {
+        let mut rng = XorShift::new(0x853c49e6748fea9b);
+
+        for n in [2, 3, 4, 7, 16, 100, 301] {
+            // A small spread gives lots of duplicates, a large one lots of wrapping.
+            for spread in [2, n as i64, 10000] {
+                for (key, rounds) in [(1, 1), (-7, 2), (811589153, 5)] {
+                    let values = random_values(&mut rng, n, spread);
+
+                    assert_eq!(
+                        mix(&values, key, rounds),
+                        reference_mix(&values, key, rounds),
+                        "{values:?} with key {key} and {rounds} rounds"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_grove_coordinates() {
+        let mixed = [1, 2, -3, 4, 0, 3, -2];
+
+        assert_eq!(grove_coordinates(&mixed, &GROVE_OFFSETS), Some(3));
+        assert_eq!(grove_coordinates(&mixed, &[1, 2]), Some(1));
+        assert_eq!(grove_coordinates(&[1, 2], &GROVE_OFFSETS), None);
+    }
+}
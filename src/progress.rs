@@ -0,0 +1,86 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+pub struct Progress {
+    total: usize,
+    done: AtomicUsize,
+    start: Instant,
+    quiet: bool,
+}
+
+impl Progress {
+    pub fn new(total: usize, quiet: bool) -> Self {
+        Self {
+            total,
+            done: AtomicUsize::new(0),
+            start: Instant::now(),
+            quiet,
+        }
+    }
+
+    pub fn finish_one(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn eta(&self) -> Option<Duration> {
+        estimate_remaining(
+            self.start.elapsed(),
+            self.done.load(Ordering::Relaxed),
+            self.total,
+        )
+    }
+
+    pub fn report(&self, message: &str) {
+        if self.quiet {
+            return;
+        }
+
+        let done = self.done.load(Ordering::Relaxed);
+        let eta = match self.eta() {
+            Some(eta) => format!("{:.1}s", eta.as_secs_f64()),
+            None => "unknown".to_owned(),
+        };
+
+        eprintln!("[{}/{}] {} (ETA {})", done, self.total, message, eta);
+    }
+}
+
+// Assumes the remaining items take as long as the finished ones did on average.
+pub fn estimate_remaining(elapsed: Duration, done: usize, total: usize) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+
+    let remaining = total.saturating_sub(done) as u32;
+
+    Some(elapsed / done as u32 * remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_remaining() {
+        let elapsed = Duration::from_secs(10);
+
+        assert_eq!(estimate_remaining(elapsed, 0, 4), None);
+        assert_eq!(
+            estimate_remaining(elapsed, 2, 4),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(estimate_remaining(elapsed, 4, 4), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_finish_one() {
+        let progress = Progress::new(3, true);
+
+        assert_eq!(progress.eta(), None);
+
+        progress.finish_one();
+        assert!(progress.eta().is_some());
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::{format, string::String};
+
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+pub const TITLES: [&str; 25] = [
+    "Calorie Counting",
+    "Rock Paper Scissors",
+    "Rucksack Reorganization",
+    "Camp Cleanup",
+    "Supply Stacks",
+    "Tuning Trouble",
+    "No Space Left On Device",
+    "Treetop Tree House",
+    "Rope Bridge",
+    "Cathode-Ray Tube",
+    "Monkey in the Middle",
+    "Hill Climbing Algorithm",
+    "Distress Signal",
+    "Regolith Reservoir",
+    "Beacon Exclusion Zone",
+    "Proboscidea Volcanium",
+    "Pyroclastic Flow",
+    "Boiling Boulders",
+    "Not Enough Minerals",
+    "Grove Positioning System",
+    "Monkey Math",
+    "Monkey Map",
+    "Unstable Diffusion",
+    "Blizzard Basin",
+    "Full of Hot Air",
+];
+
+pub fn title(day: usize) -> Option<&'static str> {
+    TITLES.get(day.checked_sub(1)?).copied()
+}
+
+pub fn part_label(day: usize, part: usize) -> String {
+    match title(day) {
+        Some(title) => format!("Day {}: {} — Part {}", day, title, part),
+        None => format!("Day {} — Part {}", day, part),
+    }
+}
+
+pub fn solver_path(day: usize) -> String {
+    format!("src/bin/day{}.rs", day)
+}
+
+pub fn input_path(day: usize) -> String {
+    format!("inputs/day{}.txt", day)
+}
+
+// Accepted answers, one per line, recorded once the site confirms them.
+pub fn answers_path(day: usize) -> String {
+    format!("inputs/day{}.answers", day)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+    pub day: usize,
+    pub title: &'static str,
+    pub implemented: bool,
+    pub input: bool,
+    pub verified_answers: usize,
+}
+
+#[cfg(feature = "std")]
+pub fn status(root: &Path, day: usize) -> Option<Status> {
+    let input = root.join(input_path(day));
+    let encrypted_input = root.join(format!("{}.enc", input_path(day)));
+
+    let verified_answers = fs::read_to_string(root.join(answers_path(day)))
+        .map(|answers| answers.lines().filter(|line| !line.is_empty()).count())
+        .unwrap_or(0);
+
+    Some(Status {
+        day,
+        title: title(day)?,
+        implemented: root.join(solver_path(day)).exists(),
+        input: input.exists() || encrypted_input.exists(),
+        verified_answers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_label() {
+        assert_eq!(part_label(16, 1), "Day 16: Proboscidea Volcanium — Part 1");
+        assert_eq!(part_label(26, 2), "Day 26 — Part 2");
+        assert_eq!(title(0), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_status() {
+        let day1 = status(Path::new(env!("CARGO_MANIFEST_DIR")), 1).unwrap();
+
+        assert_eq!(day1.title, "Calorie Counting");
+        assert!(day1.implemented);
+        assert!(!status(Path::new("/nonexistent"), 1).unwrap().implemented);
+        assert_eq!(status(Path::new("/nonexistent"), 26), None);
+    }
+}
@@ -0,0 +1,250 @@
+use alloc::{borrow::ToOwned, format, string::String, vec, vec::Vec};
+use core::{fmt::Write, str::FromStr};
+
+#[cfg(feature = "std")]
+use std::{fs, io, path::Path};
+
+#[derive(Debug)]
+pub struct Svg {
+    left: i64,
+    top: i64,
+    width: i64,
+    height: i64,
+    elements: Vec<String>,
+}
+
+impl Svg {
+    pub fn new(left: i64, top: i64, width: i64, height: i64) -> Self {
+        Self {
+            left,
+            top,
+            width,
+            height,
+            elements: vec![],
+        }
+    }
+
+    pub fn rect(&mut self, x: i64, y: i64, width: i64, height: i64, fill: &str) {
+        self.elements.push(format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x, y, width, height, fill
+        ));
+    }
+
+    pub fn polygon(&mut self, points: &[(i64, i64)], fill: &str, opacity: f64) {
+        let points: Vec<_> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+
+        self.elements.push(format!(
+            r#"<polygon points="{}" fill="{}" fill-opacity="{}"/>"#,
+            points.join(" "),
+            fill,
+            opacity
+        ));
+    }
+
+    pub fn circle(&mut self, x: i64, y: i64, radius: i64, fill: &str) {
+        self.elements.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            x, y, radius, fill
+        ));
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        writeln!(
+            text,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" shape-rendering="crispEdges">"#,
+            self.left, self.top, self.width, self.height
+        )
+        .unwrap();
+        for element in &self.elements {
+            writeln!(text, "  {}", element).unwrap();
+        }
+        text.push_str("</svg>\n");
+
+        text
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Dot {
+    statements: Vec<String>,
+}
+
+impl Dot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node(&mut self, id: &str, label: &str, fill: Option<&str>) {
+        let style = match fill {
+            Some(fill) => format!(r#", style=filled, fillcolor="{}""#, fill),
+            None => String::new(),
+        };
+
+        self.statements
+            .push(format!(r#""{}" [label="{}"{}];"#, id, label, style));
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str) {
+        self.statements.push(format!(r#""{}" -> "{}";"#, from, to));
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("digraph {\n");
+
+        for statement in &self.statements {
+            writeln!(text, "  {}", statement).unwrap();
+        }
+        text.push_str("}\n");
+
+        text
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Packing {
+    Braille,
+    HalfBlock,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidPacking(pub String);
+
+impl FromStr for Packing {
+    type Err = InvalidPacking;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "braille" => Ok(Self::Braille),
+            "half-block" => Ok(Self::HalfBlock),
+            _ => Err(InvalidPacking(s.to_owned())),
+        }
+    }
+}
+
+// Braille dots are numbered down the left column first, then the right one,
+// with the bottom row added last.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+impl Packing {
+    pub fn cell_size(self) -> (usize, usize) {
+        match self {
+            Self::Braille => (2, 4),
+            Self::HalfBlock => (1, 2),
+        }
+    }
+
+    fn glyph(self, is_set: impl Fn(usize, usize) -> bool) -> char {
+        match self {
+            Self::Braille => {
+                let mut dots = 0;
+                for (dy, row) in BRAILLE_DOTS.iter().enumerate() {
+                    for (dx, dot) in row.iter().enumerate() {
+                        if is_set(dx, dy) {
+                            dots |= dot;
+                        }
+                    }
+                }
+                char::from_u32(0x2800 + dots).unwrap()
+            }
+            Self::HalfBlock => match (is_set(0, 0), is_set(0, 1)) {
+                (false, false) => ' ',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (true, true) => '█',
+            },
+        }
+    }
+
+    // Packs a `width` by `height` grid of on/off cells into lines of text,
+    // several cells per character. Cells outside the grid are off.
+    pub fn pack(
+        self,
+        width: usize,
+        height: usize,
+        is_set: impl Fn(usize, usize) -> bool,
+    ) -> String {
+        let (cell_width, cell_height) = self.cell_size();
+
+        let mut text = String::new();
+        for y in (0..height).step_by(cell_height) {
+            for x in (0..width).step_by(cell_width) {
+                text.push(
+                    self.glyph(|dx, dy| {
+                        x + dx < width && y + dy < height && is_set(x + dx, y + dy)
+                    }),
+                );
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_text() {
+        let mut svg = Svg::new(-1, 0, 4, 3);
+        svg.rect(0, 0, 1, 1, "black");
+        svg.polygon(&[(0, 0), (1, 1), (0, 2)], "red", 0.5);
+        svg.circle(2, 2, 1, "blue");
+
+        assert_eq!(
+            svg.to_text(),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 0 4 3" shape-rendering="crispEdges">
+  <rect x="0" y="0" width="1" height="1" fill="black"/>
+  <polygon points="0,0 1,1 0,2" fill="red" fill-opacity="0.5"/>
+  <circle cx="2" cy="2" r="1" fill="blue"/>
+</svg>
+"#
+        );
+    }
+
+    #[test]
+    fn test_dot_to_text() {
+        let mut dot = Dot::new();
+        dot.node("a", "a: b + c", Some("gold"));
+        dot.node("b", "b: 1", None);
+        dot.edge("a", "b");
+
+        assert_eq!(
+            dot.to_text(),
+            r#"digraph {
+  "a" [label="a: b + c", style=filled, fillcolor="gold"];
+  "b" [label="b: 1"];
+  "a" -> "b";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_pack() {
+        let grid = ["#.#", "##.", "..#"];
+        let is_set = |x: usize, y: usize| grid[y].as_bytes()[x] == b'#';
+
+        assert_eq!(Packing::HalfBlock.pack(3, 3, is_set), "█▄▀\n  ▀\n");
+        assert_eq!(Packing::Braille.pack(3, 3, is_set), "\u{2813}\u{2805}\n");
+        assert_eq!("braille".parse(), Ok(Packing::Braille));
+        assert_eq!(
+            "dots".parse::<Packing>(),
+            Err(InvalidPacking("dots".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// A xorshift64 generator: reproducible pseudo-random numbers for benchmarks,
+// tree priorities and randomized tests, without pulling in a dependency.
+#[derive(Debug, Clone)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    // Zero is a fixed point of the generator, so it can't be used as a seed.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift seed must be non-zero");
+
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        self.state
+    }
+
+    // Slightly biased towards small values, which doesn't matter for bounds
+    // far below 2^64.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence() {
+        let mut rng = XorShift::new(1);
+
+        assert_eq!(rng.next_u64(), 1082269761);
+        assert_eq!(rng.next_u64(), 1152992998833853505);
+
+        let mut rng = XorShift::new(0x9e3779b97f4a7c15);
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+    }
+}
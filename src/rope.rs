@@ -0,0 +1,515 @@
+use alloc::{vec, vec::Vec};
+use core::{num::ParseIntError, str::FromStr};
+
+#[derive(Debug)]
+pub enum RopeError {
+    ParseIntError(ParseIntError),
+    InvalidDirection,
+    InvalidStepFormat,
+}
+
+impl From<ParseIntError> for RopeError {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl FromStr for Direction {
+    type Err = RopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" => Ok(Self::Up),
+            "R" => Ok(Self::Right),
+            "D" => Ok(Self::Down),
+            "L" => Ok(Self::Left),
+            _ => Err(Self::Err::InvalidDirection),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step(pub Direction, pub usize);
+
+impl FromStr for Step {
+    type Err = RopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let v: Vec<_> = s.split(" ").collect();
+        match v.as_slice() {
+            [direction, number] => Ok(Step(direction.parse()?, number.parse()?)),
+            _ => Err(Self::Err::InvalidStepFormat),
+        }
+    }
+}
+
+// `x` grows to the right and `y` upwards, starting from the origin.
+pub type Position = (isize, isize);
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Displacement {
+    Center,
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl From<Direction> for Displacement {
+    fn from(direction: Direction) -> Self {
+        use Direction::*;
+        use Displacement::*;
+
+        match direction {
+            Up => North,
+            Right => East,
+            Down => South,
+            Left => West,
+        }
+    }
+}
+
+impl From<Displacement> for (isize, isize) {
+    fn from(displacement: Displacement) -> Self {
+        use Displacement::*;
+
+        match displacement {
+            Center => (0, 0),
+            North => (0, 1),
+            NorthEast => (1, 1),
+            East => (1, 0),
+            SouthEast => (1, -1),
+            South => (0, -1),
+            SouthWest => (-1, -1),
+            West => (-1, 0),
+            NorthWest => (-1, 1),
+        }
+    }
+}
+
+impl From<(isize, isize)> for Displacement {
+    fn from((x, y): (isize, isize)) -> Self {
+        use core::cmp::Ordering::*;
+        use Displacement::*;
+
+        match x.abs().cmp(&y.abs()) {
+            Less => {
+                if y > 0 {
+                    North
+                } else {
+                    // |x| < |y| && y == 0 is impossible
+                    South
+                }
+            }
+            Equal => {
+                if x > 0 {
+                    if y > 0 {
+                        NorthEast
+                    } else {
+                        // |x| == |y| && x > 0 ==> y != 0
+                        SouthEast
+                    }
+                } else if x < 0 {
+                    if y > 0 {
+                        NorthWest
+                    } else {
+                        // |x| == |y| && x < 0 ==> y != 0
+                        SouthWest
+                    }
+                } else {
+                    // |x| == |y| && x == 0 ==> y == 0
+                    Center
+                }
+            }
+            Greater => {
+                if x > 0 {
+                    East
+                } else {
+                    // |x| > |y| && x == 0 is impossible
+                    West
+                }
+            }
+        }
+    }
+}
+
+impl Displacement {
+    fn into_position(self, (x, y): &Position) -> Position {
+        let (dx, dy): (isize, isize) = self.into();
+
+        (x + dx, y + dy)
+    }
+}
+
+fn new_tail_displacement(tail_displacement: Displacement, motion: Displacement) -> Displacement {
+    let (x, y): (isize, isize) = tail_displacement.into();
+    let (dx, dy): (isize, isize) = motion.into();
+
+    Displacement::from((x - dx, y - dy))
+}
+
+#[derive(Clone)]
+struct ExtendedRopeState {
+    head_position: Position,
+    knot_displacements: Vec<Displacement>,
+}
+
+impl ExtendedRopeState {
+    fn new(n_knots: usize) -> Self {
+        Self {
+            head_position: (0, 0),
+            knot_displacements: vec![Displacement::Center; n_knots],
+        }
+    }
+
+    fn knot_positions(&self) -> Vec<Position> {
+        let mut positions = vec![self.head_position];
+        for displacement in &self.knot_displacements {
+            positions.push(displacement.into_position(positions.last().unwrap()));
+        }
+
+        positions
+    }
+
+    fn tail_position(&self) -> Position {
+        self.knot_displacements
+            .iter()
+            .fold(self.head_position, |position, displacement| {
+                displacement.into_position(&position)
+            })
+    }
+
+    // Whether every knot is right behind the one before it, so that moving
+    // the head along `motion` just moves the whole rope along with it.
+    fn is_straight(&self, motion: Displacement) -> bool {
+        let (dx, dy): (isize, isize) = motion.into();
+        let behind = Displacement::from((-dx, -dy));
+
+        self.knot_displacements
+            .iter()
+            .all(|&displacement| displacement == behind)
+    }
+
+    fn translate(&mut self, motion: Displacement, distance: usize) {
+        let (dx, dy): (isize, isize) = motion.into();
+        let (x, y) = self.head_position;
+
+        self.head_position = (x + dx * distance as isize, y + dy * distance as isize);
+    }
+
+    fn apply_motion(mut self, mut motion: Displacement) -> Self {
+        let mut prev_anchor_position = self.head_position;
+        let mut new_anchor_position = motion.into_position(&prev_anchor_position);
+        self.head_position = new_anchor_position;
+
+        for displacement in self.knot_displacements.iter_mut() {
+            prev_anchor_position = displacement.into_position(&prev_anchor_position);
+            *displacement = new_tail_displacement(*displacement, motion);
+            new_anchor_position = displacement.into_position(&new_anchor_position);
+
+            let (prev_x, prev_y) = prev_anchor_position;
+            let (new_x, new_y) = new_anchor_position;
+
+            motion = Displacement::from((new_x - prev_x, new_y - prev_y));
+        }
+
+        self
+    }
+}
+
+// A straight line of `length` positions leading away from `start`, which
+// itself isn't included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub start: Position,
+    pub direction: Direction,
+    pub length: usize,
+}
+
+impl Run {
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let (dx, dy): (isize, isize) = Displacement::from(self.direction).into();
+        let (x, y) = self.start;
+
+        (1..=self.length as isize).map(move |i| (x + dx * i, y + dy * i))
+    }
+}
+
+// Where the tail went during one step: one position per unit motion while
+// the rope straightens out behind the head, then a run for the rest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TailTrail {
+    pub positions: Vec<Position>,
+    pub run: Option<Run>,
+}
+
+// Moves a rope of `knots` knots, head included, through a series of steps.
+// Each item holds every knot's position, head first, after one unit motion.
+pub struct RopeSimulation<I> {
+    steps: I,
+    current: Option<Step>,
+    rope: ExtendedRopeState,
+}
+
+impl<I: Iterator<Item = Step>> RopeSimulation<I> {
+    pub fn new(knots: usize, steps: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            steps: steps.into_iter(),
+            current: None,
+            // The head always counts as a knot.
+            rope: ExtendedRopeState::new(knots.saturating_sub(1)),
+        }
+    }
+
+    // Where the knots are now, before the next unit motion.
+    pub fn knot_positions(&self) -> Vec<Position> {
+        self.rope.knot_positions()
+    }
+
+    // Finishes the current step, or takes the next one, in a single jump
+    // once the rope is straight, so long steps cost as much as short ones.
+    pub fn next_trail(&mut self) -> Option<TailTrail> {
+        let Step(direction, mut remaining) = match self.current.take() {
+            Some(step) if step.1 > 0 => step,
+            _ => self.steps.next()?,
+        };
+        let motion = Displacement::from(direction);
+
+        let mut positions = vec![];
+        while remaining > 0 && !self.rope.is_straight(motion) {
+            self.rope = self.rope.clone().apply_motion(motion);
+            positions.push(self.rope.tail_position());
+            remaining -= 1;
+        }
+
+        let run = (remaining > 0).then(|| {
+            let start = self.rope.tail_position();
+            self.rope.translate(motion, remaining);
+
+            Run {
+                start,
+                direction,
+                length: remaining,
+            }
+        });
+
+        Some(TailTrail { positions, run })
+    }
+}
+
+impl<I: Iterator<Item = Step>> Iterator for RopeSimulation<I> {
+    type Item = Vec<Position>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match &mut self.current {
+                Some(Step(direction, remaining)) if *remaining > 0 => {
+                    *remaining -= 1;
+
+                    let motion = Displacement::from(*direction);
+                    self.rope = self.rope.clone().apply_motion(motion);
+
+                    return Some(self.rope.knot_positions());
+                }
+                _ => self.current = Some(self.steps.next()?),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShift;
+    use Displacement::*;
+
+    const DISPLACEMENTS: [Displacement; 9] = [
+        Center, North, NorthEast, East, SouthEast, South, SouthWest, West, NorthWest,
+    ];
+
+    // The two-knot rope on its own, to check the extended one against.
+    #[derive(Clone)]
+    struct RopeState {
+        head_position: Position,
+        tail_displacement: Displacement,
+    }
+
+    impl RopeState {
+        fn new() -> Self {
+            Self {
+                head_position: (0, 0),
+                tail_displacement: Displacement::Center,
+            }
+        }
+
+        fn tail_position(&self) -> Position {
+            self.tail_displacement.into_position(&self.head_position)
+        }
+
+        fn apply_motion(&self, motion: Displacement) -> Self {
+            let head_position = motion.into_position(&self.head_position);
+            let tail_displacement = new_tail_displacement(self.tail_displacement, motion);
+
+            Self {
+                head_position,
+                tail_displacement,
+            }
+        }
+    }
+
+    #[test]
+    fn test_displacement_from_into_eq() {
+        for displacement in DISPLACEMENTS {
+            let (x, y) = displacement.into();
+            assert_eq!(Displacement::from((x, y)), displacement);
+        }
+    }
+
+    #[test]
+    fn test_displacement_from_twice_into_eq() {
+        for displacement in DISPLACEMENTS {
+            let (x, y): (isize, isize) = displacement.into();
+            assert_eq!(Displacement::from((2 * x, 2 * y)), displacement);
+        }
+    }
+
+    #[test]
+    fn test_displacement_from() {
+        assert_eq!(Displacement::from((1, 2)), North);
+        assert_eq!(Displacement::from((-1, 2)), North);
+
+        assert_eq!(Displacement::from((-1, -2)), South);
+        assert_eq!(Displacement::from((1, -2)), South);
+
+        assert_eq!(Displacement::from((2, 1)), East);
+        assert_eq!(Displacement::from((2, -1)), East);
+
+        assert_eq!(Displacement::from((-2, -1)), West);
+        assert_eq!(Displacement::from((-2, 1)), West);
+    }
+
+    #[test]
+    fn test_extended_rope_1_knot_tail_position() {
+        let rope_state = RopeState::new();
+        let extended_rope_state = ExtendedRopeState::new(1);
+
+        assert_eq!(
+            rope_state.tail_position(),
+            extended_rope_state.tail_position()
+        );
+    }
+
+    #[test]
+    fn test_extended_rope_1_knot_apply_motion() {
+        let rope_state = RopeState::new();
+        let extended_rope_state = ExtendedRopeState::new(1);
+
+        for motion in DISPLACEMENTS {
+            let new_rope_state = rope_state.apply_motion(motion);
+            let new_extended_rope_state = extended_rope_state.clone().apply_motion(motion);
+
+            assert_eq!(
+                new_rope_state.tail_position(),
+                new_extended_rope_state.tail_position()
+            );
+        }
+    }
+
+    #[test]
+    fn test_rope_simulation() {
+        let steps = [
+            Step(Direction::Right, 2),
+            Step(Direction::Up, 0),
+            Step(Direction::Up, 2),
+        ];
+        let mut simulation = RopeSimulation::new(3, steps);
+
+        assert_eq!(simulation.knot_positions(), vec![(0, 0); 3]);
+        assert_eq!(
+            simulation.by_ref().collect::<Vec<_>>(),
+            vec![
+                vec![(1, 0), (0, 0), (0, 0)],
+                vec![(2, 0), (1, 0), (0, 0)],
+                vec![(2, 1), (1, 0), (0, 0)],
+                vec![(2, 2), (2, 1), (1, 1)],
+            ]
+        );
+        assert_eq!(simulation.knot_positions(), vec![(2, 2), (2, 1), (1, 1)]);
+        assert_eq!(RopeSimulation::new(0, steps).next(), Some(vec![(1, 0)]));
+    }
+
+    fn random_steps(rng: &mut XorShift, n: usize) -> Vec<Step> {
+        const DIRECTIONS: [Direction; 4] = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+
+        (0..n)
+            .map(|_| {
+                let value = rng.next_u64();
+
+                Step(DIRECTIONS[(value % 4) as usize], (value >> 2) as usize % 30)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_next_trail() {
+        let mut simulation = RopeSimulation::new(3, [Step(Direction::Right, 5)]);
+
+        assert_eq!(
+            simulation.next_trail(),
+            Some(TailTrail {
+                positions: vec![(0, 0), (0, 0)],
+                run: Some(Run {
+                    start: (0, 0),
+                    direction: Direction::Right,
+                    length: 3,
+                }),
+            })
+        );
+        assert_eq!(simulation.knot_positions(), vec![(5, 0), (4, 0), (3, 0)]);
+        assert_eq!(simulation.next_trail(), None);
+    }
+
+    #[test]
+    fn test_next_trail_matches_unit_motions() {
+        let mut rng = XorShift::new(0x853c49e6748fea9b);
+
+        for knots in [1, 2, 10] {
+            let steps = random_steps(&mut rng, 200);
+
+            let mut unit_motions = RopeSimulation::new(knots, steps.clone());
+            let mut trails = RopeSimulation::new(knots, steps.clone());
+
+            for &Step(_, length) in &steps {
+                let expected: Vec<_> = unit_motions
+                    .by_ref()
+                    .take(length)
+                    .map(|positions| *positions.last().unwrap())
+                    .collect();
+
+                let trail = trails.next_trail().unwrap();
+                let mut actual = trail.positions;
+                actual.extend(trail.run.iter().flat_map(Run::positions));
+
+                assert_eq!(actual, expected);
+                assert_eq!(trails.knot_positions(), unit_motions.knot_positions());
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use alloc::{vec, vec::Vec};
+use core::{num::ParseIntError, ops::RangeInclusive, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum SensorError {
+    ParseIntError(ParseIntError),
+    InvalidPoint,
+    InvalidSensorReport,
+}
+
+impl From<ParseIntError> for SensorError {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn manhattan_distance(&self, other: &Self) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+impl FromStr for Point {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let v: Vec<_> = s.split(&['=', ',']).collect();
+        match v.as_slice() {
+            ["x", x, " y", y] => Ok(Self {
+                x: x.parse()?,
+                y: y.parse()?,
+            }),
+            _ => Err(Self::Err::InvalidPoint),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SensorReport {
+    pub sensor_position: Point,
+    pub beacon_position: Point,
+    pub exclusion_radius: i64,
+}
+
+impl SensorReport {
+    pub fn new(sensor_position: Point, beacon_position: Point) -> Self {
+        let exclusion_radius = sensor_position.manhattan_distance(&beacon_position);
+
+        Self {
+            sensor_position,
+            beacon_position,
+            exclusion_radius,
+        }
+    }
+
+    pub fn range_at(&self, y: i64) -> Option<RangeInclusive<i64>> {
+        let Point {
+            x: sensor_x,
+            y: sensor_y,
+        } = self.sensor_position;
+
+        let remaining_distance = self.exclusion_radius - (y - sensor_y).abs();
+        if remaining_distance >= 0 {
+            Some(sensor_x - remaining_distance..=sensor_x + remaining_distance)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for SensorReport {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let v: Vec<_> = s.split(':').collect();
+        match v.as_slice() {
+            [sensor, beacon] => {
+                let sensor_position = sensor
+                    .strip_prefix("Sensor at ")
+                    .ok_or(Self::Err::InvalidSensorReport)?;
+
+                let beacon_position = beacon
+                    .strip_prefix(" closest beacon is at ")
+                    .ok_or(Self::Err::InvalidSensorReport)?;
+
+                Ok(Self::new(
+                    sensor_position.parse()?,
+                    beacon_position.parse()?,
+                ))
+            }
+            _ => Err(Self::Err::InvalidSensorReport),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RowCoverage {
+    pub y: i64,
+    pub intervals: Vec<RangeInclusive<i64>>,
+}
+
+impl RowCoverage {
+    pub fn gaps(&self, search_min: i64, search_max: i64) -> Vec<RangeInclusive<i64>> {
+        let mut gaps = vec![];
+        let mut next_x = search_min;
+
+        for interval in &self.intervals {
+            if next_x > search_max {
+                break;
+            }
+
+            if *interval.start() > next_x {
+                gaps.push(next_x..=(interval.start() - 1).min(search_max));
+            }
+
+            next_x = next_x.max(interval.end() + 1);
+        }
+
+        if next_x <= search_max {
+            gaps.push(next_x..=search_max);
+        }
+
+        gaps
+    }
+}
+
+pub fn row_coverage(sensor_reports: &[SensorReport], y: i64) -> RowCoverage {
+    let mut ranges: Vec<_> = sensor_reports
+        .iter()
+        .filter_map(|report| report.range_at(y))
+        .collect();
+    ranges.sort_by_key(|range| *range.start());
+
+    let mut intervals: Vec<RangeInclusive<i64>> = vec![];
+
+    for range in ranges {
+        match intervals.last_mut() {
+            Some(last) if *range.start() <= last.end() + 1 => {
+                if range.end() > last.end() {
+                    *last = *last.start()..=*range.end();
+                }
+            }
+            _ => intervals.push(range),
+        }
+    }
+
+    RowCoverage { y, intervals }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reports() -> Vec<SensorReport> {
+        [
+            "Sensor at x=8, y=7: closest beacon is at x=2, y=10",
+            "Sensor at x=2, y=0: closest beacon is at x=2, y=10",
+            "Sensor at x=20, y=1: closest beacon is at x=15, y=3",
+        ]
+        .iter()
+        .map(|line| line.parse().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_row_coverage() {
+        let reports = reports();
+
+        assert_eq!(reports[0].range_at(10), Some(2..=14));
+        assert_eq!(
+            row_coverage(&reports, 10),
+            RowCoverage {
+                y: 10,
+                intervals: vec![2..=14],
+            }
+        );
+        assert_eq!(
+            row_coverage(&reports, 3),
+            RowCoverage {
+                y: 3,
+                intervals: vec![-5..=13, 15..=25],
+            }
+        );
+    }
+
+    #[test]
+    fn test_row_coverage_gaps() {
+        let coverage = |intervals| RowCoverage { y: 0, intervals };
+
+        assert_eq!(coverage(vec![0..=4, 8..=12]).gaps(0, 10), vec![5..=7]);
+        assert_eq!(coverage(vec![3..=5]).gaps(0, 10), vec![0..=2, 6..=10]);
+        assert_eq!(coverage(vec![]).gaps(0, 3), vec![0..=3]);
+        assert!(coverage(vec![-5..=15]).gaps(0, 10).is_empty());
+
+        let reports = reports();
+        assert_eq!(row_coverage(&reports, 3).gaps(0, 20), vec![14..=14]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let report = reports().remove(0);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            json,
+            r#"{"sensor_position":{"x":8,"y":7},"beacon_position":{"x":2,"y":10},"exclusion_radius":9}"#
+        );
+        assert_eq!(serde_json::from_str::<SensorReport>(&json).unwrap(), report);
+
+        let coverage = row_coverage(&reports(), 10);
+        assert_eq!(
+            serde_json::to_string(&coverage).unwrap(),
+            r#"{"y":10,"intervals":[{"start":2,"end":14}]}"#
+        );
+    }
+}
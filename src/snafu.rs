@@ -0,0 +1,230 @@
+use alloc::string::String;
+use core::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign},
+    str::FromStr,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SnafuError {
+    Empty,
+    InvalidDigit(char),
+    Overflow,
+}
+
+// A number written in balanced base 5, with digits `=`, `-`, `0`, `1` and `2`
+// standing for -2 to 2.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snafu(i64);
+
+impl Snafu {
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+}
+
+fn digit_value(c: char) -> Option<i128> {
+    match c {
+        '=' => Some(-2),
+        '-' => Some(-1),
+        '0' => Some(0),
+        '1' => Some(1),
+        '2' => Some(2),
+        _ => None,
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = SnafuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(Self::Err::Empty);
+        }
+
+        // Widened because the digits before the last can overshoot `i64`.
+        let value = s.chars().try_fold(0i128, |value, c| {
+            let digit = digit_value(c).ok_or(Self::Err::InvalidDigit(c))?;
+
+            value
+                .checked_mul(5)
+                .and_then(|value| value.checked_add(digit))
+                .ok_or(Self::Err::Overflow)
+        })?;
+
+        i64::try_from(value)
+            .map(Self)
+            .map_err(|_| Self::Err::Overflow)
+    }
+}
+
+impl fmt::Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Widened so that carrying out of `i64::MIN` can't overflow.
+        let mut value = self.0 as i128;
+        let mut digits = String::new();
+
+        loop {
+            let digit = (value + 2).rem_euclid(5) - 2;
+            digits.push(match digit {
+                -2 => '=',
+                -1 => '-',
+                0 => '0',
+                1 => '1',
+                _ => '2',
+            });
+
+            value = (value - digit) / 5;
+            if value == 0 {
+                break;
+            }
+        }
+
+        let digits: String = digits.chars().rev().collect();
+        f.pad(&digits)
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(value: i64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Snafu> for i64 {
+    fn from(snafu: Snafu) -> Self {
+        snafu.0
+    }
+}
+
+// Panics on overflow, in release builds too; use `checked_add` for sums of
+// untrusted input.
+impl Add for Snafu {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other)
+            .expect("overflow adding SNAFU numbers")
+    }
+}
+
+impl AddAssign for Snafu {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for Snafu {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{string::ToString, vec::Vec};
+
+    use crate::rng::XorShift;
+
+    const EXAMPLES: [(i64, &str); 10] = [
+        (1, "1"),
+        (2, "2"),
+        (3, "1="),
+        (4, "1-"),
+        (5, "10"),
+        (8, "2="),
+        (20, "1-0"),
+        (2022, "1=11-2"),
+        (12345, "1-0---0"),
+        (314159265, "1121-1110-1=0"),
+    ];
+
+    fn random_values(rng: &mut XorShift, n: usize) -> Vec<i64> {
+        (0..n)
+            .map(|_| {
+                let value = rng.next_u64();
+
+                // Shift by a varying amount to cover small and large magnitudes.
+                (value as i64) >> (value % 64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_examples() {
+        for (value, text) in EXAMPLES {
+            assert_eq!(Snafu::from(value).to_string(), text);
+            assert_eq!(text.parse(), Ok(Snafu::from(value)));
+        }
+
+        assert_eq!(Snafu::from(0).to_string(), "0");
+        assert_eq!(Snafu::from(-3).to_string(), "-2");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Snafu>(), Err(SnafuError::Empty));
+        assert_eq!("1=3".parse::<Snafu>(), Err(SnafuError::InvalidDigit('3')));
+        assert_eq!("2".repeat(28).parse::<Snafu>(), Err(SnafuError::Overflow));
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow adding SNAFU numbers")]
+    fn test_add_overflow() {
+        let _ = Snafu(i64::MAX) + Snafu(1);
+    }
+
+    #[test]
+    fn test_sum() {
+        let sum: Snafu = EXAMPLES.iter().map(|(_, text)| text.parse().unwrap()).sum();
+
+        assert_eq!(
+            i64::from(sum),
+            EXAMPLES.iter().map(|(value, _)| value).sum::<i64>()
+        );
+        assert_eq!(Snafu::from(i64::MAX).checked_add(Snafu::from(1)), None);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut rng = XorShift::new(0x9e3779b97f4a7c15);
+
+        let values = random_values(&mut rng, 1000);
+        for value in values
+            .iter()
+            .copied()
+            .chain([0, i64::MIN, i64::MAX, i64::MIN + 1])
+        {
+            let snafu = Snafu::from(value);
+            let text = snafu.to_string();
+
+            assert_eq!(i64::from(snafu), value);
+            assert_eq!(text.parse(), Ok(snafu), "{value} as {text}");
+            assert!(!text.starts_with('0') || text == "0", "{value} as {text}");
+        }
+    }
+
+    #[test]
+    fn test_addition_matches_i64() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+
+        let values = random_values(&mut rng, 1000);
+        for pair in values.chunks(2) {
+            let (a, b) = (pair[0], pair[1]);
+
+            let expected = a.checked_add(b);
+            let sum = Snafu::from(a).checked_add(Snafu::from(b));
+            assert_eq!(sum.map(i64::from), expected);
+
+            if let Some(sum) = sum {
+                let mut total = Snafu::from(a);
+                total += Snafu::from(b);
+                assert_eq!(total, sum);
+                assert_eq!(sum.to_string().parse(), Ok(Snafu::from(a) + Snafu::from(b)));
+            }
+        }
+    }
+}
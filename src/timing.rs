@@ -0,0 +1,88 @@
+use std::{
+    fmt,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, max {:?} over {} runs",
+            self.min, self.median, self.max, self.runs
+        )
+    }
+}
+
+pub fn summarize(durations: &mut [Duration]) -> Option<Timings> {
+    durations.sort();
+
+    let runs = durations.len();
+    let median = if runs.is_multiple_of(2) {
+        (*durations.get(runs / 2)? + durations[runs / 2 - 1]) / 2
+    } else {
+        durations[runs / 2]
+    };
+
+    Some(Timings {
+        runs,
+        min: durations[0],
+        median,
+        max: durations[runs - 1],
+    })
+}
+
+// Runs `f` once to warm up, then `runs` more times while timing each call.
+pub fn time_repeated<T>(runs: usize, mut f: impl FnMut() -> T) -> Option<Timings> {
+    black_box(f());
+
+    let mut durations: Vec<_> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+
+    summarize(&mut durations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let ms = Duration::from_millis;
+
+        assert_eq!(
+            summarize(&mut [ms(5), ms(1), ms(3)]),
+            Some(Timings {
+                runs: 3,
+                min: ms(1),
+                median: ms(3),
+                max: ms(5)
+            })
+        );
+        assert_eq!(summarize(&mut [ms(4), ms(2)]).unwrap().median, ms(3));
+        assert_eq!(summarize(&mut []), None);
+    }
+
+    #[test]
+    fn test_time_repeated() {
+        let mut calls = 0;
+        let timings = time_repeated(4, || calls += 1).unwrap();
+
+        assert_eq!(calls, 5);
+        assert_eq!(timings.runs, 4);
+        assert!(timings.min <= timings.median && timings.median <= timings.max);
+    }
+}
@@ -0,0 +1,867 @@
+use alloc::{collections::BTreeSet, string::String, vec, vec::Vec};
+
+use crate::{
+    cycle::{Cycle, CycleDetector},
+    render::{Packing, Svg},
+    to_lines,
+};
+
+#[derive(Debug)]
+pub enum TowerError {
+    InvalidJetError {
+        jet: char,
+        line: usize,
+        column: usize,
+    },
+    EmptyJetPattern,
+    InvalidShapeError(char),
+    EmptyShape,
+    ShapeTooWide,
+    InvalidWidth(u64),
+    NoShapes,
+    NoCycle(usize),
+}
+
+const WALL_COLOUR: &str = "#6b5b4b";
+const AIR_COLOUR: &str = "#1b1b2f";
+pub const ROCK_COLOUR: &str = "#a0a0b0";
+
+pub const PRUNE_INTERVAL: u64 = 100;
+pub const CYCLE_SEARCH_LIMIT: usize = 1000000;
+
+pub const CHAMBER_WIDTH: u64 = 7;
+// Each row of the chamber is a bitmask.
+pub const MAX_CHAMBER_WIDTH: u64 = u64::BITS as u64;
+
+// Rocks appear two units away from the left wall.
+const SPAWN_OFFSET: u64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jet {
+    Left,
+    Right,
+}
+
+impl Jet {
+    fn from_char(value: char) -> Option<Self> {
+        match value {
+            '<' => Some(Self::Left),
+            '>' => Some(Self::Right),
+            _ => None,
+        }
+    }
+}
+
+// The pattern may be wrapped over several lines, and whitespace is ignored.
+// Positions in errors are 1-based.
+pub fn parse_jet_pattern(input: &[String]) -> Result<Vec<Jet>, TowerError> {
+    let mut jet_pattern = vec![];
+
+    for (line, text) in input.iter().enumerate() {
+        for (column, c) in text.chars().enumerate() {
+            if c.is_whitespace() {
+                continue;
+            }
+
+            jet_pattern.push(Jet::from_char(c).ok_or(TowerError::InvalidJetError {
+                jet: c,
+                line: line + 1,
+                column: column + 1,
+            })?);
+        }
+    }
+
+    Ok(jet_pattern)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: u64,
+    pub y: u64,
+}
+
+fn full_row(width: u64) -> u64 {
+    u64::MAX >> (MAX_CHAMBER_WIDTH - width)
+}
+
+fn column_bit(x: u64) -> u64 {
+    // Column 1 is the lowest bit of a row, so shapes don't depend on the width.
+    1 << (x - 1)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RockShape {
+    rows: Vec<u64>,
+    width: u64,
+}
+
+impl RockShape {
+    fn row_at(mask: u64, x: u64) -> u64 {
+        mask << (x - 1)
+    }
+
+    pub fn points(&self, bottom_left: &Point) -> Vec<Point> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(dy, &mask)| {
+                (0..self.width)
+                    .filter(move |&dx| mask & column_bit(dx + 1) != 0)
+                    .map(move |dx| Point {
+                        x: bottom_left.x + dx,
+                        y: bottom_left.y + dy as u64,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<&[String]> for RockShape {
+    type Error = TowerError;
+
+    fn try_from(drawing: &[String]) -> Result<Self, Self::Error> {
+        let mut offsets = vec![];
+
+        for (row, line) in drawing.iter().rev().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                match c {
+                    '#' => offsets.push(Point {
+                        x: column as u64,
+                        y: row as u64,
+                    }),
+                    '.' => {}
+                    _ => return Err(TowerError::InvalidShapeError(c)),
+                }
+            }
+        }
+
+        let min_x = offsets.iter().map(|offset| offset.x).min();
+        let min_y = offsets.iter().map(|offset| offset.y).min();
+
+        let (Some(min_x), Some(min_y)) = (min_x, min_y) else {
+            return Err(TowerError::EmptyShape);
+        };
+
+        let width = offsets.iter().map(|offset| offset.x).max().unwrap() - min_x + 1;
+        let height = offsets.iter().map(|offset| offset.y).max().unwrap() - min_y + 1;
+
+        // Whether it fits a given chamber is checked when the chamber is made.
+        if width > MAX_CHAMBER_WIDTH - SPAWN_OFFSET {
+            return Err(TowerError::ShapeTooWide);
+        }
+
+        let mut rows = vec![0; height as usize];
+        for offset in offsets {
+            rows[(offset.y - min_y) as usize] |= column_bit(offset.x - min_x + 1);
+        }
+
+        Ok(Self { rows, width })
+    }
+}
+
+pub fn parse_rock_shapes(input: &[String]) -> Result<Vec<RockShape>, TowerError> {
+    let shapes = input
+        .split(|line| line.is_empty())
+        .filter(|drawing| !drawing.is_empty())
+        .map(RockShape::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if shapes.is_empty() {
+        Err(TowerError::NoShapes)
+    } else {
+        Ok(shapes)
+    }
+}
+
+const DEFAULT_ROCK_SHAPES: &str = "\
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##
+";
+
+pub fn default_rock_shapes() -> Vec<RockShape> {
+    parse_rock_shapes(&to_lines(DEFAULT_ROCK_SHAPES)).expect("default rock shapes should be valid")
+}
+
+pub struct CaveState {
+    shapes: Vec<RockShape>,
+    shape_index: usize,
+    jet_index: usize,
+    jet_pattern: Vec<Jet>,
+    width: u64,
+    rows: Vec<u64>,
+    pruned_rows: u64,
+    rocks_dropped: u64,
+    prune_interval: Option<u64>,
+}
+
+impl CaveState {
+    pub fn new(jet_pattern: Vec<Jet>, shapes: Vec<RockShape>) -> Result<Self, TowerError> {
+        Self::with_width(jet_pattern, shapes, CHAMBER_WIDTH)
+    }
+
+    pub fn with_width(
+        jet_pattern: Vec<Jet>,
+        shapes: Vec<RockShape>,
+        width: u64,
+    ) -> Result<Self, TowerError> {
+        if jet_pattern.is_empty() {
+            Err(TowerError::EmptyJetPattern)
+        } else if shapes.is_empty() {
+            Err(TowerError::NoShapes)
+        } else if width == 0 || width > MAX_CHAMBER_WIDTH {
+            Err(TowerError::InvalidWidth(width))
+        } else if shapes
+            .iter()
+            .any(|shape| SPAWN_OFFSET + shape.width > width)
+        {
+            Err(TowerError::ShapeTooWide)
+        } else {
+            Ok(Self {
+                shapes,
+                shape_index: 0,
+                jet_index: 0,
+                jet_pattern,
+                width,
+                rows: vec![],
+                pruned_rows: 0,
+                rocks_dropped: 0,
+                prune_interval: None,
+            })
+        }
+    }
+
+    pub fn with_pruning(mut self, interval: u64) -> Self {
+        self.prune_interval = Some(interval);
+        self
+    }
+
+    pub fn height(&self) -> u64 {
+        self.pruned_rows + self.rows.len() as u64
+    }
+
+    pub fn width(&self) -> u64 {
+        self.width
+    }
+
+    fn row(&self, y: u64) -> u64 {
+        // The floor and any pruned rows below the tower are solid.
+        if y <= self.pruned_rows {
+            full_row(self.width)
+        } else {
+            let index = (y - self.pruned_rows - 1) as usize;
+            self.rows.get(index).copied().unwrap_or(0)
+        }
+    }
+
+    pub fn is_rock(&self, point: &Point) -> bool {
+        self.row(point.y) & column_bit(point.x) != 0
+    }
+
+    fn collides(&self, shape: &RockShape, bottom_left: &Point) -> bool {
+        shape.rows.iter().enumerate().any(|(dy, &mask)| {
+            self.row(bottom_left.y + dy as u64) & RockShape::row_at(mask, bottom_left.x) != 0
+        })
+    }
+
+    fn place(&mut self, shape: &RockShape, bottom_left: &Point) {
+        for (dy, &mask) in shape.rows.iter().enumerate() {
+            let index = (bottom_left.y + dy as u64 - self.pruned_rows - 1) as usize;
+            if index >= self.rows.len() {
+                self.rows.resize(index + 1, 0);
+            }
+
+            self.rows[index] |= RockShape::row_at(mask, bottom_left.x);
+        }
+    }
+
+    fn next_shape(&mut self) -> RockShape {
+        let shape = self.shapes[self.shape_index].clone();
+        self.shape_index = (self.shape_index + 1) % self.shapes.len();
+
+        shape
+    }
+
+    fn next_jet(&mut self) -> Jet {
+        let jet = self.jet_pattern[self.jet_index];
+        self.jet_index = (self.jet_index + 1) % self.jet_pattern.len();
+
+        jet
+    }
+
+    pub fn drop_rock(&mut self) {
+        self.drop_rock_with(|_, _, _| {})
+    }
+
+    pub fn drop_rock_with(&mut self, mut on_step: impl FnMut(&Self, &RockShape, &Point)) {
+        use Jet::*;
+        // Floor is y = 0
+        // Left wall is x = 0
+        // Right wall is x = width + 1
+
+        let shape = self.next_shape();
+        let mut bottom_left = Point {
+            x: SPAWN_OFFSET + 1,
+            y: self.height() + 4,
+        };
+
+        on_step(self, &shape, &bottom_left);
+
+        loop {
+            let new_bottom_left = match self.next_jet() {
+                Left if bottom_left.x > 1 => Some(Point {
+                    x: bottom_left.x - 1,
+                    y: bottom_left.y,
+                }),
+                Right if bottom_left.x + shape.width <= self.width => Some(Point {
+                    x: bottom_left.x + 1,
+                    y: bottom_left.y,
+                }),
+                _ => None,
+            };
+
+            if let Some(new_bottom_left) = new_bottom_left {
+                if !self.collides(&shape, &new_bottom_left) {
+                    bottom_left = new_bottom_left;
+                }
+            }
+
+            let new_bottom_left = Point {
+                x: bottom_left.x,
+                y: bottom_left.y - 1,
+            };
+
+            if self.collides(&shape, &new_bottom_left) {
+                self.place(&shape, &bottom_left);
+                break;
+            }
+
+            bottom_left = new_bottom_left;
+            on_step(self, &shape, &bottom_left);
+        }
+
+        self.rocks_dropped += 1;
+        if let Some(interval) = self.prune_interval {
+            if self.rocks_dropped.is_multiple_of(interval) {
+                self.prune();
+            }
+        }
+    }
+
+    pub fn render_rows(&self, rows: u64, falling: &[Point]) -> String {
+        let top = falling
+            .iter()
+            .map(|point| point.y)
+            .chain([self.height()])
+            .max()
+            .unwrap();
+        let bottom = top.saturating_sub(rows) + 1;
+
+        let mut output = String::new();
+
+        for y in (bottom..=top).rev() {
+            output.push('|');
+            for x in 1..=self.width {
+                let point = Point { x, y };
+
+                output.push(if falling.contains(&point) {
+                    '@'
+                } else if self.is_rock(&point) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            output.push_str("|\n");
+        }
+
+        if bottom == 1 {
+            output.push('+');
+            output.extend((0..self.width).map(|_| '-'));
+            output.push_str("+\n");
+        }
+
+        output
+    }
+
+    pub fn render_packed(&self, rows: u64, falling: &[Point], packing: Packing) -> String {
+        let top = falling
+            .iter()
+            .map(|point| point.y)
+            .chain([self.height()])
+            .max()
+            .unwrap();
+        let bottom = top.saturating_sub(rows) + 1;
+        let height = (top + 1 - bottom) as usize + usize::from(bottom == 1);
+
+        let right_wall = self.width as usize + 1;
+
+        packing.pack(right_wall + 1, height, |x, row| {
+            let Some(y) = top.checked_sub(row as u64).filter(|&y| y >= bottom) else {
+                return true;
+            };
+            let point = Point { x: x as u64, y };
+
+            x == 0 || x == right_wall || falling.contains(&point) || self.is_rock(&point)
+        })
+    }
+
+    pub fn render_svg(&self, rows: u64) -> Svg {
+        let top = self.height();
+        let bottom = top.saturating_sub(rows) + 1;
+        let height = (top + 1 - bottom) as i64;
+
+        let width = self.width as i64;
+
+        let mut svg = Svg::new(0, 0, width + 2, height + 1);
+        svg.rect(0, 0, width + 2, height + 1, WALL_COLOUR);
+        svg.rect(1, 0, width, height, AIR_COLOUR);
+
+        for y in bottom..=top {
+            for x in 1..=self.width {
+                if self.is_rock(&Point { x, y }) {
+                    svg.rect(x as i64, (top - y) as i64, 1, 1, ROCK_COLOUR);
+                }
+            }
+        }
+
+        svg
+    }
+
+    fn reachable_air(&self) -> BTreeSet<Point> {
+        let height = self.height();
+
+        let mut reachable: BTreeSet<Point> = (1..=self.width)
+            .map(|x| Point { x, y: height + 1 })
+            .collect();
+        let mut stack: Vec<Point> = reachable.iter().copied().collect();
+
+        while let Some(Point { x, y }) = stack.pop() {
+            let neighbours = [
+                (x > 1).then(|| Point { x: x - 1, y }),
+                (x < self.width).then(|| Point { x: x + 1, y }),
+                Some(Point { x, y: y - 1 }),
+            ];
+
+            for neighbour in neighbours.into_iter().flatten() {
+                if !self.is_rock(&neighbour) && reachable.insert(neighbour) {
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    pub fn prune(&mut self) {
+        // Every cell of a falling rock travels through reachable air, so the
+        // only fallen rocks it can ever collide with are at most one row
+        // below the lowest reachable cell. Anything deeper can be forgotten.
+        let Some(lowest) = self.reachable_air().iter().map(|point| point.y).min() else {
+            return;
+        };
+
+        let forgotten = (lowest - 1).saturating_sub(self.pruned_rows + 1) as usize;
+        let forgotten = forgotten.min(self.rows.len());
+
+        self.rows.drain(..forgotten);
+        self.pruned_rows += forgotten as u64;
+    }
+
+    pub fn surface(&self) -> Vec<(u64, u64)> {
+        // Air cells that a falling rock could still reach, moving left, right
+        // or down from above the tower, relative to the top of the tower.
+        let height = self.height();
+
+        let mut surface: Vec<_> = self
+            .reachable_air()
+            .into_iter()
+            .map(|Point { x, y }| (x, height + 1 - y))
+            .collect();
+        surface.sort();
+
+        surface
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+struct CycleState {
+    surface: Vec<(u64, u64)>,
+    shape_index: usize,
+    jet_index: usize,
+}
+
+pub struct TowerHeights {
+    cave_state: CaveState,
+}
+
+impl TowerHeights {
+    pub fn new(jet_pattern: Vec<Jet>, shapes: Vec<RockShape>) -> Result<Self, TowerError> {
+        Self::with_width(jet_pattern, shapes, CHAMBER_WIDTH)
+    }
+
+    pub fn with_width(
+        jet_pattern: Vec<Jet>,
+        shapes: Vec<RockShape>,
+        width: u64,
+    ) -> Result<Self, TowerError> {
+        let cave_state =
+            CaveState::with_width(jet_pattern, shapes, width)?.with_pruning(PRUNE_INTERVAL);
+
+        Ok(Self { cave_state })
+    }
+
+    pub fn cave_state(&self) -> &CaveState {
+        &self.cave_state
+    }
+}
+
+impl Iterator for TowerHeights {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cave_state.drop_rock();
+
+        Some(self.cave_state.height())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleStats {
+    pub cycle: Option<Cycle>,
+    pub rocks_simulated: usize,
+}
+
+// Height after `rocks` rocks, extrapolated once the tower starts repeating.
+pub fn height_after(
+    jet_pattern: Vec<Jet>,
+    shapes: Vec<RockShape>,
+    rocks: usize,
+) -> Result<u64, TowerError> {
+    let (height, _) = height_after_with_stats(
+        jet_pattern,
+        shapes,
+        CHAMBER_WIDTH,
+        rocks,
+        CYCLE_SEARCH_LIMIT,
+    )?;
+
+    Ok(height)
+}
+
+pub fn height_after_with_stats(
+    jet_pattern: Vec<Jet>,
+    shapes: Vec<RockShape>,
+    width: u64,
+    rocks: usize,
+    search_limit: usize,
+) -> Result<(u64, CycleStats), TowerError> {
+    let mut cave_state =
+        CaveState::with_width(jet_pattern, shapes, width)?.with_pruning(PRUNE_INTERVAL);
+
+    let mut cycle_detector = CycleDetector::new();
+    let mut height_memory: Vec<u64> = vec![];
+
+    for i in 0..rocks {
+        // Simulating every rock is only an option for short towers.
+        if i == search_limit {
+            return Err(TowerError::NoCycle(search_limit));
+        }
+
+        let cycle_state = CycleState {
+            surface: cave_state.surface(),
+            shape_index: cave_state.shape_index,
+            jet_index: cave_state.jet_index,
+        };
+        height_memory.push(cave_state.height());
+
+        if let Some(cycle) = cycle_detector.observe(i, cycle_state) {
+            let height_gain_per_cycle = height_memory[i] - height_memory[cycle.start];
+
+            let (number_of_cycles, intermediate_index) = cycle.fold(rocks);
+
+            let height = height_memory[intermediate_index]
+                + height_gain_per_cycle * (number_of_cycles as u64);
+
+            let stats = CycleStats {
+                cycle: Some(cycle),
+                rocks_simulated: i,
+            };
+
+            return Ok((height, stats));
+        }
+
+        cave_state.drop_rock();
+    }
+
+    let stats = CycleStats {
+        cycle: None,
+        rocks_simulated: rocks,
+    };
+
+    Ok((cave_state.height(), stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    fn cave_state(rocks: &[(u64, u64)]) -> CaveState {
+        let mut cave_state = CaveState::new(vec![Jet::Left], default_rock_shapes()).unwrap();
+
+        for &(x, y) in rocks {
+            if cave_state.rows.len() < y as usize {
+                cave_state.rows.resize(y as usize, 0);
+            }
+
+            cave_state.rows[y as usize - 1] |= column_bit(x);
+        }
+
+        cave_state
+    }
+
+    #[test]
+    fn test_surface_overhang() {
+        let full_row: Vec<_> = (1..=7).map(|x| (x, 1)).collect();
+
+        // Same column heights, but the first has an overhang at x = 2 which
+        // a rock can still slide under from x = 3.
+        let overhang = cave_state(&[full_row.clone(), vec![(1, 3), (2, 3)]].concat());
+        let filled = cave_state(&[full_row, vec![(1, 3), (2, 3), (2, 2)]].concat());
+
+        assert_eq!(overhang.height(), filled.height());
+        assert_ne!(overhang.surface(), filled.surface());
+
+        assert!(overhang.surface().contains(&(2, 2)));
+        assert!(overhang.surface().contains(&(1, 2)));
+        assert!(!filled.surface().contains(&(1, 2)));
+    }
+
+    #[test]
+    fn test_default_rock_shapes() {
+        let shapes = default_rock_shapes();
+
+        assert_eq!(shapes.len(), 5);
+        assert_eq!(
+            shapes[1].points(&Point { x: 3, y: 4 }),
+            vec![
+                Point { x: 4, y: 4 },
+                Point { x: 3, y: 5 },
+                Point { x: 4, y: 5 },
+                Point { x: 5, y: 5 },
+                Point { x: 4, y: 6 },
+            ]
+        );
+        assert_eq!(
+            shapes[2]
+                .points(&Point { x: 0, y: 0 })
+                .into_iter()
+                .collect::<BTreeSet<_>>(),
+            BTreeSet::from([
+                Point { x: 0, y: 0 },
+                Point { x: 1, y: 0 },
+                Point { x: 2, y: 0 },
+                Point { x: 2, y: 1 },
+                Point { x: 2, y: 2 },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_rock_shapes() {
+        let shapes = parse_rock_shapes(&to_lines("..#\n\n#####\n")).unwrap();
+
+        assert_eq!(shapes[0].rows, vec![0b1]);
+        assert_eq!(shapes[1].rows, vec![0b11111]);
+
+        assert!(matches!(
+            parse_rock_shapes(&to_lines("#x#")),
+            Err(TowerError::InvalidShapeError('x'))
+        ));
+        assert!(matches!(
+            parse_rock_shapes(&to_lines(&"#".repeat(63))),
+            Err(TowerError::ShapeTooWide)
+        ));
+        assert!(matches!(
+            parse_rock_shapes(&to_lines("...")),
+            Err(TowerError::EmptyShape)
+        ));
+        assert!(matches!(parse_rock_shapes(&[]), Err(TowerError::NoShapes)));
+    }
+
+    #[test]
+    fn test_parse_jet_pattern() {
+        let single = parse_jet_pattern(&to_lines(EXAMPLE)).unwrap();
+        let wrapped = parse_jet_pattern(&to_lines(
+            ">>><<><>><<<>><>>><<<\n\n  >>><<<><<<>><>>\t<<>> \r\n",
+        ))
+        .unwrap();
+
+        assert_eq!(single.len(), 40);
+        assert_eq!(single, wrapped);
+
+        assert!(matches!(
+            parse_jet_pattern(&to_lines(">><\n<>x<\n")),
+            Err(TowerError::InvalidJetError {
+                jet: 'x',
+                line: 2,
+                column: 3
+            })
+        ));
+        assert!(parse_jet_pattern(&to_lines("\n \n")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune() {
+        let jet_pattern = parse_jet_pattern(&to_lines(EXAMPLE)).unwrap();
+
+        let mut unpruned = CaveState::new(jet_pattern.clone(), default_rock_shapes()).unwrap();
+        let mut pruned = CaveState::new(jet_pattern, default_rock_shapes())
+            .unwrap()
+            .with_pruning(10);
+
+        for _ in 0..2022 {
+            unpruned.drop_rock();
+            pruned.drop_rock();
+
+            assert_eq!(pruned.height(), unpruned.height());
+        }
+
+        assert_eq!(pruned.surface(), unpruned.surface());
+
+        // Every row of the tower without pruning, only the top few dozen with.
+        assert_eq!(unpruned.rows.len(), 3068);
+        assert!(pruned.rows.len() < 100);
+    }
+
+    #[test]
+    fn test_tower_heights() {
+        let jet_pattern = parse_jet_pattern(&to_lines(EXAMPLE)).unwrap();
+
+        let heights: Vec<_> = TowerHeights::new(jet_pattern.clone(), default_rock_shapes())
+            .unwrap()
+            .take(2022)
+            .collect();
+
+        assert_eq!(heights[..4], [1, 4, 6, 7]);
+        assert_eq!(heights[2021], 3068);
+
+        assert_eq!(
+            height_after(jet_pattern.clone(), default_rock_shapes(), 2022).unwrap(),
+            3068
+        );
+        assert_eq!(
+            height_after(jet_pattern, default_rock_shapes(), 1000000000000).unwrap(),
+            1514285714288
+        );
+    }
+
+    #[test]
+    fn test_cycle_stats() {
+        let jet_pattern = parse_jet_pattern(&to_lines(EXAMPLE)).unwrap();
+
+        let (height, stats) = height_after_with_stats(
+            jet_pattern.clone(),
+            default_rock_shapes(),
+            CHAMBER_WIDTH,
+            1000000000000,
+            1000,
+        )
+        .unwrap();
+        assert_eq!(height, 1514285714288);
+        assert_eq!(
+            stats,
+            CycleStats {
+                cycle: Some(Cycle {
+                    start: 28,
+                    length: 35
+                }),
+                rocks_simulated: 63
+            }
+        );
+
+        let (height, stats) = height_after_with_stats(
+            jet_pattern.clone(),
+            default_rock_shapes(),
+            CHAMBER_WIDTH,
+            4,
+            10,
+        )
+        .unwrap();
+        assert_eq!(height, 7);
+        assert_eq!(
+            stats,
+            CycleStats {
+                cycle: None,
+                rocks_simulated: 4
+            }
+        );
+
+        assert!(matches!(
+            height_after_with_stats(
+                jet_pattern,
+                default_rock_shapes(),
+                CHAMBER_WIDTH,
+                1000000000000,
+                10
+            ),
+            Err(TowerError::NoCycle(10))
+        ));
+    }
+
+    #[test]
+    fn test_chamber_width() {
+        let jet_pattern = parse_jet_pattern(&to_lines(EXAMPLE)).unwrap();
+
+        let seven = TowerHeights::with_width(jet_pattern.clone(), default_rock_shapes(), 7)
+            .unwrap()
+            .nth(2021);
+        assert_eq!(seven, Some(3068));
+
+        // A wider chamber spreads the same rocks over more columns.
+        for width in [9, 20, MAX_CHAMBER_WIDTH] {
+            let mut cave_state =
+                CaveState::with_width(jet_pattern.clone(), default_rock_shapes(), width).unwrap();
+            for _ in 0..2022 {
+                cave_state.drop_rock();
+            }
+
+            assert!(cave_state.height() < 3068, "width {width}");
+            assert!(cave_state.surface().iter().all(|&(x, _)| x <= width));
+
+            let rows = cave_state.render_rows(3, &[]);
+            assert!(rows.lines().all(|line| line.len() == width as usize + 2));
+        }
+
+        // The horizontal rock needs four columns past the spawn offset.
+        assert!(matches!(
+            CaveState::with_width(jet_pattern.clone(), default_rock_shapes(), 5),
+            Err(TowerError::ShapeTooWide)
+        ));
+        assert!(CaveState::with_width(jet_pattern.clone(), default_rock_shapes(), 6).is_ok());
+        assert!(matches!(
+            CaveState::with_width(jet_pattern.clone(), default_rock_shapes(), 0),
+            Err(TowerError::InvalidWidth(0))
+        ));
+        assert!(matches!(
+            CaveState::with_width(jet_pattern, default_rock_shapes(), 65),
+            Err(TowerError::InvalidWidth(65))
+        ));
+    }
+}
@@ -0,0 +1,73 @@
+use std::{env, fmt::Debug, time::Duration};
+
+use serde_json::{json, Value};
+
+use crate::puzzles::part_label;
+
+pub const URL_VARIABLE: &str = "AOC_WEBHOOK_URL";
+
+pub fn completion_message(
+    day: usize,
+    part: usize,
+    answer: &impl Debug,
+    elapsed: Duration,
+) -> String {
+    format!(
+        "{}: {:?} (took {:.1?})",
+        part_label(day, part),
+        answer,
+        elapsed
+    )
+}
+
+// Discord reads the message from `content`, Slack from `text`.
+pub fn payload(url: &str, message: &str) -> Value {
+    let is_discord = ["discord.com/", "discordapp.com/"]
+        .iter()
+        .any(|host| url.contains(host));
+
+    if is_discord {
+        json!({ "content": message })
+    } else {
+        json!({ "text": message })
+    }
+}
+
+// Does nothing unless a webhook is configured. The answer has already been
+// printed by then, so a failed post is only reported.
+pub fn notify_completion(day: usize, part: usize, answer: &impl Debug, elapsed: Duration) {
+    let Ok(url) = env::var(URL_VARIABLE) else {
+        return;
+    };
+
+    let message = completion_message(day, part, answer, elapsed);
+
+    if let Err(e) = ureq::post(&url).send_json(payload(&url, &message)) {
+        eprintln!("Could not notify {}: {}", URL_VARIABLE, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_message() {
+        assert_eq!(
+            completion_message(16, 2, &1707, Duration::from_millis(95250)),
+            "Day 16: Proboscidea Volcanium — Part 2: 1707 (took 95.2s)"
+        );
+    }
+
+    #[test]
+    fn test_payload() {
+        assert_eq!(
+            payload("https://hooks.slack.com/services/T0/B0/x", "done"),
+            json!({ "text": "done" })
+        );
+        assert_eq!(
+            payload("https://discord.com/api/webhooks/1/x", "done"),
+            json!({ "content": "done" })
+        );
+    }
+}
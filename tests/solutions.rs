@@ -0,0 +1,151 @@
+// Runs each day's binary on an input and checks the answers it prints. The
+// examples from the puzzle texts are checked in under `tests/fixtures`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use aoc::puzzles::{answers_path, input_path, part_label};
+
+const CRT_EXAMPLE: &str = "\
+##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....";
+
+// Answers are printed with `{:?}` after the part label, except for images
+// like day 10's, which start on the next line and end at a blank one.
+fn parse_answers(day: usize, output: &str) -> Vec<String> {
+    let lines: Vec<_> = output.lines().collect();
+
+    (1..=2)
+        .filter_map(|part| {
+            let label = format!("{}: ", part_label(day, part));
+            let index = lines.iter().position(|line| line.starts_with(&label))?;
+
+            let answer = lines[index][label.len()..].trim_end();
+            if !answer.is_empty() {
+                return Some(answer.trim_matches('"').to_owned());
+            }
+
+            let image: Vec<_> = lines[index + 1..]
+                .iter()
+                .take_while(|line| !line.is_empty())
+                .copied()
+                .collect();
+            Some(image.join("\n"))
+        })
+        .collect()
+}
+
+fn run(day: usize, binary: &str, directory: &Path, args: &[&str]) -> Vec<String> {
+    let output = Command::new(binary)
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "day {} failed: {}",
+        day,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    parse_answers(day, &String::from_utf8(output.stdout).unwrap())
+}
+
+// The binaries read `inputs/dayN.txt`, so each example gets a directory of
+// its own with the fixture in that place.
+fn run_example(day: usize, binary: &str, args: &[&str]) -> Vec<String> {
+    let directory = env::temp_dir().join(format!("aoc-example-day{}-{}", day, std::process::id()));
+    fs::create_dir_all(directory.join("inputs")).unwrap();
+
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("day{}.txt", day));
+    fs::copy(fixture, directory.join(input_path(day))).unwrap();
+
+    let answers = run(day, binary, &directory, args);
+
+    fs::remove_dir_all(directory).unwrap();
+
+    answers
+}
+
+macro_rules! solutions {
+    ($($name:ident: $day:literal $args:expr => $answers:expr;)*) => {
+        $(
+            #[test]
+            fn $name() {
+                assert_eq!(
+                    run_example($day, env!(concat!("CARGO_BIN_EXE_day", $day)), &$args),
+                    $answers
+                );
+            }
+        )*
+
+        const BINARIES: &[(usize, &str)] = &[$(($day, env!(concat!("CARGO_BIN_EXE_day", $day)))),*];
+    };
+}
+
+solutions! {
+    day1: 1 [] => ["24000", "45000"];
+    day2: 2 [] => ["15", "12"];
+    day3: 3 [] => ["157", "70"];
+    day4: 4 [] => ["2", "4"];
+    day5: 5 [] => ["CMZ", "MCD"];
+    day6: 6 [] => ["7", "19"];
+    day7: 7 [] => ["95437", "24933642"];
+    day8: 8 [] => ["21", "8"];
+    day9: 9 [] => ["13", "1"];
+    day10: 10 [] => ["13140", CRT_EXAMPLE];
+    day11: 11 [] => ["10605", "2713310158"];
+    day12: 12 [] => ["31", "29"];
+    day13: 13 [] => ["13", "140"];
+    day14: 14 [] => ["24", "93"];
+    day15: 15 ["--row", "10", "--max", "20"] => ["26", "56000011"];
+    day16: 16 [] => ["1651", "1707"];
+    day17: 17 [] => ["3068", "1514285714288"];
+    day18: 18 [] => ["64", "58"];
+    day19: 19 ["--quiet"] => ["33", "3472"];
+    day20: 20 [] => ["3", "1623178306"];
+    day21: 21 [] => ["152", "301"];
+    day22: 22 [] => ["6032", "5031"];
+    day23: 23 [] => ["110", "20"];
+    day24: 24 [] => ["18", "54"];
+    day25: 25 [] => ["2=-1=0"];
+}
+
+// Checks every day whose input and accepted answers are present locally.
+// Real inputs can take a while, so run with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn real_inputs() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    for &(day, binary) in BINARIES {
+        let Ok(answers) = fs::read_to_string(root.join(answers_path(day))) else {
+            continue;
+        };
+        if !root.join(input_path(day)).exists() {
+            continue;
+        }
+
+        let expected: Vec<_> = answers.lines().filter(|line| !line.is_empty()).collect();
+        let actual = run(day, binary, &root, &[]);
+
+        for (part, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+            // Images are recorded as the letters they spell, so they can't be compared.
+            if actual.contains('\n') {
+                continue;
+            }
+
+            assert_eq!(actual, expected, "day {} part {}", day, part + 1);
+        }
+    }
+}